@@ -80,12 +80,15 @@
 #let real-funcs = {
   let func-names = (
     "asinh", "acosh", "atanh",
-    "airy_ai", "airy_bi", "gamma", "digamma", "erf", "zeta", "lambert_w"
+    "airy_ai", "airy_bi", "gamma", "digamma", "erf", "lambert_w"
   )
   func-names.map(
     key => (key, convert-wasm-func(key, (float,), float))
   ).to-dict()
-} + {
+} + (
+  // empty bytes leave the number of series terms to the library
+  zeta: x => float.from-bytes((wasm-funcs.zeta)(float.to-bytes(float(x)), bytes(()))),
+) + {
   let func-names = (
     "bessel_jn", "bessel_yn"
   )
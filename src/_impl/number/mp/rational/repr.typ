@@ -25,12 +25,31 @@
   str(math-utils-wasm.mpq_to_str(to-bytes(n), option-flags))
 }
 
+#let /*pub*/ to-unicode-str(
+  n,
+  plus-sign: false,
+  signed-zero: false,
+  signed-inf: false,
+  denom-one: false,
+  hyphen-minus: false,
+) = {
+  let option-flags = build-option-flags(
+    plus-sign,
+    signed-zero,
+    signed-inf,
+    denom-one,
+    hyphen-minus,
+  )
+  str(math-utils-wasm.mpq_to_unicode_fraction(to-bytes(n), option-flags))
+}
+
 #let /*pub*/ to-math(
   n,
   plus-sign: false,
   signed-zero: false,
   signed-inf: false,
   denom-one: false,
+  hyphen-minus: false,
   sign-on-num: false,
   fmt: none,
   display: false,
@@ -40,7 +59,7 @@
     signed-zero,
     signed-inf,
     denom-one,
-    false,
+    hyphen-minus,
   )
   let (sign, num, den) = cbor(math-utils-wasm.mpq_to_math(to-bytes(n), option-flags))
 
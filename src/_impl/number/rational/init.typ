@@ -1,6 +1,7 @@
 // -> number/rational/init.typ
 
 #import "@preview/elembic:1.1.1" as e
+#import "../mp/rational/init.typ": from-bytes as mp-rational-from-bytes
 #let math-utils-wasm = plugin("../../math-utils.wasm")
 
 #let rational = e.types.declare(
@@ -35,7 +36,12 @@
 }
 
 #let /*pub*/ from-bytes(buffer) = {
-  let (sign, num, den) = cbor(buffer)
+  let data = cbor(buffer)
+  // arithmetic results that overflow 64 bits come back as multi-precision rationals
+  if type(data) != dictionary or "sign" not in data {
+    return mp-rational-from-bytes(buffer)
+  }
+  let (sign, num, den) = data
   make-rational(sign, num, den)
 }
 
@@ -2,5 +2,6 @@
 #import "../../../_impl/number/mp/rational/repr.typ": (
   repr,
   to-str,
+  to-unicode-str,
   to-math,
 )
@@ -0,0 +1,187 @@
+use std::{env, fs, path::Path};
+
+use syn::{ExprClosure, Ident, Item, parse::Parser};
+
+// Collects the names and argument counts of every exported plugin function so that
+// `plugin_info` and `has_function` never go out of sync with the source. Each function is
+// recorded with its number of arguments and how many of them are required, the rest being
+// trailing `Option<T>` closure parameters that `define_func!` lets the caller omit.
+
+fn collect_rs_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_rs_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+type FuncInfo = (String, usize, usize);
+
+fn is_option(ty: &syn::Type) -> bool {
+    let syn::Type::Path(type_path) = ty else {
+        return false;
+    };
+    type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Option")
+}
+
+fn parse_define_func(tokens: proc_macro2::TokenStream) -> FuncInfo {
+    let parser = |input: syn::parse::ParseStream| {
+        let func_name: Ident = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let closure: ExprClosure = input.parse()?;
+        // the remaining tokens are flags that do not affect the signature
+        input.parse::<proc_macro2::TokenStream>()?;
+        let n_required = closure
+            .inputs
+            .iter()
+            .rposition(
+                |input| !matches!(input, syn::Pat::Type(pat_type) if is_option(&pat_type.ty)),
+            )
+            .map_or(0, |i| i + 1);
+        Ok((func_name.to_string(), closure.inputs.len(), n_required))
+    };
+    parser.parse2(tokens).unwrap()
+}
+
+/// Macros from outside the crate that are invoked as items but expand to no plugin function.
+const FOREIGN_MACROS: &[&str] = &["flags", "include", "initiate_protocol", "thread_local"];
+
+/// The names of the `macro_rules!` macros defined in `items`, with whether their body
+/// mentions `define_func` or `wasm_func`.
+fn collect_macro_defs(items: &[Item], defs: &mut Vec<(String, bool)>) {
+    for item in items {
+        match item {
+            Item::Macro(item_macro) if item_macro.mac.path.is_ident("macro_rules") => {
+                let Some(ident) = &item_macro.ident else {
+                    continue;
+                };
+                let body = item_macro.mac.tokens.to_string();
+                let exports = body.contains("define_func") || body.contains("wasm_func");
+                defs.push((ident.to_string(), exports));
+            }
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    collect_macro_defs(items, defs);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_funcs(
+    items: &[Item],
+    macro_defs: &[(String, bool)],
+    file: &Path,
+    funcs: &mut Vec<FuncInfo>,
+) {
+    for item in items {
+        match item {
+            Item::Fn(item_fn)
+                if item_fn
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path().is_ident("wasm_func")) =>
+            {
+                let n_args = item_fn.sig.inputs.len();
+                funcs.push((item_fn.sig.ident.to_string(), n_args, n_args));
+            }
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    collect_funcs(items, macro_defs, file, funcs);
+                }
+            }
+            Item::Macro(item_macro) if item_macro.ident.is_none() => {
+                let Some(macro_name) = item_macro.mac.path.get_ident() else {
+                    continue;
+                };
+                let macro_name = macro_name.to_string();
+                let tokens = item_macro.mac.tokens.clone();
+                // helper macros in `lib.rs` that expand to `define_func!`
+                match macro_name.as_str() {
+                    "define_func" => funcs.push(parse_define_func(tokens)),
+                    "define_float_method_func" => {
+                        let method: Ident = syn::parse2(tokens).unwrap();
+                        funcs.push((method.to_string(), 1, 1));
+                    }
+                    "define_complex_method_func" => {
+                        let method: Ident = syn::parse2(tokens).unwrap();
+                        funcs.push((format!("{method}_complex"), 1, 1));
+                    }
+                    "define_dual_method_func" => {
+                        let method: Ident = syn::parse2(tokens).unwrap();
+                        funcs.push((format!("dual_{method}"), 2, 2));
+                    }
+                    "define_method_func_with_complex" => {
+                        let method: Ident = syn::parse2(tokens).unwrap();
+                        funcs.push((method.to_string(), 1, 1));
+                        funcs.push((format!("{method}_complex"), 1, 1));
+                    }
+                    name => {
+                        // any other macro must be known not to export anything, so that a new
+                        // helper cannot silently leave its functions out of `plugin_info`
+                        let local = macro_defs.iter().find(|(def, _)| def == name);
+                        match local {
+                            Some((_, false)) => {}
+                            None if FOREIGN_MACROS.contains(&name) => {}
+                            Some((_, true)) => panic!(
+                                "`{name}!` in {} expands to plugin functions, which build.rs \
+                                 does not know how to list",
+                                file.display()
+                            ),
+                            None => panic!(
+                                "`{name}!` in {} is not known to build.rs; add it to \
+                                 `FOREIGN_MACROS` if it exports no plugin function",
+                                file.display()
+                            ),
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+
+    let mut files = Vec::new();
+    collect_rs_files(Path::new("src"), &mut files);
+
+    let asts: Vec<_> = files
+        .into_iter()
+        .map(|file| {
+            let src = fs::read_to_string(&file).unwrap();
+            (syn::parse_file(&src).unwrap(), file)
+        })
+        .collect();
+    let mut macro_defs = Vec::new();
+    for (ast, _) in &asts {
+        collect_macro_defs(&ast.items, &mut macro_defs);
+    }
+    let mut funcs = Vec::new();
+    for (ast, file) in &asts {
+        collect_funcs(&ast.items, &macro_defs, file, &mut funcs);
+    }
+
+    let mut out = String::from("pub(crate) const PLUGIN_FUNCTIONS: &[(&str, usize, usize)] = &[\n");
+    for (name, n_args, n_required) in funcs {
+        out += &format!("    ({name:?}, {n_args}, {n_required}),\n");
+    }
+    out += "];\n";
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("plugin_functions.rs");
+    fs::write(out_path, out).unwrap();
+}
@@ -0,0 +1,242 @@
+//! Ranking and unranking of combinations and permutations in lexicographic order.
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn,
+    base::num::{
+        arithmetic::traits::{BinomialCoefficient, Factorial},
+        basic::traits::Zero,
+    },
+};
+
+/// The largest `n` and `k` accepted, which keeps the lists and the factorials computed here to a
+/// manageable size.
+pub const MAX_SIZE: u64 = 1 << 16;
+
+fn check_size(name: &str, value: u64) -> Result<(), anyhow::Error> {
+    if value > MAX_SIZE {
+        bail!("{name} = {value} is above the limit of {MAX_SIZE}");
+    }
+    Ok(())
+}
+
+fn binom(n: u64, k: u64) -> Mpn {
+    if k > n {
+        Mpn::ZERO
+    } else {
+        Mpn::binomial_coefficient(Mpn::from(n), Mpn::from(k))
+    }
+}
+
+/// Checks that `0 ≤ rank < count` and converts it to a natural number.
+fn check_rank(rank: &Mpz, count: &Mpn) -> Result<Mpn, anyhow::Error> {
+    match Mpn::try_from(rank) {
+        Ok(rank) if rank < *count => Ok(rank),
+        _ => bail!("rank {rank} is out of range [0, {count})"),
+    }
+}
+
+/// The `rank`-th `k`-element subset of `{0, …, n - 1}` in lexicographic order, as a list of
+/// strictly increasing indices.
+pub fn combination_unrank(n: u64, k: u64, rank: &Mpz) -> Result<Vec<u64>, anyhow::Error> {
+    check_size("n", n)?;
+    check_size("k", k)?;
+    let mut rank = check_rank(rank, &binom(n, k))?;
+    let mut indices = Vec::with_capacity(k as usize);
+    let mut x = 0;
+    for i in 0..k {
+        // there are binom(n - x - 1, k - i - 1) combinations continuing with x at position i
+        loop {
+            let count = binom(n - x - 1, k - i - 1);
+            if rank < count {
+                break;
+            }
+            rank -= count;
+            x += 1;
+        }
+        indices.push(x);
+        x += 1;
+    }
+    Ok(indices)
+}
+
+/// The lexicographic rank of a `k`-element subset of `{0, …, n - 1}` given as strictly
+/// increasing indices. This inverts [`combination_unrank`].
+pub fn combination_rank(n: u64, indices: &[u64]) -> Result<Mpn, anyhow::Error> {
+    check_size("n", n)?;
+    if indices.windows(2).any(|w| w[0] >= w[1]) {
+        bail!("indices must be strictly increasing");
+    }
+    if let Some(&last) = indices.last()
+        && last >= n
+    {
+        bail!("index {last} is out of range [0, {n})");
+    }
+    let k = indices.len() as u64;
+    let mut rank = Mpn::ZERO;
+    let mut start = 0;
+    for (i, &x) in (0..).zip(indices) {
+        // skip every combination continuing with start, …, x - 1 at position i; by the
+        // hockey-stick identity their count is binom(n - start, k - i) - binom(n - x, k - i)
+        rank += binom(n - start, k - i) - binom(n - x, k - i);
+        start = x + 1;
+    }
+    Ok(rank)
+}
+
+/// The `rank`-th permutation of `{0, …, n - 1}` in lexicographic order, read off from the
+/// digits of `rank` in the factorial number system.
+pub fn permutation_unrank(n: u64, rank: &Mpz) -> Result<Vec<u64>, anyhow::Error> {
+    check_size("n", n)?;
+    let mut rank = check_rank(rank, &Mpn::factorial(n))?;
+    // the digits least significant first, where the i-th is ⌊rank / i!⌋ mod (i + 1)
+    let mut digits = Vec::with_capacity(n as usize);
+    for base in 1..=n {
+        let base = Mpn::from(base);
+        digits.push(u64::try_from(&(&rank % &base)).unwrap());
+        rank /= base;
+    }
+    let mut remaining: Vec<u64> = (0..n).collect();
+    Ok(digits
+        .into_iter()
+        .rev()
+        .map(|digit| remaining.remove(digit as usize))
+        .collect())
+}
+
+/// The lexicographic rank of a permutation of `{0, …, n - 1}`. This inverts
+/// [`permutation_unrank`].
+pub fn permutation_rank(permutation: &[u64]) -> Result<Mpn, anyhow::Error> {
+    check_size("n", permutation.len() as u64)?;
+    let n = permutation.len();
+    let mut seen = vec![false; n];
+    for &x in permutation {
+        if x as usize >= n || seen[x as usize] {
+            bail!("indices must be a permutation of 0, …, {}", n - 1);
+        }
+        seen[x as usize] = true;
+    }
+    // the i-th factorial digit counts the later entries smaller than the i-th one
+    let mut rank = Mpn::ZERO;
+    for (i, &x) in permutation.iter().enumerate() {
+        let digit = permutation[i + 1..].iter().filter(|&&y| y < x).count();
+        rank = rank * Mpn::from(n - i) + Mpn::from(digit);
+    }
+    Ok(rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// xorshift64*, enough to pick test cases reproducibly.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        /// A natural number below `n`, nearly uniform for the sizes used here.
+        fn below_mpn(&mut self, n: &Mpn) -> Mpn {
+            let mut x = Mpn::ZERO;
+            while x < n << 64u32 {
+                x = (x << 64u32) + Mpn::from(self.next());
+            }
+            x % n
+        }
+    }
+
+    #[test]
+    fn combinations_in_order() {
+        let all: Vec<_> = (0..10)
+            .map(|rank| combination_unrank(5, 2, &Mpz::from(rank)).unwrap())
+            .collect();
+        assert_eq!(all[0], [0, 1]);
+        assert_eq!(all[4], [1, 2]);
+        assert_eq!(all[9], [3, 4]);
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+        assert!(combination_unrank(0, 0, &Mpz::ZERO).unwrap().is_empty());
+    }
+
+    #[test]
+    fn permutations_in_order() {
+        let all: Vec<_> = (0..24)
+            .map(|rank| permutation_unrank(4, &Mpz::from(rank)).unwrap())
+            .collect();
+        assert_eq!(all[0], [0, 1, 2, 3]);
+        assert_eq!(all[1], [0, 1, 3, 2]);
+        assert_eq!(all[23], [3, 2, 1, 0]);
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn combination_round_trip() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..300 {
+            let n = rng.below(101);
+            let k = rng.below(n + 1);
+            let rank = rng.below_mpn(&binom(n, k));
+            let indices = combination_unrank(n, k, &Mpz::from(&rank)).unwrap();
+            assert_eq!(indices.len() as u64, k);
+            assert_eq!(combination_rank(n, &indices).unwrap(), rank);
+        }
+    }
+
+    #[test]
+    fn permutation_round_trip() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..300 {
+            let n = rng.below(101);
+            let rank = rng.below_mpn(&Mpn::factorial(n));
+            let permutation = permutation_unrank(n, &Mpz::from(&rank)).unwrap();
+            assert_eq!(permutation_rank(&permutation).unwrap(), rank);
+        }
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let error = combination_unrank(5, 2, &Mpz::from(10)).unwrap_err();
+        assert_eq!(error.to_string(), "rank 10 is out of range [0, 10)");
+        assert!(combination_unrank(5, 2, &Mpz::from(-1)).is_err());
+        assert!(combination_unrank(5, 6, &Mpz::ZERO).is_err());
+        let error = permutation_unrank(3, &Mpz::from(6)).unwrap_err();
+        assert_eq!(error.to_string(), "rank 6 is out of range [0, 6)");
+        assert!(combination_rank(5, &[1, 1]).is_err());
+        assert!(combination_rank(5, &[2, 1]).is_err());
+        assert!(combination_rank(5, &[1, 5]).is_err());
+        assert!(permutation_rank(&[0, 2, 2]).is_err());
+        assert!(permutation_rank(&[0, 3, 1]).is_err());
+    }
+
+    #[test]
+    fn sizes_are_limited() {
+        // binom(n, n) = 1, so the rank alone cannot catch these
+        let error = combination_unrank(u64::MAX, u64::MAX, &Mpz::ZERO).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("n = {} is above the limit of {MAX_SIZE}", u64::MAX)
+        );
+        let error = combination_unrank(MAX_SIZE, MAX_SIZE + 1, &Mpz::ZERO).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("k = {} is above the limit of {MAX_SIZE}", MAX_SIZE + 1)
+        );
+        let all = combination_unrank(MAX_SIZE, MAX_SIZE, &Mpz::ZERO).unwrap();
+        assert_eq!(all.len() as u64, MAX_SIZE);
+        assert!(combination_rank(MAX_SIZE + 1, &[0]).is_err());
+        assert!(permutation_unrank(MAX_SIZE + 1, &Mpz::ZERO).is_err());
+        assert!(permutation_unrank(u64::MAX, &Mpz::ZERO).is_err());
+        let identity: Vec<u64> = (0..1000).collect();
+        assert_eq!(permutation_unrank(1000, &Mpz::ZERO).unwrap(), identity);
+        let too_long: Vec<u64> = (0..=MAX_SIZE).collect();
+        assert!(permutation_rank(&too_long).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Divisors and the multiplicative functions defined through them, computed from the prime
+//! factorization.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use anyhow::bail;
+use malachite::{
+    Natural as Mpn,
+    base::num::{
+        arithmetic::traits::{DivExact, Pow},
+        basic::traits::{One, Zero},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::primes;
+
+fn factorize_positive(n: &Mpn) -> Result<Vec<(Mpn, u64)>, anyhow::Error> {
+    if *n == 0u32 {
+        bail!("only positive integers have divisors");
+    }
+    primes::factorize_bounded(n)
+}
+
+/// Euler's totient φ(n), the number of `k` in `[1, n]` coprime to `n`.
+pub fn euler_phi(n: &Mpn) -> Result<Mpn, anyhow::Error> {
+    Ok(factorize_positive(n)?
+        .into_iter()
+        .map(|(p, e)| (&p - Mpn::ONE) * (&p).pow(e - 1))
+        .product())
+}
+
+/// The Möbius function μ(n): 0 if `n` has a square factor and `(-1)ᵏ` for a product of `k`
+/// distinct primes.
+pub fn mobius(n: &Mpn) -> Result<i8, anyhow::Error> {
+    let factors = factorize_positive(n)?;
+    if factors.iter().any(|&(_, e)| e > 1) {
+        return Ok(0);
+    }
+    Ok(if factors.len() % 2 == 0 { 1 } else { -1 })
+}
+
+fn sigma_of_factors(factors: Vec<(Mpn, u64)>, k: u64) -> Mpn {
+    factors
+        .into_iter()
+        .map(|(p, e)| {
+            if k == 0 {
+                return Mpn::from(e + 1);
+            }
+            // 1 + pᵏ + p²ᵏ + … + pᵉᵏ
+            let q = p.pow(k);
+            ((&q).pow(e + 1) - Mpn::ONE).div_exact(q - Mpn::ONE)
+        })
+        .product()
+}
+
+/// The divisor function σₖ(n), the sum of the `k`-th powers of the divisors of `n`; σ₀ counts
+/// them.
+pub fn sigma(n: &Mpn, k: u64) -> Result<Mpn, anyhow::Error> {
+    Ok(sigma_of_factors(factorize_positive(n)?, k))
+}
+
+/// The divisors of `n` in increasing order, or an error if there are more than `max_count`.
+pub fn divisors(n: &Mpn, max_count: u64) -> Result<Vec<Mpn>, anyhow::Error> {
+    let factors = factorize_positive(n)?;
+    let count: Mpn = factors.iter().map(|&(_, e)| Mpn::from(e + 1)).product();
+    if count > max_count {
+        bail!("{n} has {count} divisors, more than the limit of {max_count}");
+    }
+    let mut divisors = vec![Mpn::ONE];
+    for (p, e) in factors {
+        let mut next = Vec::with_capacity(divisors.len() * (e as usize + 1));
+        let mut power = Mpn::ONE;
+        for _ in 0..=e {
+            next.extend(divisors.iter().map(|d| d * &power));
+            power *= &p;
+        }
+        divisors = next;
+    }
+    divisors.sort();
+    Ok(divisors)
+}
+
+/// `s(n) = σ₁(n) - n` for positive `n`, or `None` if `n` cannot be factored within
+/// [`primes::FACTOR_STEPS`].
+fn try_aliquot_sum(n: &Mpn) -> Option<Mpn> {
+    let factors = primes::factorize_bounded(n).ok()?;
+    Some(sigma_of_factors(factors, 1) - n)
+}
+
+/// The aliquot sum `s(n)`, the sum of the proper divisors of `n`.
+pub fn aliquot_sum(n: &Mpn) -> Result<Mpn, anyhow::Error> {
+    Ok(sigma_of_factors(factorize_positive(n)?, 1) - n)
+}
+
+/// How `s(n)` compares to `n`: less for deficient numbers, equal for perfect numbers and greater
+/// for abundant numbers.
+pub fn abundance(n: &Mpn) -> Result<Ordering, anyhow::Error> {
+    Ok(aliquot_sum(n)?.cmp(n))
+}
+
+/// The partner `m = s(n) ≠ n` with `s(m) = n` if `n` is one of an amicable pair.
+pub fn amicable_partner(n: &Mpn) -> Result<Option<Mpn>, anyhow::Error> {
+    let m = aliquot_sum(n)?;
+    if m == *n || m == 0u32 {
+        return Ok(None);
+    }
+    Ok((aliquot_sum(&m)? == *n).then_some(m))
+}
+
+/// Why an aliquot sequence stopped.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum AliquotEnd {
+    /// The sequence reached 1 and then 0.
+    Terminated,
+    /// The last `length` terms repeat forever: a perfect number for length 1, an amicable pair
+    /// for length 2 and a sociable cycle otherwise.
+    Cycle { length: u64 },
+    /// The step limit was reached.
+    StepLimit,
+    /// The last term could not be factored within [`primes::FACTOR_STEPS`], so its successor is
+    /// unknown.
+    Unfactored,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AliquotSequence {
+    pub terms: Vec<Mpn>,
+    pub end: AliquotEnd,
+}
+
+/// The aliquot sequence `n, s(n), s(s(n)), …` with at most `max_steps` terms after `n`.
+pub fn aliquot_sequence(n: &Mpn, max_steps: u64) -> Result<AliquotSequence, anyhow::Error> {
+    if *n == 0u32 {
+        bail!("only positive integers have divisors");
+    }
+    let mut terms = vec![n.clone()];
+    let mut seen = HashMap::from([(n.clone(), 0)]);
+    let finish = |terms, end| Ok(AliquotSequence { terms, end });
+    for _ in 0..max_steps {
+        let last = terms.last().unwrap();
+        if *last == 0u32 {
+            return finish(terms, AliquotEnd::Terminated);
+        }
+        let next = if *last == 1u32 {
+            Mpn::ZERO
+        } else {
+            match try_aliquot_sum(last) {
+                Some(next) => next,
+                None => return finish(terms, AliquotEnd::Unfactored),
+            }
+        };
+        if let Some(&start) = seen.get(&next) {
+            let length = (terms.len() - start) as u64;
+            return finish(terms, AliquotEnd::Cycle { length });
+        }
+        seen.insert(next.clone(), terms.len());
+        terms.push(next);
+    }
+    let end = if *terms.last().unwrap() == 0u32 {
+        AliquotEnd::Terminated
+    } else {
+        AliquotEnd::StepLimit
+    };
+    finish(terms, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::base::num::arithmetic::traits::Gcd;
+
+    use super::*;
+
+    #[test]
+    fn multiplicative_on_coprime_pairs() {
+        let n = |x: u64| Mpn::from(x);
+        for a in 1..60u64 {
+            for b in 1..60u64 {
+                if n(a).gcd(n(b)) != 1u32 {
+                    continue;
+                }
+                let ab = n(a * b);
+                assert_eq!(
+                    euler_phi(&ab).unwrap(),
+                    euler_phi(&n(a)).unwrap() * euler_phi(&n(b)).unwrap()
+                );
+                assert_eq!(
+                    mobius(&ab).unwrap(),
+                    mobius(&n(a)).unwrap() * mobius(&n(b)).unwrap()
+                );
+                for k in 0..3 {
+                    assert_eq!(
+                        sigma(&ab, k).unwrap(),
+                        sigma(&n(a), k).unwrap() * sigma(&n(b), k).unwrap()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_values() {
+        let n = |x: u64| Mpn::from(x);
+        assert_eq!(euler_phi(&n(1)).unwrap(), 1u32);
+        assert_eq!(mobius(&n(1)).unwrap(), 1);
+        assert_eq!(mobius(&n(12)).unwrap(), 0);
+        assert_eq!(mobius(&n(30)).unwrap(), -1);
+        assert_eq!(sigma(&n(12), 0).unwrap(), 6u32);
+        assert_eq!(sigma(&n(12), 2).unwrap(), 1 + 4 + 9 + 16 + 36 + 144u32);
+        let divisors_of_12: Vec<Mpn> = [1, 2, 3, 4, 6, 12].map(n).into();
+        assert_eq!(divisors(&n(12), 6).unwrap(), divisors_of_12);
+        assert_eq!(
+            divisors(&n(12), 5).unwrap_err().to_string(),
+            "12 has 6 divisors, more than the limit of 5"
+        );
+        assert!(euler_phi(&Mpn::ZERO).is_err());
+        assert_eq!(aliquot_sum(&n(28)).unwrap(), 28u32);
+    }
+
+    #[test]
+    fn large_cases() {
+        let p = Mpn::from(1_000_000_000_000_000_009u64);
+        assert_eq!(euler_phi(&p).unwrap(), &p - Mpn::ONE);
+        let q = Mpn::from(1_000_000_007u64);
+        assert_eq!(
+            euler_phi(&(&p * &q)).unwrap(),
+            (&p - Mpn::ONE) * (&q - Mpn::ONE)
+        );
+        assert_eq!(sigma(&(&p * &q), 0).unwrap(), 4u32);
+    }
+
+    #[test]
+    fn perfect_and_amicable_numbers() {
+        let n = |x: u64| Mpn::from(x);
+        for perfect in [6, 28, 496, 8128, 33550336] {
+            assert_eq!(abundance(&n(perfect)).unwrap(), Ordering::Equal);
+            assert_eq!(amicable_partner(&n(perfect)).unwrap(), None);
+        }
+        assert_eq!(abundance(&n(12)).unwrap(), Ordering::Greater);
+        assert_eq!(abundance(&n(1)).unwrap(), Ordering::Less);
+        assert_eq!(abundance(&n(13)).unwrap(), Ordering::Less);
+        assert_eq!(amicable_partner(&n(220)).unwrap(), Some(n(284)));
+        assert_eq!(amicable_partner(&n(284)).unwrap(), Some(n(220)));
+        assert_eq!(amicable_partner(&n(1)).unwrap(), None);
+        assert_eq!(amicable_partner(&n(12496)).unwrap(), None);
+    }
+
+    #[test]
+    fn aliquot_sequences() {
+        let n = |x: u64| Mpn::from(x);
+        let opening_of_276: Vec<Mpn> = [
+            276, 396, 696, 1104, 1872, 3770, 3790, 3050, 2716, 2772, 5964, 10164, 19628, 19684,
+            22876, 26404, 30044, 33796, 38780, 54628, 54684,
+        ]
+        .map(n)
+        .into();
+        let sequence = aliquot_sequence(&n(276), 20).unwrap();
+        assert_eq!(sequence.terms, opening_of_276);
+        assert_eq!(sequence.end, AliquotEnd::StepLimit);
+
+        let sequence = aliquot_sequence(&n(10), 100).unwrap();
+        assert_eq!(sequence.terms, [10, 8, 7, 1, 0].map(n));
+        assert_eq!(sequence.end, AliquotEnd::Terminated);
+        // reaching 0 on the last allowed step still counts as terminating
+        let sequence = aliquot_sequence(&n(10), 4).unwrap();
+        assert_eq!(sequence.end, AliquotEnd::Terminated);
+
+        let sequence = aliquot_sequence(&n(95), 100).unwrap();
+        assert_eq!(sequence.terms, [95, 25, 6].map(n));
+        assert_eq!(sequence.end, AliquotEnd::Cycle { length: 1 });
+        let sequence = aliquot_sequence(&n(220), 100).unwrap();
+        assert_eq!(sequence.end, AliquotEnd::Cycle { length: 2 });
+        // the sociable cycle 12496 → 14288 → 15472 → 14536 → 14264 → 12496
+        let sequence = aliquot_sequence(&n(12496), 100).unwrap();
+        assert_eq!(sequence.terms.len(), 5);
+        assert_eq!(sequence.end, AliquotEnd::Cycle { length: 5 });
+
+        let sequence = aliquot_sequence(&n(276), 0).unwrap();
+        assert_eq!(sequence.terms, [n(276)]);
+        assert_eq!(sequence.end, AliquotEnd::StepLimit);
+        assert!(aliquot_sequence(&Mpn::ZERO, 10).is_err());
+    }
+}
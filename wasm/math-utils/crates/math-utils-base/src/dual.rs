@@ -0,0 +1,169 @@
+use std::ops::*;
+
+use num::Float;
+
+/// A dual number `re + du ε` with `ε² = 0`. Evaluating a function at `x + ε` gives `f(x) + f'(x) ε`,
+/// so the dual part carries the derivative through each operation by the chain rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dual<T> {
+    pub re: T,
+    pub du: T,
+}
+
+impl<T> Dual<T> {
+    pub fn new(re: T, du: T) -> Self {
+        Self { re, du }
+    }
+}
+
+impl<T: Float> Dual<T> {
+    /// The constant `c`, whose derivative is zero.
+    pub fn constant(c: T) -> Self {
+        Self::new(c, T::zero())
+    }
+
+    /// The independent variable at `x`, whose derivative is one.
+    pub fn variable(x: T) -> Self {
+        Self::new(x, T::one())
+    }
+
+    /// Applies a function with value `f` and derivative `df` at `self.re`.
+    fn chain(self, f: T, df: T) -> Self {
+        Self::new(f, df * self.du)
+    }
+
+    pub fn sin(self) -> Self {
+        self.chain(self.re.sin(), self.re.cos())
+    }
+
+    pub fn cos(self) -> Self {
+        self.chain(self.re.cos(), -self.re.sin())
+    }
+
+    pub fn tan(self) -> Self {
+        let t = self.re.tan();
+        self.chain(t, T::one() + t * t)
+    }
+
+    pub fn exp(self) -> Self {
+        let e = self.re.exp();
+        self.chain(e, e)
+    }
+
+    pub fn ln(self) -> Self {
+        self.chain(self.re.ln(), self.re.recip())
+    }
+
+    pub fn sqrt(self) -> Self {
+        let s = self.re.sqrt();
+        self.chain(s, (s + s).recip())
+    }
+
+    pub fn powf(self, n: T) -> Self {
+        self.chain(self.re.powf(n), n * self.re.powf(n - T::one()))
+    }
+
+    pub fn recip(self) -> Self {
+        let r = self.re.recip();
+        self.chain(r, -r * r)
+    }
+}
+
+impl<T: Float> Neg for Dual<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.re, -self.du)
+    }
+}
+
+impl<T: Float> Add for Dual<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.re + rhs.re, self.du + rhs.du)
+    }
+}
+
+impl<T: Float> Sub for Dual<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.re - rhs.re, self.du - rhs.du)
+    }
+}
+
+impl<T: Float> Mul for Dual<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // (a + bε)(c + dε) = ac + (ad + bc)ε
+        Self::new(self.re * rhs.re, self.re * rhs.du + self.du * rhs.re)
+    }
+}
+
+impl<T: Float> Div for Dual<T> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        // (a + bε) / (c + dε) = a / c + (bc - ad) / c² ε
+        Self::new(
+            self.re / rhs.re,
+            (self.du * rhs.re - self.re * rhs.du) / (rhs.re * rhs.re),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{E, FRAC_PI_3, FRAC_PI_6, LN_2};
+
+    use super::*;
+
+    fn assert_dual(x: Dual<f64>, re: f64, du: f64) {
+        assert!(
+            (x.re - re).abs() <= 1e-15 * re.abs().max(1.0)
+                && (x.du - du).abs() <= 1e-15 * du.abs().max(1.0),
+            "{x:?} vs ({re}, {du})"
+        );
+    }
+
+    #[test]
+    fn elementary_derivatives() {
+        // sin' = cos and cos' = -sin at π/6, where sin = 1/2 and cos = √3/2
+        let half_root3 = 3f64.sqrt() / 2.0;
+        assert_dual(Dual::variable(FRAC_PI_6).sin(), 0.5, half_root3);
+        assert_dual(Dual::variable(FRAC_PI_6).cos(), half_root3, -0.5);
+        // tan' = 1 + tan² = 4 at π/3
+        assert_dual(Dual::variable(FRAC_PI_3).tan(), 3f64.sqrt(), 4.0);
+        assert_dual(Dual::variable(1.0).exp(), E, E);
+        assert_dual(Dual::variable(2.0).ln(), LN_2, 0.5);
+        assert_dual(Dual::variable(4.0).sqrt(), 2.0, 0.25);
+        assert_dual(Dual::variable(2.0).powf(3.0), 8.0, 12.0);
+        assert_dual(Dual::variable(4.0).recip(), 0.25, -0.0625);
+        assert_dual(Dual::constant(2.0).sin(), 2f64.sin(), 0.0);
+    }
+
+    #[test]
+    fn chain_rule() {
+        // d/dx sin(x²) = 2x cos(x²) and d/dx exp(ln x) = 1
+        let x = Dual::variable(1.5);
+        assert_dual((x * x).sin(), 2.25f64.sin(), 3.0 * 2.25f64.cos());
+        assert_dual(x.ln().exp(), 1.5, 1.0);
+        assert_dual(-(x - Dual::constant(1.0)), -0.5, -1.0);
+    }
+
+    #[test]
+    fn quotient_rule() {
+        // (x / (x² + 1))' = (1 - x²) / (x² + 1)², which is -3/25 at x = 2
+        let x = Dual::variable(2.0);
+        let q = x / (x * x + Dual::constant(1.0));
+        assert_dual(q, 0.4, -0.12);
+        // (sin / cos)' = 1 / cos² agrees with tan'
+        let x = Dual::variable(0.7);
+        let q = x.sin() / x.cos();
+        let t = x.tan();
+        assert!((q.re - t.re).abs() < 1e-15 && (q.du - t.du).abs() < 1e-14);
+        assert_dual(Dual::constant(3.0) / Dual::variable(2.0), 1.5, -0.75);
+    }
+}
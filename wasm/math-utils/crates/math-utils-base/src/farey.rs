@@ -0,0 +1,263 @@
+//! Farey sequences and the Stern–Brocot tree.
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn, Rational as Mpq,
+    base::num::basic::traits::{One, Zero},
+    rational::{
+        arithmetic::traits::SimplestRationalInInterval, conversion::traits::ContinuedFraction,
+    },
+};
+
+/// The largest order accepted by [`sequence`], whose output grows like `3n² / π²`.
+pub const MAX_SEQUENCE_ORDER: u64 = 10000;
+
+/// The Farey sequence `Fₙ` in increasing order. Each term after two adjacent terms `a/b < c/d`
+/// is `(kc - a) / (kd - b)` with `k = ⌊(n + b) / d⌋`.
+pub fn sequence(n: u64) -> Result<Vec<Mpq>, anyhow::Error> {
+    if n == 0 {
+        bail!("the order of a Farey sequence must be positive");
+    }
+    if n > MAX_SEQUENCE_ORDER {
+        bail!("Farey sequences of order above {MAX_SEQUENCE_ORDER} are not supported");
+    }
+    let (mut a, mut b, mut c, mut d) = (0, 1, 1, n);
+    let mut terms = vec![Mpq::ZERO];
+    while c <= n {
+        terms.push(Mpq::from_naturals(Mpn::from(c), Mpn::from(d)));
+        let k = (n + b) / d;
+        (a, b, c, d) = (c, d, k * c - a, k * d - b);
+    }
+    Ok(terms)
+}
+
+/// `(a + c) / (b + d)` for `a/b` and `c/d` in lowest terms.
+pub fn mediant(x: &Mpq, y: &Mpq) -> Mpq {
+    let numerator = Mpz::from_sign_and_abs_ref(*x >= 0u32, x.numerator_ref())
+        + Mpz::from_sign_and_abs_ref(*y >= 0u32, y.numerator_ref());
+    Mpq::from_integers(
+        numerator,
+        Mpz::from(x.denominator_ref() + y.denominator_ref()),
+    )
+}
+
+/// The fractions adjacent to `x` in the Farey sequence `Fₙ`, which consists of the reduced
+/// fractions in `[0, 1]` with denominators at most `n`. If `x` is not in `Fₙ` these are the
+/// members of `Fₙ` enclosing it. There is no left neighbor of 0 and no right neighbor of 1.
+pub fn neighbors(x: &Mpq, n: &Mpn) -> Result<(Option<Mpq>, Option<Mpq>), anyhow::Error> {
+    if *n == 0u32 {
+        bail!("the order of a Farey sequence must be positive");
+    }
+    if *x < 0u32 || *x > 1u32 {
+        bail!("{x} is out of range [0, 1]");
+    }
+    let fraction = |p: Mpn, q: Mpn| Mpq::from_naturals(p, q);
+    if *x == 0u32 {
+        return Ok((None, Some(fraction(Mpn::ONE, n.clone()))));
+    }
+    if *x == 1u32 {
+        return Ok((Some(fraction(n - Mpn::ONE, n.clone())), None));
+    }
+    let (p, q) = x.numerator_and_denominator_ref();
+    // descend the Stern–Brocot tree between a/b = 0/1 and c/d = 1/1, which always stay adjacent
+    // (bc - ad = 1), taking each run of steps in one direction at once
+    let (mut a, mut b, mut c, mut d) = (Mpn::ZERO, Mpn::ONE, Mpn::ONE, Mpn::ONE);
+    loop {
+        let (m, k) = (&a + &c, &b + &d);
+        if k > *n {
+            // every fraction strictly between a/b and c/d has a denominator of at least b + d
+            return Ok((Some(fraction(a, b)), Some(fraction(c, d))));
+        }
+        // compare x = p/q with the mediant m/k
+        let (lhs, rhs) = (p * &k, &m * q);
+        if lhs == rhs {
+            // x is in Fₙ and its neighbors are the farthest fractions a/b + j·x within the order
+            let j = (n - &b) / q;
+            let left = fraction(&a + &j * p, &b + &j * q);
+            let j = (n - &d) / q;
+            let right = fraction(&c + &j * p, &d + &j * q);
+            return Ok((Some(left), Some(right)));
+        }
+        if lhs > rhs {
+            // move a/b toward x: (a + j·c) / (b + j·d) stays below x for j·(cq - pd) < pb - aq,
+            // and stays within the order for b + j·d ≤ n
+            let (gap, step) = (p * &b - &a * q, &c * q - p * &d);
+            let j = ((gap - Mpn::ONE) / step).min((n - &b) / &d);
+            a += &j * &c;
+            b += j * &d;
+        } else {
+            let (gap, step) = (&c * q - p * &d, p * &b - &a * q);
+            let j = ((gap - Mpn::ONE) / step).min((n - &d) / &b);
+            c += &j * &a;
+            d += j * &b;
+        }
+    }
+}
+
+/// The simplest rational in `(x - eps, x + eps)`, i.e. the one with the least denominator, which
+/// is also the first such fraction met when descending the Stern–Brocot tree toward `x`.
+pub fn simplest_within(x: &Mpq, eps: &Mpq) -> Result<Mpq, anyhow::Error> {
+    if *eps <= 0u32 {
+        bail!("the tolerance must be positive, but got {eps}");
+    }
+    Ok(Mpq::simplest_rational_in_open_interval(
+        &(x - eps),
+        &(x + eps),
+    ))
+}
+
+/// The moves from the root `1/1` of the Stern–Brocot tree to the positive rational `x`, as runs
+/// of `(right, length)`. They follow from the continued fraction `[a₀; a₁, …, aₙ]` of `x` as
+/// `Rᵃ⁰ Lᵃ¹ Rᵃ² …` with the last run shortened by one.
+pub fn stern_brocot_runs(x: &Mpq) -> Result<Vec<(bool, Mpn)>, anyhow::Error> {
+    if *x <= 0u32 {
+        bail!("only positive rationals are in the Stern–Brocot tree");
+    }
+    let (a0, rest) = x.continued_fraction();
+    let mut runs: Vec<(bool, Mpn)> = std::iter::once(Mpn::try_from(a0).unwrap())
+        .chain(rest)
+        .enumerate()
+        .map(|(i, a)| (i % 2 == 0, a))
+        .collect();
+    let last = &mut runs.last_mut().unwrap().1;
+    *last -= Mpn::ONE;
+    runs.retain(|(_, len)| *len != 0u32);
+    Ok(runs)
+}
+
+/// The path to `x` in the Stern–Brocot tree as a string of `L` and `R`, cut off after `max_len`
+/// moves. The flag tells whether the path was cut off.
+pub fn stern_brocot_path(x: &Mpq, max_len: usize) -> Result<(String, bool), anyhow::Error> {
+    let mut path = String::new();
+    for (right, len) in stern_brocot_runs(x)? {
+        let room = max_len - path.len();
+        let take = usize::try_from(&len).map_or(room, |len| len.min(room));
+        path.extend(std::iter::repeat_n(if right { 'R' } else { 'L' }, take));
+        if len > take {
+            return Ok((path, true));
+        }
+    }
+    Ok((path, false))
+}
+
+/// The node of the Stern–Brocot tree reached from the root `1/1` by `moves`, where `true` is a
+/// step to the right.
+pub fn from_stern_brocot_moves(moves: impl IntoIterator<Item = bool>) -> Mpq {
+    // the node is the mediant of the nearest ancestors a/b on its left and c/d on its right
+    let (mut a, mut b, mut c, mut d) = (Mpn::ZERO, Mpn::ONE, Mpn::ONE, Mpn::ZERO);
+    for right in moves {
+        if right {
+            a += &c;
+            b += &d;
+        } else {
+            c += &a;
+            d += &b;
+        }
+    }
+    Mpq::from_naturals(a + c, b + d)
+}
+
+/// The inverse of [`stern_brocot_path`]; the empty path leads to the root `1/1`.
+pub fn from_stern_brocot_path(path: &str) -> Result<Mpq, anyhow::Error> {
+    let moves = path
+        .chars()
+        .map(|c| match c {
+            'L' => Ok(false),
+            'R' => Ok(true),
+            _ => bail!("invalid move {c:?} in a Stern–Brocot path; expected 'L' or 'R'"),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(from_stern_brocot_moves(moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(n: u64, d: u64) -> Mpq {
+        Mpq::from_unsigneds(n, d)
+    }
+
+    /// `b·c - a·d` for `a/b` and `c/d`, which is 1 for Farey neighbors `a/b < c/d`.
+    fn determinant(x: &Mpq, y: &Mpq) -> Mpz {
+        let (a, b) = x.numerator_and_denominator_ref();
+        let (c, d) = y.numerator_and_denominator_ref();
+        Mpz::from(b * c) - Mpz::from(a * d)
+    }
+
+    #[test]
+    fn paths_round_trip() {
+        for d in 1..=30 {
+            for n in 1..=30 {
+                let x = q(n, d);
+                let (path, truncated) = stern_brocot_path(&x, 100).unwrap();
+                assert!(!truncated);
+                assert_eq!(from_stern_brocot_path(&path).unwrap(), x, "{path}");
+            }
+        }
+        assert_eq!(
+            stern_brocot_path(&q(1, 1), 10).unwrap(),
+            (String::new(), false)
+        );
+        assert_eq!(stern_brocot_path(&q(3, 7), 10).unwrap().0, "LLRR");
+        assert_eq!(stern_brocot_path(&q(5, 2), 10).unwrap().0, "RRL");
+        // the path to n is n - 1 steps right, which is cut off without being built in full
+        let huge = Mpq::from(u64::MAX);
+        assert_eq!(
+            stern_brocot_path(&huge, 5).unwrap(),
+            ("RRRRR".to_string(), true)
+        );
+        assert_eq!(
+            stern_brocot_path(&q(3, 7), 3).unwrap(),
+            ("LLR".to_string(), true)
+        );
+        assert!(stern_brocot_path(&Mpq::ZERO, 10).is_err());
+        assert!(stern_brocot_path(&-q(1, 2), 10).is_err());
+        assert!(from_stern_brocot_path("LRX").is_err());
+    }
+
+    #[test]
+    fn neighbors_are_adjacent() {
+        for n in 1..=12u64 {
+            let terms = sequence(n).unwrap();
+            for (i, x) in terms.iter().enumerate() {
+                let (left, right) = neighbors(x, &Mpn::from(n)).unwrap();
+                assert_eq!(left.as_ref(), i.checked_sub(1).map(|i| &terms[i]));
+                assert_eq!(right.as_ref(), terms.get(i + 1));
+                if let Some(left) = &left {
+                    assert_eq!(determinant(left, x), 1);
+                }
+                if let Some(right) = &right {
+                    assert_eq!(determinant(x, right), 1);
+                }
+            }
+        }
+        // a fraction outside the sequence lies between its neighbors, which are adjacent
+        let x = q(355, 1131);
+        let n = Mpn::from(100u32);
+        let (Some(left), Some(right)) = neighbors(&x, &n).unwrap() else {
+            panic!("{x} has neighbors on both sides");
+        };
+        assert!(left < x && x < right);
+        assert_eq!(determinant(&left, &right), 1);
+        let n = Mpn::from(10u64.pow(18));
+        let (Some(left), Some(right)) = neighbors(&q(1, 3), &n).unwrap() else {
+            panic!("1/3 has neighbors on both sides");
+        };
+        assert_eq!(determinant(&left, &q(1, 3)), 1);
+        assert_eq!(determinant(&q(1, 3), &right), 1);
+        assert!(*left.denominator_ref() > 10u64.pow(18) - 3);
+        assert!(neighbors(&q(3, 2), &n).is_err());
+        assert!(neighbors(&q(1, 2), &Mpn::ZERO).is_err());
+    }
+
+    #[test]
+    fn mediants_and_sequences() {
+        assert_eq!(mediant(&q(1, 3), &q(1, 2)), q(2, 5));
+        assert_eq!(mediant(&-q(1, 2), &q(1, 3)), Mpq::ZERO);
+        let lengths: Vec<usize> = (1..=8).map(|n| sequence(n).unwrap().len()).collect();
+        assert_eq!(lengths, [2, 3, 5, 7, 11, 13, 19, 23]);
+        assert!(sequence(0).is_err());
+        assert!(sequence(MAX_SEQUENCE_ORDER + 1).is_err());
+    }
+}
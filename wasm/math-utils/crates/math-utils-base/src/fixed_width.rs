@@ -0,0 +1,279 @@
+//! Simulation of fixed-width machine integers: wrapping arithmetic on `bits`-bit words read as
+//! either unsigned or two's-complement signed values.
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn,
+    base::num::{
+        arithmetic::traits::{ModPowerOf2, PowerOf2},
+        basic::traits::Zero,
+        conversion::traits::ToStringBase,
+        logic::traits::BitAccess,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_BITS: u64 = 512;
+
+/// The outcome of a fixed-width operation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedWidthResult {
+    /// The wrapped result, read as signed or unsigned like the operands.
+    pub value: Mpz,
+    /// Whether the operation on the raw bit patterns, read as unsigned, did not fit in the word;
+    /// for additions this is the carry out of the top bit.
+    pub carry: bool,
+    /// Whether the exact result differs from the wrapped one.
+    pub overflow: bool,
+    /// The bit pattern of the result, `bits` binary digits wide.
+    pub bin: String,
+    /// The bit pattern of the result, `⌈bits / 4⌉` hexadecimal digits wide.
+    pub hex: String,
+}
+
+fn check_bits(bits: u64) -> Result<(), anyhow::Error> {
+    if bits == 0 || bits > MAX_BITS {
+        bail!("bit width {bits} is out of range [1, {MAX_BITS}]");
+    }
+    Ok(())
+}
+
+/// The values representable in the word, as `[min, max)`.
+fn range(bits: u64, signed: bool) -> (Mpz, Mpz) {
+    if signed {
+        let half = Mpz::power_of_2(bits - 1);
+        (-half.clone(), half)
+    } else {
+        (Mpz::ZERO, Mpz::power_of_2(bits))
+    }
+}
+
+fn check_operand(x: &Mpz, bits: u64, signed: bool) -> Result<(), anyhow::Error> {
+    let (min, max) = range(bits, signed);
+    if *x < min || *x >= max {
+        let kind = if signed { "signed" } else { "unsigned" };
+        bail!("{x} is out of range [{min}, {max}) of {kind} {bits}-bit integers");
+    }
+    Ok(())
+}
+
+/// The bit pattern of `x` modulo `2^bits`.
+fn pattern(x: &Mpz, bits: u64) -> Mpn {
+    x.mod_power_of_2(bits)
+}
+
+/// Reads a bit pattern as a signed or unsigned integer.
+fn interpret(pattern: &Mpn, bits: u64, signed: bool) -> Mpz {
+    if signed && pattern.get_bit(bits - 1) {
+        Mpz::from(pattern) - Mpz::power_of_2(bits)
+    } else {
+        Mpz::from(pattern)
+    }
+}
+
+fn pad(digits: String, width: u64) -> String {
+    format!("{digits:0>width$}", width = width as usize)
+}
+
+fn to_binary(pattern: &Mpn, bits: u64) -> String {
+    pad(pattern.to_string_base(2), bits)
+}
+
+/// Wraps the exact result `exact` of an operation whose unsigned counterpart on the bit patterns
+/// gave `unsigned_exact`.
+fn wrap(exact: Mpz, unsigned_exact: Mpz, bits: u64, signed: bool) -> FixedWidthResult {
+    let result = pattern(&exact, bits);
+    let value = interpret(&result, bits, signed);
+    FixedWidthResult {
+        carry: unsigned_exact != result,
+        overflow: exact != value,
+        bin: to_binary(&result, bits),
+        hex: pad(result.to_string_base(16), bits.div_ceil(4)),
+        value,
+    }
+}
+
+pub fn add(x: &Mpz, y: &Mpz, bits: u64, signed: bool) -> Result<FixedWidthResult, anyhow::Error> {
+    check_bits(bits)?;
+    check_operand(x, bits, signed)?;
+    check_operand(y, bits, signed)?;
+    let unsigned = Mpz::from(pattern(x, bits)) + Mpz::from(pattern(y, bits));
+    Ok(wrap(x + y, unsigned, bits, signed))
+}
+
+pub fn mul(x: &Mpz, y: &Mpz, bits: u64, signed: bool) -> Result<FixedWidthResult, anyhow::Error> {
+    check_bits(bits)?;
+    check_operand(x, bits, signed)?;
+    check_operand(y, bits, signed)?;
+    let unsigned = Mpz::from(pattern(x, bits)) * Mpz::from(pattern(y, bits));
+    Ok(wrap(x * y, unsigned, bits, signed))
+}
+
+/// Two's-complement negation. The carry is set unless `x` is zero, i.e. whenever `0 - x`
+/// borrows.
+pub fn neg(x: &Mpz, bits: u64, signed: bool) -> Result<FixedWidthResult, anyhow::Error> {
+    check_bits(bits)?;
+    check_operand(x, bits, signed)?;
+    let unsigned = -Mpz::from(pattern(x, bits));
+    Ok(wrap(-x, unsigned, bits, signed))
+}
+
+/// Shifts left by `amount`, dropping the bits shifted past the top of the word. The carry is set
+/// if any of the dropped bits was one.
+pub fn shl(
+    x: &Mpz,
+    amount: u64,
+    bits: u64,
+    signed: bool,
+) -> Result<FixedWidthResult, anyhow::Error> {
+    check_bits(bits)?;
+    check_operand(x, bits, signed)?;
+    // shifting by the full width already clears the word
+    let amount = amount.min(bits);
+    let unsigned = Mpz::from(pattern(x, bits) << amount);
+    Ok(wrap(x << amount, unsigned, bits, signed))
+}
+
+/// Shifts right by `amount`: arithmetically for signed words and logically for unsigned ones.
+/// The result never overflows; the carry is set if any of the bits shifted out was one.
+pub fn shr(
+    x: &Mpz,
+    amount: u64,
+    bits: u64,
+    signed: bool,
+) -> Result<FixedWidthResult, anyhow::Error> {
+    check_bits(bits)?;
+    check_operand(x, bits, signed)?;
+    let exact = x >> amount;
+    let mut result = wrap(exact, Mpz::ZERO, bits, signed);
+    result.carry = pattern(x, bits.min(amount)) != 0u32;
+    Ok(result)
+}
+
+/// The `bits`-wide two's-complement bit pattern of `x`, which may be given either as a signed or
+/// an unsigned value, i.e. in `[-2^(bits - 1), 2^bits)`.
+pub fn to_twos_complement(x: &Mpz, bits: u64) -> Result<String, anyhow::Error> {
+    check_bits(bits)?;
+    if *x < -Mpz::power_of_2(bits - 1) || *x >= Mpz::power_of_2(bits) {
+        bail!("{x} does not fit in {bits} bits");
+    }
+    Ok(to_binary(&pattern(x, bits), bits))
+}
+
+/// Reads a string of binary digits as a two's-complement signed integer as wide as the string.
+/// Underscores may be used as digit separators.
+pub fn from_twos_complement(src: &str) -> Result<Mpz, anyhow::Error> {
+    let digits: String = src.chars().filter(|&c| c != '_').collect();
+    let bits = digits.len() as u64;
+    check_bits(bits)?;
+    let mut pattern = Mpn::ZERO;
+    for (i, c) in digits.chars().rev().enumerate() {
+        match c {
+            '0' => {}
+            '1' => pattern.set_bit(i as u64),
+            _ => bail!("invalid binary digit {c:?}"),
+        }
+    }
+    Ok(interpret(&pattern, bits, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn z(x: i64) -> Mpz {
+        Mpz::from(x)
+    }
+
+    #[test]
+    fn signed_overflow() {
+        let r = add(&z(127), &z(1), 8, true).unwrap();
+        assert_eq!(r.value, -128);
+        assert!(r.overflow && !r.carry);
+        assert_eq!((r.bin.as_str(), r.hex.as_str()), ("10000000", "80"));
+        // -1 + -1 carries out of the top bit but does not overflow
+        let r = add(&z(-1), &z(-1), 8, true).unwrap();
+        assert_eq!(r.value, -2);
+        assert!(r.carry && !r.overflow);
+        let r = neg(&z(-128), 8, true).unwrap();
+        assert_eq!(r.value, -128);
+        assert!(r.overflow && r.carry);
+        let r = neg(&z(0), 8, true).unwrap();
+        assert!(!r.overflow && !r.carry);
+    }
+
+    #[test]
+    fn unsigned_carry() {
+        let r = mul(&z(255), &z(2), 8, false).unwrap();
+        assert_eq!(r.value, 254);
+        assert!(r.carry && r.overflow);
+        assert_eq!((r.bin.as_str(), r.hex.as_str()), ("11111110", "fe"));
+        let r = add(&z(200), &z(55), 8, false).unwrap();
+        assert_eq!(r.value, 255);
+        assert!(!r.carry && !r.overflow);
+        // the hex digits cover a partial nibble
+        assert_eq!(add(&z(-1), &z(0), 5, true).unwrap().hex, "1f");
+    }
+
+    #[test]
+    fn wide_words_wrap_around() {
+        let max = Mpz::power_of_2(256) - Mpz::from(1);
+        let r = add(&max, &z(1), 256, false).unwrap();
+        assert_eq!(r.value, 0);
+        assert!(r.carry && r.overflow);
+        assert_eq!(r.bin, "0".repeat(256));
+        assert_eq!(r.hex, "0".repeat(64));
+        let half = Mpz::power_of_2(255);
+        let r = add(&(&half - Mpz::from(1)), &z(1), 256, true).unwrap();
+        assert_eq!(r.value, -half);
+        assert!(r.overflow && !r.carry);
+        let r = mul(&max, &max, 512, false).unwrap();
+        assert!(!r.overflow);
+    }
+
+    #[test]
+    fn shifts() {
+        let r = shl(&z(64), 1, 8, true).unwrap();
+        assert_eq!(r.value, -128);
+        assert!(r.overflow && !r.carry);
+        let r = shl(&z(-1), 1000, 8, true).unwrap();
+        assert_eq!(r.value, 0);
+        assert!(r.carry);
+        // arithmetic for signed words, logical for unsigned ones
+        let r = shr(&z(-5), 1, 8, true).unwrap();
+        assert_eq!(r.value, -3);
+        assert!(r.carry && !r.overflow);
+        assert_eq!(shr(&z(251), 1, 8, false).unwrap().value, 125);
+        assert_eq!(shr(&z(-1), 1000, 8, true).unwrap().value, -1);
+        assert!(!shr(&z(4), 2, 8, false).unwrap().carry);
+    }
+
+    #[test]
+    fn twos_complement_conversions() {
+        assert_eq!(to_twos_complement(&z(-1), 8).unwrap(), "11111111");
+        assert_eq!(to_twos_complement(&z(255), 8).unwrap(), "11111111");
+        assert_eq!(to_twos_complement(&z(5), 4).unwrap(), "0101");
+        assert!(to_twos_complement(&z(256), 8).is_err());
+        assert!(to_twos_complement(&z(-129), 8).is_err());
+        assert_eq!(from_twos_complement("1111_1111").unwrap(), -1);
+        assert_eq!(from_twos_complement("0111").unwrap(), 7);
+        assert_eq!(from_twos_complement("1000").unwrap(), -8);
+        assert!(from_twos_complement("").is_err());
+        assert!(from_twos_complement("12").is_err());
+    }
+
+    #[test]
+    fn invalid_widths_and_operands() {
+        assert_eq!(
+            add(&z(0), &z(0), 0, false).unwrap_err().to_string(),
+            "bit width 0 is out of range [1, 512]"
+        );
+        assert!(add(&z(0), &z(0), 513, false).is_err());
+        assert!(from_twos_complement(&"0".repeat(513)).is_err());
+        assert_eq!(
+            add(&z(128), &z(0), 8, true).unwrap_err().to_string(),
+            "128 is out of range [-128, 128) of signed 8-bit integers"
+        );
+        assert!(add(&z(-1), &z(0), 8, false).is_err());
+    }
+}
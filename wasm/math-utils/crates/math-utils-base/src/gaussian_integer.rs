@@ -0,0 +1,407 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::*,
+};
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn,
+    base::{
+        num::{
+            arithmetic::traits::{DivRound, FloorSqrt, ModPow, ModPowerOf2, Sign, UnsignedAbs},
+            basic::traits::{One, Zero},
+        },
+        rounding_modes::RoundingMode,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::primes;
+
+/// An element of ℤ\[i\], i.e. a complex number whose real and imaginary parts are both integers.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GaussianInteger {
+    pub re: Mpz,
+    pub im: Mpz,
+}
+
+impl GaussianInteger {
+    pub const ZERO: Self = Self {
+        re: Mpz::ZERO,
+        im: Mpz::ZERO,
+    };
+    pub const ONE: Self = Self {
+        re: Mpz::ONE,
+        im: Mpz::ZERO,
+    };
+    pub const I: Self = Self {
+        re: Mpz::ZERO,
+        im: Mpz::ONE,
+    };
+
+    pub fn new(re: Mpz, im: Mpz) -> Self {
+        Self { re, im }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re == 0 && self.im == 0
+    }
+
+    /// Whether `self` is one of the units `±1` and `±i`.
+    pub fn is_unit(&self) -> bool {
+        self.norm() == 1u32
+    }
+
+    /// Whether `self` is a Gaussian prime: either an associate of a rational prime `p ≡ 3 (mod 4)`,
+    /// or an element whose norm is a rational prime.
+    pub fn is_gaussian_prime(&self) -> bool {
+        if self.re == 0 || self.im == 0 {
+            let p = (&self.re + &self.im).unsigned_abs();
+            (&p).mod_power_of_2(2) == 3u32 && primes::is_probable_prime(&p)
+        } else {
+            primes::is_probable_prime(&self.norm())
+        }
+    }
+
+    pub fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    /// The field norm `N(a + bi) = a² + b²`.
+    pub fn norm(&self) -> Mpn {
+        (&self.re * &self.re + &self.im * &self.im).unsigned_abs()
+    }
+
+    /// Returns the associate of `self` lying in the first quadrant, i.e. with a positive real
+    /// part and a non-negative imaginary part. Zero is returned unchanged.
+    pub fn normalize(self) -> Self {
+        if self.is_zero() {
+            return self;
+        }
+        let Self { mut re, mut im } = self;
+        // multiply by -i until the result lands in the first quadrant
+        while !(re > 0 && im >= 0) {
+            (re, im) = (im, -re);
+        }
+        Self { re, im }
+    }
+
+    /// Euclidean division in ℤ\[i\]. The quotient is `self / rhs` with both parts rounded to the
+    /// nearest integer (ties to even), so the remainder always satisfies `N(r) ≤ N(rhs) / 2`.
+    ///
+    /// Panics if `rhs` is zero.
+    pub fn div_rem(self, rhs: &Self) -> (Self, Self) {
+        if rhs.is_zero() {
+            panic!("division by zero");
+        }
+        let n = Mpz::from(rhs.norm());
+        let num = self.clone() * rhs.clone().conj();
+        let q = Self {
+            re: num.re.div_round(&n, RoundingMode::Nearest).0,
+            im: num.im.div_round(&n, RoundingMode::Nearest).0,
+        };
+        let r = self - rhs.clone() * q.clone();
+        (q, r)
+    }
+
+    /// The greatest common divisor computed by the Euclidean algorithm, normalized to the first
+    /// quadrant so that the result is unique.
+    pub fn gcd(self, rhs: Self) -> Self {
+        let (mut a, mut b) = (self, rhs);
+        while !b.is_zero() {
+            let (_, r) = a.div_rem(&b);
+            (a, b) = (b, r);
+        }
+        a.normalize()
+    }
+
+    /// Returns `self / rhs` if `rhs` divides `self` exactly.
+    pub fn div_exact(&self, rhs: &Self) -> Option<Self> {
+        let (q, r) = self.clone().div_rem(rhs);
+        r.is_zero().then_some(q)
+    }
+
+    /// Factors `self` into Gaussian primes. Fails for zero and when the norm cannot be factored
+    /// within [`primes::FACTOR_STEPS`].
+    ///
+    /// The rational primes dividing the norm are lifted to ℤ\[i\]: `2 = -i(1 + i)²`, a prime
+    /// `p ≡ 3 (mod 4)` stays prime, and a prime `p ≡ 1 (mod 4)` splits as `p = ππ̄` where
+    /// `π = a + bi` with `a² + b² = p` is found by Cornacchia's algorithm.
+    pub fn factor(&self) -> Result<GaussianFactorization, anyhow::Error> {
+        if self.is_zero() {
+            bail!("cannot factorize 0");
+        }
+        let mut rest = self.clone();
+        let mut factors = Vec::new();
+        for (p, _) in primes::factorize_bounded(&self.norm())? {
+            let candidates = if p == 2u32 {
+                vec![Self::new(Mpz::ONE, Mpz::ONE)]
+            } else if (&p).mod_power_of_2(2) == 3u32 {
+                vec![Self::from(Mpz::from(p))]
+            } else {
+                let pi = split_prime(&p);
+                let pi_conj = pi.clone().conj().normalize();
+                // list the split primes in a fixed order: smaller real part first
+                if pi.re < pi_conj.re {
+                    vec![pi, pi_conj]
+                } else {
+                    vec![pi_conj, pi]
+                }
+            };
+            for pi in candidates {
+                let mut k = 0;
+                while let Some(q) = rest.div_exact(&pi) {
+                    rest = q;
+                    k += 1;
+                }
+                if k > 0 {
+                    factors.push((pi, k));
+                }
+            }
+        }
+        Ok(GaussianFactorization {
+            unit: rest,
+            factors,
+        })
+    }
+}
+
+/// Writes the prime `p ≡ 1 (mod 4)` as `a² + b²` and returns `a + bi` normalized to the first
+/// quadrant.
+fn split_prime(p: &Mpn) -> GaussianInteger {
+    // a square root of -1 modulo p is c^((p - 1) / 4) for any quadratic non-residue c
+    let p_minus_one = p - Mpn::ONE;
+    let half = &p_minus_one >> 1;
+    let mut c = Mpn::from(2u32);
+    while (&c).mod_pow(&half, p) != p_minus_one {
+        c += Mpn::ONE;
+    }
+    let x: Mpn = c.mod_pow(&p_minus_one >> 2, p);
+    // Cornacchia: run the Euclidean algorithm on (p, x) until the remainder drops below √p
+    let (mut a, mut b) = (p.clone(), x);
+    while &b * &b > *p {
+        (a, b) = (b.clone(), a % b);
+    }
+    let im = (p - &b * &b).floor_sqrt();
+    GaussianInteger::new(Mpz::from(b), Mpz::from(im)).normalize()
+}
+
+/// A factorization `unit · π₁^e₁ ⋯ πₖ^eₖ` into first-quadrant Gaussian primes, ordered by norm.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct GaussianFactorization {
+    pub unit: GaussianInteger,
+    pub factors: Vec<(GaussianInteger, u64)>,
+}
+
+impl From<Mpz> for GaussianInteger {
+    fn from(value: Mpz) -> Self {
+        Self {
+            re: value,
+            im: Mpz::ZERO,
+        }
+    }
+}
+
+impl Neg for GaussianInteger {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            re: -self.re,
+            im: -self.im,
+        }
+    }
+}
+
+impl Add for GaussianInteger {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl Sub for GaussianInteger {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re - rhs.re,
+            im: self.im - rhs.im,
+        }
+    }
+}
+
+impl Mul for GaussianInteger {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+        let (a, b, c, d) = (self.re, self.im, rhs.re, rhs.im);
+        Self {
+            re: &a * &c - &b * &d,
+            im: a * d + b * c,
+        }
+    }
+}
+
+/// The quotient of [`GaussianInteger::div_rem`]. Panics if `rhs` is zero.
+impl Div for GaussianInteger {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.div_rem(&rhs).0
+    }
+}
+
+/// The remainder of [`GaussianInteger::div_rem`]. Panics if `rhs` is zero.
+impl Rem for GaussianInteger {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.div_rem(&rhs).1
+    }
+}
+
+impl Display for GaussianInteger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.im == 0 {
+            return Display::fmt(&self.re, f);
+        }
+        if self.re != 0 {
+            Display::fmt(&self.re, f)?;
+            if !self.im.sign().is_lt() {
+                write!(f, "+")?;
+            }
+        }
+        if self.im == -1 {
+            write!(f, "-")?;
+        } else if self.im != 1 {
+            Display::fmt(&self.im, f)?;
+        }
+        write!(f, "i")
+    }
+}
+
+impl Debug for GaussianInteger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g(re: i64, im: i64) -> GaussianInteger {
+        GaussianInteger::new(Mpz::from(re), Mpz::from(im))
+    }
+
+    #[test]
+    fn div_rem_rounds_ties_to_even() {
+        // 1/2, 3/2 and 5/2 are ties between two integers
+        assert_eq!(g(1, 0).div_rem(&g(2, 0)), (g(0, 0), g(1, 0)));
+        assert_eq!(g(3, 0).div_rem(&g(2, 0)), (g(2, 0), g(-1, 0)));
+        assert_eq!(g(5, 0).div_rem(&g(2, 0)), (g(2, 0), g(1, 0)));
+        assert_eq!(g(-3, 0).div_rem(&g(2, 0)), (g(-2, 0), g(1, 0)));
+        // (1 + 3i) / 2 = 0.5 + 1.5i ties in both parts
+        assert_eq!(g(1, 3).div_rem(&g(2, 0)), (g(0, 2), g(1, -1)));
+        // (1 + 2i) / (1 + i) = 1.5 + 0.5i
+        assert_eq!(g(1, 2).div_rem(&g(1, 1)), (g(2, 0), g(-1, 0)));
+    }
+
+    #[test]
+    fn div_rem_remainder_is_small() {
+        for a in -12..=12 {
+            for b in -12..=12 {
+                for (c, d) in [(2, 0), (1, 1), (3, -2), (-4, 5), (0, 7)] {
+                    let (x, y) = (g(a, b), g(c, d));
+                    let (q, r) = x.clone().div_rem(&y);
+                    assert_eq!(y.clone() * q + r.clone(), x);
+                    assert!(r.norm() * Mpn::from(2u32) <= y.norm(), "{x} / {y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gcd() {
+        assert_eq!(g(5, 0).gcd(g(2, 1)), g(2, 1));
+        assert_eq!(g(5, 0).gcd(g(2, -1)), g(1, 2));
+        assert_eq!(g(2, 1).gcd(g(1, 2)), g(1, 0));
+        assert_eq!(g(0, 0).gcd(g(0, -3)), g(3, 0));
+        assert_eq!(g(4, 0).gcd(g(2, 2)), g(2, 2));
+        // (2 + i)(3 + 2i) = 4 + 7i and (2 + i)(1 - i) = 3 - i
+        assert_eq!(g(4, 7).gcd(g(3, -1)), g(2, 1));
+    }
+
+    fn factors(x: GaussianInteger) -> (GaussianInteger, Vec<(GaussianInteger, u64)>) {
+        let GaussianFactorization { unit, factors } = x.factor().unwrap();
+        (unit, factors)
+    }
+
+    #[test]
+    fn factor_small_primes() {
+        assert_eq!(factors(g(2, 0)), (g(0, -1), vec![(g(1, 1), 2)]));
+        assert_eq!(
+            factors(g(5, 0)),
+            (g(0, -1), vec![(g(1, 2), 1), (g(2, 1), 1)])
+        );
+        assert_eq!(
+            factors(g(13, 0)),
+            (g(0, -1), vec![(g(2, 3), 1), (g(3, 2), 1)])
+        );
+        assert_eq!(factors(g(-7, 0)), (g(-1, 0), vec![(g(7, 0), 1)]));
+        assert_eq!(factors(g(0, 1)), (g(0, 1), vec![]));
+        assert!(g(0, 0).factor().is_err());
+    }
+
+    #[test]
+    fn factor_40_bit() {
+        // -i (1 + i)³ (1 + 2i) (2 + i) · 7 · (3 + 2i) · 1031 · (242 + 995i)², where
+        // 242² + 995² = 1048589 is prime
+        assert_eq!(
+            factors(g(301362073250, -241001683370)),
+            (
+                g(0, -1),
+                vec![
+                    (g(1, 1), 3),
+                    (g(1, 2), 1),
+                    (g(2, 1), 1),
+                    (g(7, 0), 1),
+                    (g(3, 2), 1),
+                    (g(1031, 0), 1),
+                    (g(242, 995), 2),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn factor_reconstructs() {
+        for a in -30..=30 {
+            for b in -30..=30 {
+                let x = g(a, b);
+                if x.is_zero() {
+                    continue;
+                }
+                let (unit, factors) = factors(x.clone());
+                assert!(unit.is_unit());
+                let mut product = unit;
+                for (pi, k) in factors {
+                    assert!(pi.is_gaussian_prime() && pi.clone().normalize() == pi);
+                    for _ in 0..k {
+                        product = product * pi.clone();
+                    }
+                }
+                assert_eq!(product, x);
+            }
+        }
+    }
+}
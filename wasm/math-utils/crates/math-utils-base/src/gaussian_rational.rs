@@ -0,0 +1,179 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::*,
+    str::FromStr,
+};
+
+use anyhow::bail;
+use malachite::base::num::{
+    arithmetic::traits::Sign,
+    basic::traits::{NegativeOne, One, Zero},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{MpqExt, traits::ExtendedNumber};
+
+/// A complex number whose real and imaginary parts are both exact (extended) rationals.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct GaussianRational {
+    pub re: MpqExt,
+    pub im: MpqExt,
+}
+
+impl GaussianRational {
+    pub const ZERO: Self = Self {
+        re: MpqExt::ZERO,
+        im: MpqExt::ZERO,
+    };
+    pub const ONE: Self = Self {
+        re: MpqExt::ONE,
+        im: MpqExt::ZERO,
+    };
+    pub const I: Self = Self {
+        re: MpqExt::ZERO,
+        im: MpqExt::ONE,
+    };
+
+    pub fn new(re: MpqExt, im: MpqExt) -> Self {
+        Self { re, im }
+    }
+
+    pub fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.im.is_zero()
+    }
+}
+
+impl From<MpqExt> for GaussianRational {
+    fn from(value: MpqExt) -> Self {
+        Self {
+            re: value,
+            im: MpqExt::ZERO,
+        }
+    }
+}
+
+fn parse_imaginary_coefficient(src: &str) -> Result<MpqExt, anyhow::Error> {
+    match src {
+        "" | "+" => Ok(MpqExt::ONE),
+        "-" => Ok(MpqExt::NEGATIVE_ONE),
+        src => MpqExt::from_str(src),
+    }
+}
+
+impl FromStr for GaussianRational {
+    type Err = anyhow::Error;
+
+    /// Parses strings such as `1/2+3/4i`, `-i` or `0.[3]`. Whitespace is ignored.
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let src: String = src.chars().filter(|c| !c.is_whitespace()).collect();
+        if src.is_empty() {
+            bail!("empty string cannot be parsed as a complex number");
+        }
+        match src.strip_suffix('i') {
+            Some(body) => {
+                // find the sign separating the real part from the imaginary part, skipping the
+                // leading sign as well as signs of exponents and denominators
+                let split_idx = body.char_indices().rev().find_map(|(idx, c)| {
+                    if idx > 0 && (c == '+' || c == '-') {
+                        match body[..idx].chars().last() {
+                            Some('e' | 'E' | '/') => None,
+                            _ => Some(idx),
+                        }
+                    } else {
+                        None
+                    }
+                });
+                match split_idx {
+                    Some(idx) => Ok(Self {
+                        re: MpqExt::from_str(&body[..idx])?,
+                        im: parse_imaginary_coefficient(&body[idx..])?,
+                    }),
+                    None => Ok(Self {
+                        re: MpqExt::ZERO,
+                        im: parse_imaginary_coefficient(body)?,
+                    }),
+                }
+            }
+            None => Ok(MpqExt::from_str(&src)?.into()),
+        }
+    }
+}
+
+impl Neg for GaussianRational {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            re: -self.re,
+            im: -self.im,
+        }
+    }
+}
+
+impl Add for GaussianRational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl Sub for GaussianRational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            re: self.re - rhs.re,
+            im: self.im - rhs.im,
+        }
+    }
+}
+
+impl Mul for GaussianRational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+        let (a, b, c, d) = (self.re, self.im, rhs.re, rhs.im);
+        Self {
+            re: &a * &c - &b * &d,
+            im: a * d + b * c,
+        }
+    }
+}
+
+impl Display for GaussianRational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.im.is_zero() {
+            return Display::fmt(&self.re, f);
+        }
+        if !self.re.is_zero() {
+            Display::fmt(&self.re, f)?;
+            if !self.im.sign().is_lt() {
+                write!(f, "+")?;
+            }
+        }
+        if self.im == MpqExt::NEGATIVE_ONE {
+            write!(f, "-")?;
+        } else if self.im != MpqExt::ONE {
+            Display::fmt(&self.im, f)?;
+        }
+        write!(f, "i")
+    }
+}
+
+impl Debug for GaussianRational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,338 @@
+use std::ops::*;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// A closed interval `[lo, hi]` of reals. Every operation rounds its lower bound down and its
+/// upper bound up, so the result always encloses the exact image of the operands.
+///
+/// IEEE 754 directed rounding is not available in Rust, so each bound is computed to nearest
+/// and its error is recovered exactly with an error-free transformation (TwoSum, or a fused
+/// multiply-add for products, quotients and square roots); the bound is moved by one ulp only if
+/// the rounding went the wrong way, and always near the underflow range, where the error is lost.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "(f64, f64)", into = "(f64, f64)")]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub const ENTIRE: Self = Self {
+        lo: f64::NEG_INFINITY,
+        hi: f64::INFINITY,
+    };
+
+    pub fn new(lo: f64, hi: f64) -> Result<Self, anyhow::Error> {
+        if lo.is_nan() || hi.is_nan() {
+            bail!("interval bounds cannot be NaN");
+        }
+        if lo > hi {
+            bail!("lower bound {lo} exceeds upper bound {hi}");
+        }
+        Ok(Self { lo, hi })
+    }
+
+    /// The degenerate interval `[x, x]`.
+    pub fn point(x: f64) -> Self {
+        Self { lo: x, hi: x }
+    }
+
+    /// `hi - lo`, rounded up.
+    pub fn width(&self) -> f64 {
+        let (d, e) = two_sum(self.hi, -self.lo);
+        round_up(d, e)
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        (self.lo..=self.hi).contains(&x)
+    }
+
+    pub fn abs(self) -> Self {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            -self
+        } else {
+            Self {
+                lo: 0.0,
+                hi: self.hi.max(-self.lo),
+            }
+        }
+    }
+
+    /// The square roots of the non-negative part of the interval, which must not be empty.
+    pub fn sqrt(self) -> Result<Self, anyhow::Error> {
+        if self.hi < 0.0 {
+            bail!("square root of an interval of negative numbers");
+        }
+        let sqrt_bounded = |x: f64| {
+            let s = x.sqrt();
+            if x != 0.0 && x < EXACT_ERROR_THRESHOLD {
+                return (s, f64::NAN);
+            }
+            // x - s² exactly
+            (s, (-s).mul_add(s, x))
+        };
+        let (lo, lo_err) = sqrt_bounded(self.lo.max(0.0));
+        let (hi, hi_err) = sqrt_bounded(self.hi);
+        Ok(Self {
+            lo: round_down(lo, lo_err),
+            hi: round_up(hi, hi_err),
+        })
+    }
+}
+
+impl TryFrom<(f64, f64)> for Interval {
+    type Error = anyhow::Error;
+
+    fn try_from((lo, hi): (f64, f64)) -> Result<Self, Self::Error> {
+        Self::new(lo, hi)
+    }
+}
+
+impl From<Interval> for (f64, f64) {
+    fn from(value: Interval) -> Self {
+        (value.lo, value.hi)
+    }
+}
+
+/// Below this magnitude the error term of a product, quotient or square root may fall under the
+/// subnormal range and round to zero, so the rounding direction is treated as unknown.
+const EXACT_ERROR_THRESHOLD: f64 = f64::MIN_POSITIVE * (1u128 << 106) as f64;
+
+/// `(s, e)` with `s = fl(a + b)` and `a + b = s + e` exactly.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let b_virtual = s - a;
+    let a_virtual = s - b_virtual;
+    (s, (a - a_virtual) + (b - b_virtual))
+}
+
+/// The greatest float not above `x + err`, given the rounded value `x` and the sign of its error
+/// `err`. An error of `NaN` means the error is unknown, as after an overflow.
+fn round_down(x: f64, err: f64) -> f64 {
+    if err < 0.0 || err.is_nan() {
+        x.next_down()
+    } else {
+        x
+    }
+}
+
+/// The least float not below `x + err`; see [`round_down`].
+fn round_up(x: f64, err: f64) -> f64 {
+    if err > 0.0 || err.is_nan() {
+        x.next_up()
+    } else {
+        x
+    }
+}
+
+/// `a · b` for interval endpoints, where `0 · ∞` stands for the limit `0`, together with the
+/// rounding error.
+fn product(a: f64, b: f64) -> (f64, f64) {
+    if a == 0.0 || b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let p = a * b;
+    if p.abs() < EXACT_ERROR_THRESHOLD {
+        return (p, f64::NAN);
+    }
+    (p, a.mul_add(b, -p))
+}
+
+/// `a / b` for interval endpoints, where `b` is nonzero, together with a value of the same sign
+/// as the rounding error.
+fn quotient(a: f64, b: f64) -> (f64, f64) {
+    if a == 0.0 {
+        return (0.0, 0.0);
+    }
+    let q = a / b;
+    if a.is_infinite() || b.is_infinite() {
+        return (q, if q.is_nan() { f64::NAN } else { 0.0 });
+    }
+    if q.abs() < EXACT_ERROR_THRESHOLD || a.abs() < EXACT_ERROR_THRESHOLD {
+        return (q, f64::NAN);
+    }
+    // the remainder a - q · b is exact and the exact quotient is q + r / b
+    let r = (-q).mul_add(b, a);
+    (q, r * b.signum())
+}
+
+impl Neg for Interval {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (lo, lo_err) = two_sum(self.lo, rhs.lo);
+        let (hi, hi_err) = two_sum(self.hi, rhs.hi);
+        Self {
+            lo: round_down(lo, lo_err),
+            hi: round_up(hi, hi_err),
+        }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+/// The interval spanned by the products or quotients of all pairs of endpoints.
+fn hull_of_endpoints(x: Interval, y: Interval, op: fn(f64, f64) -> (f64, f64)) -> Interval {
+    let candidates = [
+        op(x.lo, y.lo),
+        op(x.lo, y.hi),
+        op(x.hi, y.lo),
+        op(x.hi, y.hi),
+    ];
+    Interval {
+        lo: candidates
+            .iter()
+            .map(|&(v, err)| round_down(v, err))
+            .fold(f64::INFINITY, f64::min),
+        hi: candidates
+            .iter()
+            .map(|&(v, err)| round_up(v, err))
+            .fold(f64::NEG_INFINITY, f64::max),
+    }
+}
+
+impl Mul for Interval {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        hull_of_endpoints(self, rhs, product)
+    }
+}
+
+impl Div for Interval {
+    type Output = Self;
+
+    /// A divisor containing zero gives the entire real line, the tightest single interval
+    /// enclosing the quotients.
+    fn div(self, rhs: Self) -> Self::Output {
+        if rhs.contains(0.0) {
+            return Self::ENTIRE;
+        }
+        hull_of_endpoints(self, rhs, quotient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::Rational as Mpq;
+
+    use super::*;
+
+    fn exact(x: f64) -> Mpq {
+        Mpq::try_from(x).unwrap()
+    }
+
+    /// Checks that `result` encloses `op` applied exactly to every pair of endpoints, where an
+    /// infinite bound encloses anything beyond the range of `f64` after an overflow.
+    fn assert_encloses(result: Interval, x: Interval, y: Interval, op: fn(Mpq, Mpq) -> Mpq) {
+        for a in [x.lo, x.hi] {
+            for b in [y.lo, y.hi] {
+                let value = op(exact(a), exact(b));
+                assert!(
+                    (result.lo == f64::NEG_INFINITY || exact(result.lo) <= value)
+                        && (result.hi == f64::INFINITY || value <= exact(result.hi)),
+                    "{result:?} misses {value} from {a} and {b}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rounding_encloses_exact_results() {
+        let values: [f64; 8] = [0.1, 0.2, 1.0 / 3.0, -0.7, 3.0, 1e16 + 2.0, -1e-300, 7.0];
+        for &a in &values {
+            for &b in &values {
+                for &c in &values {
+                    let x = Interval::new(a.min(b), a.max(b)).unwrap();
+                    let y = Interval::point(c);
+                    assert_encloses(x + y, x, y, |a, b| a + b);
+                    assert_encloses(x - y, x, y, |a, b| a - b);
+                    assert_encloses(x * y, x, y, |a, b| a * b);
+                    assert_encloses(x / y, x, y, |a, b| a / b);
+                }
+            }
+        }
+        // 0.1 + 0.2 and 1 / 3 are inexact, so the bounds are one ulp apart
+        let sum = Interval::point(0.1) + Interval::point(0.2);
+        assert_eq!(sum.lo.next_up(), sum.hi);
+        let third = Interval::point(1.0) / Interval::point(3.0);
+        assert_eq!(third.lo.next_up(), third.hi);
+        // 10⁻⁶⁰⁰ underflows to zero along with its rounding error
+        let tiny = Interval::point(-1e-300) * Interval::point(-1e-300);
+        assert!(tiny.lo <= 0.0 && tiny.hi > 0.0);
+        // exact operations stay points
+        let product = Interval::point(1.5) * Interval::point(-4.0);
+        assert_eq!(product, Interval::point(-6.0));
+        let root = Interval::point(2.0).sqrt().unwrap();
+        let two = exact(2.0);
+        assert!(exact(root.lo) * exact(root.lo) <= two && two <= exact(root.hi) * exact(root.hi));
+        assert_eq!(root.lo.next_up(), root.hi);
+        assert_eq!(
+            Interval::new(4.0, 9.0).unwrap().sqrt().unwrap(),
+            Interval::new(2.0, 3.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn division_by_intervals_containing_zero() {
+        let x = Interval::new(1.0, 2.0).unwrap();
+        for y in [(-1.0, 1.0), (0.0, 1.0), (-1.0, 0.0), (0.0, 0.0)] {
+            let y = Interval::new(y.0, y.1).unwrap();
+            assert_eq!(x / y, Interval::ENTIRE, "{y:?}");
+        }
+        let y = Interval::new(f64::MIN_POSITIVE, 1.0).unwrap();
+        assert!((x / y).hi.is_finite() && (x / y).lo == 1.0);
+    }
+
+    #[test]
+    fn square_roots_of_negative_numbers() {
+        let error = Interval::new(-2.0, -1.0).unwrap().sqrt().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "square root of an interval of negative numbers"
+        );
+        // only the non-negative part counts
+        assert_eq!(
+            Interval::new(-1.0, 4.0).unwrap().sqrt().unwrap(),
+            Interval::new(0.0, 2.0).unwrap()
+        );
+        assert_eq!(
+            Interval::new(-1.0, 0.0).unwrap().sqrt().unwrap(),
+            Interval::point(0.0)
+        );
+    }
+
+    #[test]
+    fn invalid_bounds() {
+        let error = Interval::new(2.0, 1.0).unwrap_err();
+        assert_eq!(error.to_string(), "lower bound 2 exceeds upper bound 1");
+        assert!(Interval::new(f64::NAN, 1.0).is_err());
+        assert!(Interval::new(0.0, f64::NAN).is_err());
+        assert!(Interval::try_from((1.0, -1.0)).is_err());
+        assert_eq!(Interval::new(1.0, 1.0).unwrap(), Interval::point(1.0));
+        assert_eq!(
+            Interval::new(f64::NEG_INFINITY, f64::INFINITY).unwrap(),
+            Interval::ENTIRE
+        );
+    }
+}
@@ -1,10 +1,32 @@
+pub mod combinatorics;
+pub mod divisors;
+mod dual;
+pub mod farey;
+pub mod fixed_width;
+mod gaussian_integer;
+mod gaussian_rational;
+mod interval;
 mod macros;
+pub mod modular;
 mod mpn_ext;
 mod mpq_ext;
 mod mpz_ext;
 pub mod parsing;
+pub mod pell;
+mod pm_value;
+pub mod primes;
+pub mod probability;
+mod quad_ext;
+pub mod small_rational;
+pub mod taylor;
 pub mod traits;
 
-pub use mpn_ext::MpnExt;
-pub use mpq_ext::MpqExt;
+pub use dual::Dual;
+pub use gaussian_integer::{GaussianFactorization, GaussianInteger};
+pub use gaussian_rational::GaussianRational;
+pub use interval::Interval;
+pub use mpn_ext::{MAX_POWER_BITS, MpnExt, log2_natural};
+pub use mpq_ext::{MpqExt, RadixExpansion};
 pub use mpz_ext::MpzExt;
+pub use pm_value::PmValue;
+pub use quad_ext::QuadExt;
@@ -7,18 +7,32 @@ macro_rules! impl_sum {
                 I: Iterator<Item = $t>,
             {
                 let mut stack = Vec::new();
-                for (i, x) in xs.enumerate() {
+                let mut xs = xs.enumerate();
+                while let Some((i, x)) = xs.next() {
                     if x.is_nan() {
                         return <$t>::NAN;
                     }
+                    if x.is_infinite() {
+                        // nothing but NaN or an infinity of the other sign can change the result
+                        let positive = x.is_sign_positive();
+                        return if xs.any(|(_, y)| {
+                            y.is_nan() || (y.is_infinite() && y.is_sign_positive() != positive)
+                        }) {
+                            <$t>::NAN
+                        } else {
+                            x
+                        };
+                    }
                     let mut s = x;
                     for _ in 0..(i + 1).trailing_zeros() {
                         s += stack.pop().unwrap();
                     }
                     stack.push(s);
                 }
-                let mut s = <$t>::ZERO;
-                for x in stack.into_iter().rev() {
+                // start from the first partial sum rather than +0, so that a sum of -0s stays -0
+                let mut partial_sums = stack.into_iter().rev();
+                let mut s = partial_sums.next().unwrap_or(<$t>::ZERO);
+                for x in partial_sums {
                     s += x;
                 }
                 s
@@ -31,18 +45,32 @@ macro_rules! impl_sum {
                 I: Iterator<Item = &'a $t>,
             {
                 let mut stack = Vec::new();
-                for (i, x) in xs.enumerate() {
+                let mut xs = xs.enumerate();
+                while let Some((i, x)) = xs.next() {
                     if x.is_nan() {
                         return <$t>::NAN;
                     }
+                    if x.is_infinite() {
+                        // nothing but NaN or an infinity of the other sign can change the result
+                        let positive = x.is_sign_positive();
+                        return if xs.any(|(_, y)| {
+                            y.is_nan() || (y.is_infinite() && y.is_sign_positive() != positive)
+                        }) {
+                            <$t>::NAN
+                        } else {
+                            x.clone()
+                        };
+                    }
                     let mut s = x.clone();
                     for _ in 0..(i + 1).trailing_zeros() {
                         s += stack.pop().unwrap();
                     }
                     stack.push(s);
                 }
-                let mut s = <$t>::ZERO;
-                for x in stack.into_iter().rev() {
+                // start from the first partial sum rather than +0, so that a sum of -0s stays -0
+                let mut partial_sums = stack.into_iter().rev();
+                let mut s = partial_sums.next().unwrap_or(<$t>::ZERO);
+                for x in partial_sums {
                     s += x;
                 }
                 s
@@ -60,10 +88,26 @@ macro_rules! impl_product {
                 I: Iterator<Item = $t>,
             {
                 let mut stack = Vec::new();
-                for (i, x) in xs.enumerate() {
+                let mut xs = xs.enumerate();
+                while let Some((i, x)) = xs.next() {
                     if x.is_nan() {
                         return <$t>::NAN;
                     }
+                    if x.is_zero() {
+                        // multiplying into a zero only tracks the sign, or gives NaN for an
+                        // infinite or NaN factor, so the product needs no more big multiplications
+                        let mut s = x;
+                        for (_, y) in xs {
+                            s *= y;
+                            if s.is_nan() {
+                                return <$t>::NAN;
+                            }
+                        }
+                        for y in stack {
+                            s *= y;
+                        }
+                        return s;
+                    }
                     let mut s = x;
                     for _ in 0..(i + 1).trailing_zeros() {
                         s *= stack.pop().unwrap();
@@ -84,10 +128,26 @@ macro_rules! impl_product {
                 I: Iterator<Item = &'a $t>,
             {
                 let mut stack = Vec::new();
-                for (i, x) in xs.enumerate() {
+                let mut xs = xs.enumerate();
+                while let Some((i, x)) = xs.next() {
                     if x.is_nan() {
                         return <$t>::NAN;
                     }
+                    if x.is_zero() {
+                        // multiplying into a zero only tracks the sign, or gives NaN for an
+                        // infinite or NaN factor, so the product needs no more big multiplications
+                        let mut s = x.clone();
+                        for (_, y) in xs {
+                            s *= y;
+                            if s.is_nan() {
+                                return <$t>::NAN;
+                            }
+                        }
+                        for y in stack {
+                            s *= y;
+                        }
+                        return s;
+                    }
                     let mut s = x.clone();
                     for _ in 0..(i + 1).trailing_zeros() {
                         s *= stack.pop().unwrap();
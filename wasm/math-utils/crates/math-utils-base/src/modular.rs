@@ -0,0 +1,417 @@
+//! Modular arithmetic: Chinese remaindering, rational reconstruction, multiplicative orders,
+//! discrete logarithms and modular square roots.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn, Rational as Mpq,
+    base::num::{
+        arithmetic::traits::{
+            CeilingSqrt, DivExact, ExtendedGcd, Gcd, Mod, ModInverse, ModMul, ModPow, ModPowerOf2,
+            ModSquare, Pow, PowerOf2,
+        },
+        basic::traits::{One, Two, Zero},
+    },
+};
+
+use crate::{MpqExt, primes};
+
+/// Reduces `a` into `[0, m)`.
+fn reduce(a: &Mpz, m: &Mpn) -> Mpn {
+    Mpn::try_from(a.mod_op(Mpz::from(m))).unwrap()
+}
+
+/// Solves the system `x ≡ rᵢ (mod mᵢ)` and returns `(x, m)` with `0 ≤ x < m` where `m` is the
+/// least common multiple of the moduli. The moduli need not be pairwise coprime; an error is
+/// returned only if the congruences are inconsistent.
+pub fn crt(residues: &[Mpz], moduli: &[Mpz]) -> Result<(Mpn, Mpn), anyhow::Error> {
+    if residues.len() != moduli.len() {
+        bail!("residues and moduli must have the same length");
+    }
+    let mut x = Mpz::ZERO;
+    let mut m = Mpz::ONE;
+    for (r, n) in residues.iter().zip(moduli) {
+        if *n <= 0 {
+            bail!("moduli must be positive");
+        }
+        // x + m·t ≡ r (mod n) is solvable iff g = gcd(m, n) divides r - x
+        let (g, s, _) = (&m).extended_gcd(n);
+        let g = Mpz::from(g);
+        let diff = r - &x;
+        if (&diff).mod_op(&g) != 0 {
+            bail!("the congruences are inconsistent");
+        }
+        let n_over_g = n.div_exact(&g);
+        let t = (diff.div_exact(&g) * s).mod_op(&n_over_g);
+        x += &m * t;
+        m *= n_over_g;
+        x = x.mod_op(&m);
+    }
+    Ok((Mpn::try_from(x).unwrap(), Mpn::try_from(m).unwrap()))
+}
+
+/// The fraction `p/q` with `|p| ≤ max_num`, `0 < q ≤ max_den`, `gcd(p, q) = 1` and
+/// `p ≡ residue · q (mod modulus)`, recovered by running the extended Euclidean algorithm on
+/// `(modulus, residue)` until the remainder drops to `max_num`. The answer is unique if
+/// `2 · max_num · max_den < modulus`; `None` is returned if there is none.
+pub fn rational_reconstruction(
+    residue: &Mpz,
+    modulus: &Mpz,
+    max_num: &Mpz,
+    max_den: &Mpz,
+) -> Result<Option<MpqExt>, anyhow::Error> {
+    if *modulus <= 0 {
+        bail!("the modulus must be positive");
+    }
+    if *max_num < 0 || *max_den <= 0 {
+        bail!(
+            "the bound on the numerator must be non-negative and that on the denominator positive"
+        );
+    }
+    // invariant: rᵢ ≡ tᵢ · residue (mod modulus)
+    let (mut r0, mut r1) = (modulus.clone(), residue.mod_op(modulus));
+    let (mut t0, mut t1) = (Mpz::ZERO, Mpz::ONE);
+    while r1 > *max_num {
+        let q = &r0 / &r1;
+        (r0, r1) = (r1.clone(), r0 - &q * r1);
+        (t0, t1) = (t1.clone(), t0 - q * t1);
+    }
+    if *t1.unsigned_abs_ref() > *max_den || r1.unsigned_abs_ref().gcd(t1.unsigned_abs_ref()) != 1u32
+    {
+        return Ok(None);
+    }
+    Ok(Some(MpqExt::from(Mpq::from_integers(r1, t1))))
+}
+
+/// The multiplicative order of `a` modulo `m`, i.e. the least `k > 0` with `aᵏ ≡ 1 (mod m)`.
+/// Fails if `m` or `φ(m)` cannot be factored within [`primes::FACTOR_STEPS`] steps.
+pub fn multiplicative_order(a: &Mpz, m: &Mpz) -> Result<Mpn, anyhow::Error> {
+    if *m <= 0 {
+        bail!("modulus must be positive");
+    }
+    let m = Mpn::try_from(m).unwrap();
+    let a = reduce(a, &m);
+    if (&a).gcd(&m) != 1u32 {
+        bail!("base must be coprime to the modulus");
+    }
+    if m == 1u32 {
+        return Ok(Mpn::ONE);
+    }
+    // the order divides φ(m); strip prime factors from φ(m) for as long as the power stays 1
+    let phi: Mpn = primes::factorize_bounded(&m)?
+        .into_iter()
+        .map(|(p, e)| (&p - Mpn::ONE) * (&p).pow(e - 1))
+        .product();
+    let mut order = phi.clone();
+    for (p, e) in primes::factorize_bounded(&phi)? {
+        for _ in 0..e {
+            let candidate = (&order).div_exact(&p);
+            if (&a).mod_pow(&candidate, &m) != 1u32 {
+                break;
+            }
+            order = candidate;
+        }
+    }
+    Ok(order)
+}
+
+/// Finds the least `x ≥ 0` with `baseˣ ≡ target (mod m)` using baby-step giant-step.
+///
+/// The search takes about `2√ord(base)` steps; if that exceeds `max_steps` an error is returned
+/// instead of attempting it. Returns `Ok(None)` if `target` is not a power of `base`.
+pub fn discrete_log(
+    base: &Mpz,
+    target: &Mpz,
+    m: &Mpz,
+    max_steps: u64,
+) -> Result<Option<Mpn>, anyhow::Error> {
+    let order = multiplicative_order(base, m)?;
+    let m = Mpn::try_from(m).unwrap();
+    if m == 1u32 {
+        // every residue is 0 modulo 1
+        return Ok(Some(Mpn::ZERO));
+    }
+    let (base, target) = (reduce(base, &m), reduce(target, &m));
+    let s = (&order).ceiling_sqrt();
+    if &s << 1u32 > max_steps {
+        bail!("discrete logarithm needs more than {max_steps} steps");
+    }
+    let s = u64::try_from(&s).unwrap();
+    // baby steps: baseʲ for 0 ≤ j < s, keeping the smallest exponent for each value
+    let mut table = HashMap::new();
+    let mut power = Mpn::ONE;
+    for j in 0..s {
+        table.entry(power.clone()).or_insert(j);
+        power = power.mod_mul(&base, &m);
+    }
+    // giant steps: target · base^(−si) for 0 ≤ i < s
+    let giant = (&base).mod_pow(Mpn::from(s), &m).mod_inverse(&m).unwrap();
+    let mut gamma = target;
+    for i in 0..s {
+        if let Some(j) = table.get(&gamma) {
+            return Ok(Some(Mpn::from(i) * Mpn::from(s) + Mpn::from(*j)));
+        }
+        gamma = gamma.mod_mul(&giant, &m);
+    }
+    Ok(None)
+}
+
+/// A square root of `a` modulo the odd prime `p` by Tonelli–Shanks, or `None` if `a` is a
+/// quadratic non-residue. The other root is `p` minus the returned one.
+pub fn sqrt_mod_prime(a: &Mpz, p: &Mpn) -> Option<Mpn> {
+    let a = reduce(a, p);
+    if a == 0u32 {
+        return Some(Mpn::ZERO);
+    }
+    let p_minus_one = p - Mpn::ONE;
+    let half = &p_minus_one >> 1u32;
+    // Euler's criterion
+    if (&a).mod_pow(&half, p) != 1u32 {
+        return None;
+    }
+    if p.mod_power_of_2(2) == 3u32 {
+        return Some(a.mod_pow((p + Mpn::ONE) >> 2u32, p));
+    }
+    // p - 1 = q · 2ˢ with q odd, and z is any quadratic non-residue
+    let s = p_minus_one.trailing_zeros().unwrap();
+    let q = &p_minus_one >> s;
+    let mut z = Mpn::TWO;
+    while (&z).mod_pow(&half, p) != p_minus_one {
+        z += Mpn::ONE;
+    }
+    let mut m = s;
+    let mut c = z.mod_pow(&q, p);
+    let mut t = (&a).mod_pow(&q, p);
+    let mut r = a.mod_pow((q + Mpn::ONE) >> 1u32, p);
+    while t != 1u32 {
+        // the least i with t^(2^i) = 1
+        let mut i = 0;
+        let mut t_pow = t.clone();
+        while t_pow != 1u32 {
+            t_pow = t_pow.mod_square(p);
+            i += 1;
+        }
+        let b = c.mod_pow(Mpn::power_of_2(m - i - 1), p);
+        m = i;
+        c = (&b).mod_square(p);
+        t = t.mod_mul(&c, p);
+        r = r.mod_mul(b, p);
+    }
+    Some(r)
+}
+
+/// All square roots of `b` modulo `pᵏ` for `b` coprime to the prime `p`, sorted.
+fn sqrt_mod_prime_power_coprime(b: &Mpn, p: &Mpn, k: u64) -> Vec<Mpn> {
+    let m = p.pow(k);
+    let mut roots = if *p == 2u32 {
+        match k {
+            1 => vec![Mpn::ONE],
+            2 if b.mod_power_of_2(2) == 1u32 => vec![Mpn::ONE, Mpn::from(3u32)],
+            _ if k >= 3 && b.mod_power_of_2(3) == 1u32 => {
+                // lift a root of the form 1 (mod 8) one bit at a time
+                let mut r = Mpn::ONE;
+                for i in 3..k {
+                    if (&r * &r).mod_power_of_2(i + 1) != b.mod_power_of_2(i + 1) {
+                        r += Mpn::power_of_2(i - 1);
+                    }
+                }
+                let r2 = (&r + Mpn::power_of_2(k - 1)).mod_power_of_2(k);
+                vec![&m - &r, &m - &r2, r, r2]
+            }
+            _ => Vec::new(),
+        }
+    } else {
+        let Some(mut r) = sqrt_mod_prime(&Mpz::from(b), p) else {
+            return Vec::new();
+        };
+        // Hensel lifting: r ← r - (r² - b) / 2r modulo the next power of p
+        let mut pj = p.clone();
+        for _ in 1..k {
+            pj *= p;
+            let inv = (Mpn::TWO * &r % &pj).mod_inverse(&pj).unwrap();
+            let correction = (Mpz::from(&r * &r) - Mpz::from(b)) * Mpz::from(inv);
+            r = reduce(&(Mpz::from(r) - correction), &pj);
+        }
+        vec![&m - &r, r]
+    };
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+/// All square roots of `a` modulo `pᵏ` for a prime `p`, sorted ascending. An error is returned
+/// if there are more than `max_roots` of them, which can only happen when `p` divides `a`.
+pub fn sqrt_mod_prime_power(
+    a: &Mpz,
+    p: &Mpn,
+    k: u64,
+    max_roots: u64,
+) -> Result<Vec<Mpn>, anyhow::Error> {
+    if k == 0 {
+        return Ok(vec![Mpn::ZERO]);
+    }
+    let m = p.pow(k);
+    let mut b = reduce(a, &m);
+    // a = pᵛ · b with b coprime to p
+    let mut v = 0;
+    while v < k && (&b % p) == 0u32 {
+        b /= p;
+        v += 1;
+    }
+    let exceeds = |count: &Mpn| *count > max_roots;
+    if v == k {
+        // x² ≡ 0 exactly when p^⌈k/2⌉ divides x
+        let count = p.pow(k / 2);
+        if exceeds(&count) {
+            bail!("there are more than {max_roots} square roots");
+        }
+        let step = p.pow(k.div_ceil(2));
+        let count = u64::try_from(&count).unwrap();
+        return Ok((0..count).map(|t| Mpn::from(t) * &step).collect());
+    }
+    if v % 2 == 1 {
+        return Ok(Vec::new());
+    }
+    // x = p^(v/2) · y with y² ≡ b (mod p^(k-v)), and each such y has p^(v/2) lifts modulo
+    // p^(k - v/2)
+    let ys = sqrt_mod_prime_power_coprime(&b, p, k - v);
+    let lifts = p.pow(v / 2);
+    if exceeds(&(Mpn::from(ys.len()) * &lifts)) {
+        bail!("there are more than {max_roots} square roots");
+    }
+    let lifts = u64::try_from(&lifts).unwrap();
+    let (scale, step) = (p.pow(v / 2), p.pow(k - v));
+    let mut roots = Vec::new();
+    for y in &ys {
+        for t in 0..lifts {
+            roots.push((&scale * (y + Mpn::from(t) * &step)) % &m);
+        }
+    }
+    roots.sort();
+    Ok(roots)
+}
+
+/// All square roots of `a` modulo `n`, sorted ascending. The roots modulo each prime power
+/// dividing `n` are combined with [`crt`]; an error is returned if there are more than
+/// `max_roots` of them, or if `n` cannot be factored within [`primes::FACTOR_STEPS`] steps.
+pub fn sqrt_mod(a: &Mpz, n: &Mpz, max_roots: u64) -> Result<Vec<Mpn>, anyhow::Error> {
+    if *n <= 0 {
+        bail!("modulus must be positive");
+    }
+    let n = Mpn::try_from(n).unwrap();
+    let mut roots = vec![Mpn::ZERO];
+    let mut modulus = Mpn::ONE;
+    for (p, e) in primes::factorize_bounded(&n)? {
+        let local = sqrt_mod_prime_power(a, &p, e, max_roots)?;
+        if Mpn::from(roots.len()) * Mpn::from(local.len()) > max_roots {
+            bail!("there are more than {max_roots} square roots");
+        }
+        let pe = Mpz::from(p.pow(e));
+        let moduli = [Mpz::from(&modulus), pe.clone()];
+        let mut combined = Vec::with_capacity(roots.len() * local.len());
+        for r in &roots {
+            for s in &local {
+                let residues = [Mpz::from(r), Mpz::from(s)];
+                combined.push(crt(&residues, &moduli).unwrap().0);
+            }
+        }
+        roots = combined;
+        modulus *= Mpn::try_from(pe).unwrap();
+    }
+    roots.sort();
+    Ok(roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mpz(n: i64) -> Mpz {
+        Mpz::from(n)
+    }
+
+    #[test]
+    fn small_orders() {
+        let order = |a, m| multiplicative_order(&mpz(a), &mpz(m)).unwrap();
+        assert_eq!(order(2, 7), 3u32);
+        assert_eq!(order(3, 7), 6u32);
+        // the decimal period of 1/7
+        assert_eq!(order(10, 7), 6u32);
+        assert_eq!(order(-1, 7), 2u32);
+        assert_eq!(order(7, 1), 1u32);
+        // (ℤ/2⁵ℤ)^× ≅ C₂ × C₈
+        assert_eq!(order(3, 32), 8u32);
+        assert!(multiplicative_order(&mpz(2), &mpz(8)).is_err());
+        assert!(multiplicative_order(&mpz(2), &mpz(0)).is_err());
+    }
+
+    #[test]
+    fn discrete_log_small() {
+        let log = |base, target, m| discrete_log(&mpz(base), &mpz(target), &mpz(m), 1000).unwrap();
+        assert_eq!(log(3, 13, 17), Some(Mpn::from(4u32)));
+        assert_eq!(log(2, 1, 7), Some(Mpn::ZERO));
+        // 3 is not a power of 2 modulo 7
+        assert_eq!(log(2, 3, 7), None);
+    }
+
+    #[test]
+    fn discrete_log_step_bound_for_64_bit_modulus() {
+        // the largest prime below 2⁶⁴
+        let p = Mpn::from(18_446_744_073_709_551_557u64);
+        let order = multiplicative_order(&mpz(2), &Mpz::from(&p)).unwrap();
+        assert_eq!(Mpn::TWO.mod_pow(order, &p), 1u32);
+        assert!(discrete_log(&mpz(2), &mpz(3), &Mpz::from(&p), 1 << 20).is_err());
+    }
+
+    #[test]
+    fn sqrt_mod_small_prime() {
+        let r = sqrt_mod_prime(&mpz(10), &Mpn::from(13u32)).unwrap();
+        assert!(r == 6u32 || r == 7u32);
+        assert_eq!(
+            sqrt_mod(&mpz(10), &mpz(13), 16).unwrap(),
+            [6u32, 7].map(Mpn::from)
+        );
+        assert_eq!(sqrt_mod_prime(&mpz(0), &Mpn::from(13u32)), Some(Mpn::ZERO));
+    }
+
+    #[test]
+    fn sqrt_mod_non_residue() {
+        // the squares modulo 13 are 1, 3, 4, 9, 10 and 12
+        assert_eq!(sqrt_mod_prime(&mpz(5), &Mpn::from(13u32)), None);
+        assert!(sqrt_mod(&mpz(5), &mpz(13), 16).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sqrt_mod_256_bit_primes() {
+        // 2²⁵⁵ − 19 ≡ 5 (mod 8) goes through Tonelli–Shanks, and the secp256k1 prime
+        // 2²⁵⁶ − 2³² − 977 ≡ 3 (mod 4) through the shortcut
+        let p1 = Mpn::power_of_2(255) - Mpn::from(19u32);
+        let p2 = Mpn::power_of_2(256) - Mpn::power_of_2(32) - Mpn::from(977u32);
+        for p in [p1, p2] {
+            let x = (&p >> 3u32) + Mpn::from(12345u32);
+            let a = Mpz::from((&x).mod_square(&p));
+            let r = sqrt_mod_prime(&a, &p).unwrap();
+            assert!(r == x || r == &p - &x);
+            // a non-residue times a residue is a non-residue
+            let z = (2u32..)
+                .map(Mpn::from)
+                .find(|z| sqrt_mod_prime(&Mpz::from(z), &p).is_none());
+            let non_residue = Mpz::from(z.unwrap().mod_mul(Mpn::try_from(&a).unwrap(), &p));
+            assert_eq!(sqrt_mod_prime(&non_residue, &p), None);
+        }
+    }
+
+    #[test]
+    fn sqrt_mod_composite() {
+        assert_eq!(
+            sqrt_mod(&mpz(1), &mpz(15), 16).unwrap(),
+            [1u32, 4, 11, 14].map(Mpn::from)
+        );
+        assert_eq!(
+            sqrt_mod(&mpz(1), &mpz(8), 16).unwrap(),
+            [1u32, 3, 5, 7].map(Mpn::from)
+        );
+        assert!(sqrt_mod(&mpz(1), &mpz(3 * 5 * 7 * 11 * 13), 16).is_err());
+    }
+}
@@ -15,6 +15,7 @@ use malachite::{
             arithmetic::traits::{CheckedSub, Sign},
             basic::traits::{Infinity, NaN, One, Two, Zero},
             conversion::traits::FromStringBase,
+            logic::traits::SignificantBits,
         },
     },
 };
@@ -25,6 +26,16 @@ use crate::{
     traits::{ExtendedNumber, SignStrict, Ten},
 };
 
+/// Powers of more bits than this are refused before malachite tries to allocate them.
+pub const MAX_POWER_BITS: f64 = 1e7;
+
+/// `log₂ n` for a nonzero `n`, from its leading 64 bits.
+pub fn log2_natural(n: &Mpn) -> f64 {
+    let shift = n.significant_bits().saturating_sub(64);
+    let top = u64::try_from(&(n >> shift)).unwrap();
+    (top as f64).log2() + shift as f64
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 #[serde(try_from = "SerdeMpnExt", into = "SerdeMpnExt")]
 pub enum MpnExt {
@@ -60,8 +71,8 @@ impl TryFrom<SerdeMpnExt> for MpnExt {
             "inf" => Ok(MpnExt::INFINITY),
             "0" => Ok(MpnExt::ZERO),
             src => {
-                if src.starts_with("0x") {
-                    Ok(Integer(Mpn::from_string_base(16, &src[2..]).ok_or_else(
+                if let Some(digits) = src.strip_prefix("0x") {
+                    Ok(Integer(Mpn::from_string_base(16, digits).ok_or_else(
                         || anyhow!("Unrecognized digits in {}", src),
                     )?))
                 } else {
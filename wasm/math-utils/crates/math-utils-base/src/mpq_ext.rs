@@ -1,6 +1,7 @@
 use std::{
     cmp::Ordering,
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
     iter::{Product, Sum},
     ops::*,
     str::FromStr,
@@ -11,14 +12,23 @@ use malachite::{
     Integer as Mpz, Natural as Mpn, Rational as Mpq,
     base::{
         comparison::traits::{Max, Min},
-        num::{arithmetic::traits::*, basic::traits::*},
+        num::{
+            arithmetic::traits::*,
+            basic::traits::*,
+            conversion::traits::{RoundingFrom, ToStringBase},
+            logic::traits::SignificantBits,
+        },
+        rounding_modes::RoundingMode,
     },
+    rational::conversion::traits::ContinuedFraction,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{MpnExt, MpzExt, impl_product, impl_sum, parsing::*, traits::*};
+use crate::{
+    MAX_POWER_BITS, MpnExt, MpzExt, impl_product, impl_sum, modular, parsing::*, traits::*,
+};
 
-#[derive(Clone, Serialize, Deserialize, Hash)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum MpqExt {
     Zero(bool),
     Inf(bool),
@@ -186,6 +196,280 @@ impl MpqExt {
             (Integer(m), Integer(n)) => Self::from_integers_ref(m, n),
         }
     }
+
+    /// The greatest common divisor `gcd(p, r) / lcm(q, s)` of `p/q` and `r/s`, i.e. the largest
+    /// rational of which both are integer multiples. The result is never negative.
+    ///
+    /// `NaN` absorbs everything, zero acts as the identity and infinity absorbs every other value.
+    pub fn gcd(&self, other: &Self) -> Self {
+        use MpqExt::*;
+        match (self, other) {
+            (NaN, _) | (_, NaN) => NaN,
+            (Inf(_), _) | (_, Inf(_)) => Inf(true),
+            (Zero(_), x) | (x, Zero(_)) => x.abs(),
+            (Rational(x), Rational(y)) => {
+                let n = x.numerator_ref().gcd(y.numerator_ref());
+                let d = x.denominator_ref().lcm(y.denominator_ref());
+                Rational(Mpq::from_naturals(n, d))
+            }
+        }
+    }
+
+    /// The least common multiple `lcm(p, r) / gcd(q, s)` of `p/q` and `r/s`, i.e. the smallest
+    /// positive rational that is an integer multiple of both. The result is never negative.
+    ///
+    /// `NaN` absorbs everything, zero absorbs every other value, and infinity absorbs every
+    /// nonzero value.
+    pub fn lcm(&self, other: &Self) -> Self {
+        use MpqExt::*;
+        match (self, other) {
+            (NaN, _) | (_, NaN) => NaN,
+            (Zero(_), _) | (_, Zero(_)) => Zero(true),
+            (Inf(_), _) | (_, Inf(_)) => Inf(true),
+            (Rational(x), Rational(y)) => {
+                let n = x.numerator_ref().lcm(y.numerator_ref());
+                let d = x.denominator_ref().gcd(y.denominator_ref());
+                Rational(Mpq::from_naturals(n, d))
+            }
+        }
+    }
+
+    /// The `f64` nearest to `self`, with ties to even as in IEEE 754. Magnitudes of at least
+    /// `f64::MAX` plus half an ulp round to infinity.
+    pub fn to_f64_nearest(&self) -> f64 {
+        use MpqExt::*;
+        match self {
+            NaN => f64::NAN,
+            &Zero(s) => {
+                if s {
+                    0.0
+                } else {
+                    -0.0
+                }
+            }
+            &Inf(s) => {
+                if s {
+                    f64::INFINITY
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+            Rational(q) => {
+                // malachite saturates at f64::MAX, whereas IEEE rounding overflows to infinity
+                let overflow = Mpq::power_of_2(1024u64) - Mpq::power_of_2(970u64);
+                if q.abs() >= overflow {
+                    if q.sign().is_gt() {
+                        f64::INFINITY
+                    } else {
+                        f64::NEG_INFINITY
+                    }
+                } else {
+                    f64::rounding_from(q, RoundingMode::Nearest).0
+                }
+            }
+        }
+    }
+
+    /// The regular continued fraction `[a₀; a₁, …, aₙ]` of a finite value, as `a₀` and the
+    /// positive partial quotients after it. Of the two expansions of a rational number the shorter
+    /// one, not ending in 1, is returned. Returns `None` for infinities and `NaN`.
+    pub fn continued_fraction(&self) -> Option<(Mpz, Vec<Mpn>)> {
+        use MpqExt::*;
+        match self {
+            Zero(_) => Some((Mpz::ZERO, Vec::new())),
+            Inf(_) | NaN => None,
+            Rational(q) => {
+                let (a0, rest) = q.continued_fraction();
+                Some((a0, rest.collect()))
+            }
+        }
+    }
+
+    /// The positional expansion of a finite value in `base`, found by long division: the
+    /// expansion starts repeating as soon as a remainder recurs. At most `max_digits` fractional
+    /// digits are produced; if neither the end nor a full period is reached by then, the digits
+    /// so far are returned as non-repeating and `truncated` is set.
+    pub fn to_radix_expansion(
+        &self,
+        base: u32,
+        max_digits: usize,
+    ) -> Result<RadixExpansion, anyhow::Error> {
+        use MpqExt::*;
+        if !(2..=36).contains(&base) {
+            return Err(anyhow!("base {base} is out of range [2, 36]"));
+        }
+        let (negative, q) = match self {
+            &Zero(s) => (!s, Mpq::ZERO),
+            Rational(q) => (*q < 0u32, q.abs()),
+            Inf(_) | NaN => {
+                return Err(anyhow!("infinity or NaN has no positional expansion"));
+            }
+        };
+        let (numerator, denominator) = q.into_numerator_and_denominator();
+        let (int_part, mut remainder) = numerator.div_rem(&denominator);
+        let digit = |d: &Mpn| char::from_digit(u32::try_from(d).unwrap(), base).unwrap();
+        let mut digits = String::new();
+        // the position in `digits` at which each remainder was met
+        let mut seen = std::collections::HashMap::new();
+        let mut repeating = String::new();
+        let mut truncated = false;
+        while remainder != 0u32 {
+            if let Some(&start) = seen.get(&remainder) {
+                repeating = digits.split_off(start);
+                break;
+            }
+            if digits.len() == max_digits {
+                truncated = true;
+                break;
+            }
+            seen.insert(remainder.clone(), digits.len());
+            let d;
+            (d, remainder) = (remainder * Mpn::from(base)).div_rem(&denominator);
+            digits.push(digit(&d));
+        }
+        Ok(RadixExpansion {
+            negative,
+            int_digits: int_part.to_string_base(base as u8),
+            frac_digits: digits,
+            repeating_digits: repeating,
+            truncated,
+        })
+    }
+
+    /// The denominator of a finite value with its factors of 2 and 5 counted and removed, as
+    /// `(max(v₂, v₅), rest)`.
+    fn split_decimal_denominator(&self) -> Result<(u64, Mpn), anyhow::Error> {
+        use MpqExt::*;
+        let mut den = match self {
+            Zero(_) => Mpn::ONE,
+            Rational(q) => q.denominator_ref().clone(),
+            Inf(_) | NaN => return Err(anyhow!("infinity or NaN has no decimal expansion")),
+        };
+        let twos = den.trailing_zeros().unwrap_or(0);
+        den >>= twos;
+        let mut fives = 0;
+        let five = Mpn::from(5u32);
+        while (&den).divisible_by(&five) {
+            den /= &five;
+            fives += 1;
+        }
+        Ok((twos.max(fives), den))
+    }
+
+    /// The number of decimal digits after the point before the expansion starts repeating, which
+    /// is the larger power of 2 or 5 in the denominator.
+    pub fn decimal_preperiod(&self) -> Result<u64, anyhow::Error> {
+        Ok(self.split_decimal_denominator()?.0)
+    }
+
+    /// The length of the repeating block of the decimal expansion, 0 if it terminates. Once the
+    /// factors of 2 and 5 are removed from the denominator `q`, the period is the multiplicative
+    /// order of 10 modulo what is left.
+    pub fn decimal_period(&self) -> Result<Mpn, anyhow::Error> {
+        let (_, rest) = self.split_decimal_denominator()?;
+        if rest == 1u32 {
+            return Ok(Mpn::ZERO);
+        }
+        modular::multiplicative_order(&Mpz::TEN, &Mpz::from(rest))
+    }
+
+    /// The greatest integer not above `self`, as [`Floor`] for borrowed values. A zero result
+    /// keeps the sign of `self`, so `0.5` floors to `+0` and `-0` to `-0`; infinities and `NaN`
+    /// map to themselves.
+    pub fn floor_to_mpz(&self) -> MpzExt {
+        self.floor()
+    }
+
+    /// The least integer not below `self`, as [`Ceiling`] for borrowed values. A zero result
+    /// keeps the sign of `self`, so `-0.5` rounds up to `-0`.
+    pub fn ceil_to_mpz(&self) -> MpzExt {
+        self.ceiling()
+    }
+
+    /// The nearest integer to `self`, with ties going to the even one. A zero result keeps the
+    /// sign of `self`, so `-0.5` rounds to `-0`.
+    pub fn round_to_mpz(&self) -> MpzExt {
+        use MpqExt::*;
+        match self {
+            &Zero(s) => MpzExt::Zero(s),
+            &Inf(s) => MpzExt::Inf(s),
+            NaN => MpzExt::NAN,
+            Rational(q) => match Mpz::rounding_from(q, RoundingMode::Nearest).0 {
+                Mpz::ZERO => MpzExt::Zero(q.sign().is_gt()),
+                n => MpzExt::Integer(n),
+            },
+        }
+    }
+
+    /// The closest rational to `√self` with a denominator of at most `max_den`. Newton's
+    /// iteration `r ← (r + x / r) / 2` keeps the root between `x / r` and `r`, and runs until both
+    /// ends have the same closest fraction, which is then the one for the root as well since the
+    /// values sharing a closest fraction form an interval. Negative numbers give `NaN`.
+    ///
+    /// Each step doubles the size of the iterate, so the iteration stops with an error once it
+    /// exceeds [`MAX_POWER_BITS`]; values above that size and denominators above a quarter of it
+    /// are rejected up front.
+    pub fn sqrt_approx(&self, max_den: &Mpn) -> Result<MpqExt, anyhow::Error> {
+        use MpqExt::*;
+        if *max_den == 0u32 {
+            return Err(anyhow!("the maximum denominator must be positive"));
+        }
+        let max_den_bits = max_den.significant_bits();
+        if max_den_bits as f64 > MAX_POWER_BITS / 4.0 {
+            return Err(anyhow!(
+                "a maximum denominator of {max_den_bits} bits is above the limit of {:e} bits",
+                MAX_POWER_BITS / 4.0
+            ));
+        }
+        let x = match self {
+            NaN | Inf(false) => return Ok(NaN),
+            &Zero(s) => return Ok(Zero(s)),
+            Inf(true) => return Ok(Inf(true)),
+            Rational(q) if *q < 0u32 => return Ok(NaN),
+            Rational(q) => q,
+        };
+        let bits = x.significant_bits();
+        if bits as f64 > MAX_POWER_BITS {
+            return Err(anyhow!(
+                "a value of {bits} bits is above the limit of {MAX_POWER_BITS:e} bits"
+            ));
+        }
+        // a tiny root can round to zero, which is positive like the root
+        let wrap = |q: Mpq| if q == 0u32 { Zero(true) } else { Rational(q) };
+        let (num, den) = x.numerator_and_denominator_ref();
+        if let (Some(p), Some(q)) = (num.checked_sqrt(), den.checked_sqrt()) {
+            return Ok(wrap(Mpq::from_naturals(p, q).approx(max_den)));
+        }
+        // √(n / d) = √(nd) / d, which (⌊√(nd)⌋ + 1) / d exceeds
+        let mut upper = Mpq::from_naturals((num * den).floor_sqrt() + Mpn::ONE, den.clone());
+        loop {
+            if (&upper).significant_bits() as f64 > MAX_POWER_BITS {
+                return Err(anyhow!(
+                    "the square root did not settle within the limit of {MAX_POWER_BITS:e} bits"
+                ));
+            }
+            let lower = x / &upper;
+            let best = (&upper).approx(max_den);
+            if lower.approx(max_den) == best {
+                return Ok(wrap(best));
+            }
+            upper = (&upper + x / &upper) >> 1u64;
+        }
+    }
+}
+
+/// A positional expansion `±int.frac[repeating]` as produced by
+/// [`MpqExt::to_radix_expansion`], with lowercase letters for digits beyond 9.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RadixExpansion {
+    pub negative: bool,
+    pub int_digits: String,
+    /// The fractional digits before the period.
+    pub frac_digits: String,
+    /// The period, empty if the expansion terminates.
+    pub repeating_digits: String,
+    /// Whether the expansion was cut off at the digit limit before its end or period was found.
+    pub truncated: bool,
 }
 
 impl Sign for MpqExt {
@@ -227,37 +511,22 @@ impl SignStrict for MpqExt {
 impl ExtendedNumber for MpqExt {
     #[inline]
     fn is_nan(&self) -> bool {
-        use MpqExt::*;
-        match self {
-            NaN => true,
-            _ => false,
-        }
+        matches!(self, MpqExt::NaN)
     }
 
     #[inline]
     fn is_zero(&self) -> bool {
-        match self {
-            Self::Zero(_) => true,
-            _ => false,
-        }
+        matches!(self, Self::Zero(_))
     }
 
     #[inline]
     fn is_infinite(&self) -> bool {
-        use MpqExt::*;
-        match self {
-            Inf(_) => true,
-            _ => false,
-        }
+        matches!(self, MpqExt::Inf(_))
     }
 
     #[inline]
     fn is_finite(&self) -> bool {
-        use MpqExt::*;
-        match self {
-            Inf(_) | NaN => false,
-            _ => true,
-        }
+        !matches!(self, MpqExt::Inf(_) | MpqExt::NaN)
     }
 
     #[inline]
@@ -534,6 +803,9 @@ impl ReciprocalAssign for MpqExt {
     }
 }
 
+// Signed zeros follow IEEE 754 under rounding to nearest: `-0 + -0 = -0`, any other sum of zeros
+// is `+0`, and so is an exact cancellation `x + (-x)`. The owned, borrowed and assigning variants
+// below all agree on this.
 impl Add for MpqExt {
     type Output = Self;
 
@@ -604,30 +876,6 @@ impl AddAssign for MpqExt {
                 if q1 == 0 { Zero(true) } else { Rational(q1) }
             }
         }
-        // match (self, rhs) {
-        //     (NaN, _)
-        //     | (Zero(_), Zero(false))
-        //     | (Rational(_), Zero(_))
-        //     | (Inf(_), Zero(_) | Rational(_))
-        //     | (Inf(true), Inf(true))
-        //     | (Inf(false), Inf(false)) => {}
-        //     (a, NaN)
-        //     | (a @ Inf(true), Inf(false))
-        //     | (a @ Inf(false), Inf(true)) => *a = MpqExt::NaN,
-        //     (Zero(s), Zero(true)) => *s = true,
-        //     (a @ Zero(_), other @ (Rational(_) | Inf(_)))
-        //     | (a @ Rational(_), other @ Inf(_)) => *a = other,
-        //     (a @ Rational(_), Rational(q2)) => {
-        //         if let Rational(q1) = a {
-        //             *q1 += q2;
-        //             if q1 == &0 {
-        //                 *a = MpqExt::ZERO;
-        //             }
-        //         } else {
-        //             unreachable!();
-        //         }
-        //     },
-        // }
     }
 }
 
@@ -646,30 +894,6 @@ impl AddAssign<&Self> for MpqExt {
                 if q1 == 0 { Zero(true) } else { Rational(q1) }
             }
         }
-        // match (self, rhs) {
-        //     (NaN, _)
-        //     | (Zero(_), Zero(false))
-        //     | (Rational(_), Zero(_))
-        //     | (Inf(_), Zero(_) | Rational(_))
-        //     | (Inf(true), Inf(true))
-        //     | (Inf(false), Inf(false)) => {}
-        //     (a, NaN)
-        //     | (a @ Inf(true), Self::Inf(false))
-        //     | (a @ Inf(false), Self::Inf(true)) => *a = MpqExt::NaN,
-        //     (Zero(s), Zero(true)) => *s = true,
-        //     (a @ Zero(_), other @ (Rational(_) | Inf(_)))
-        //     | (a @ Self::Rational(_), other @ Inf(_)) => *a = other.clone(),
-        //     (a @ Rational(_), Rational(q2)) => {
-        //         if let Rational(q1) = a {
-        //             *q1 += q2;
-        //             if q1 == &0 {
-        //                 *a = MpqExt::ZERO;
-        //             }
-        //         } else {
-        //             unreachable!();
-        //         }
-        //     },
-        // }
     }
 }
 
@@ -795,7 +1019,7 @@ impl Sub for MpqExt {
             (Zero(_), other) => -other,
             (Inf(true), _) | (_, Inf(false)) => Self::INFINITY,
             (Inf(false), _) | (_, Inf(true)) => Self::NEGATIVE_INFINITY,
-            (Rational(q1), Rational(q2)) => Rational(q1 - q2),
+            (Rational(q1), Rational(q2)) => (q1 - q2).into(),
         }
     }
 }
@@ -813,7 +1037,7 @@ impl Sub<&Self> for MpqExt {
             (Zero(_), other) => -other,
             (Inf(true), _) | (_, Inf(false)) => Self::INFINITY,
             (Inf(false), _) | (_, Inf(true)) => Self::NEGATIVE_INFINITY,
-            (Rational(q1), Rational(q2)) => Rational(q1 - q2),
+            (Rational(q1), Rational(q2)) => (q1 - q2).into(),
         }
     }
 }
@@ -831,7 +1055,7 @@ impl Sub<MpqExt> for &MpqExt {
             (Zero(_), other) => -other,
             (Inf(true), _) | (_, Inf(false)) => MpqExt::INFINITY,
             (Inf(false), _) | (_, Inf(true)) => MpqExt::NEGATIVE_INFINITY,
-            (Rational(q1), Rational(q2)) => Rational(q1 - q2),
+            (Rational(q1), Rational(q2)) => (q1 - q2).into(),
         }
     }
 }
@@ -849,7 +1073,7 @@ impl Sub<Self> for &MpqExt {
             (Zero(_), other) => -other,
             (Inf(true), _) | (_, Inf(false)) => MpqExt::INFINITY,
             (Inf(false), _) | (_, Inf(true)) => MpqExt::NEGATIVE_INFINITY,
-            (Rational(q1), Rational(q2)) => Rational(q1 - q2),
+            (Rational(q1), Rational(q2)) => (q1 - q2).into(),
         }
     }
 }
@@ -899,7 +1123,7 @@ impl PowAssign<u64> for MpqExt {
                 if exp == 0 {
                     *self = MpqExt::ONE;
                 } else {
-                    *s = exp % 2 == 0;
+                    *s = exp.is_multiple_of(2);
                 }
             }
             Rational(q) => {
@@ -925,6 +1149,7 @@ impl PowAssign<i64> for MpqExt {
             Inf(true) if exp < 0 => *self = MpqExt::ZERO,
             Inf(s @ false) if exp > 0 => *s = exp % 2 == 0,
             Inf(false) => *self = Zero(exp % 2 == 0),
+            Rational(q) => q.pow_assign(exp),
             _ => {}
         }
     }
@@ -1036,6 +1261,61 @@ impl PartialEq for MpqExt {
     }
 }
 
+// Compares with the exact value of the float, so `1/10` is less than `0.1`, whose value is
+// `3602879701896397/36028797018963968`. As between `MpqExt`s, `-0` equals `0` and `NaN` is
+// unordered.
+impl PartialOrd<f64> for MpqExt {
+    fn partial_cmp(&self, other: &f64) -> Option<Ordering> {
+        use MpqExt::*;
+        match self {
+            NaN => None,
+            Zero(_) => 0.0.partial_cmp(other),
+            &Inf(s) => {
+                let inf = if s { f64::INFINITY } else { f64::NEG_INFINITY };
+                inf.partial_cmp(other)
+            }
+            Rational(q) => q.partial_cmp(other),
+        }
+    }
+}
+
+impl PartialEq<f64> for MpqExt {
+    fn eq(&self, other: &f64) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd<MpqExt> for f64 {
+    fn partial_cmp(&self, other: &MpqExt) -> Option<Ordering> {
+        other.partial_cmp(self).map(Ordering::reverse)
+    }
+}
+
+impl PartialEq<MpqExt> for f64 {
+    fn eq(&self, other: &MpqExt) -> bool {
+        other == self
+    }
+}
+
+// `Hash` must agree with `PartialEq`, which ignores the sign of zero.
+impl Hash for MpqExt {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use MpqExt::*;
+        match self {
+            Zero(_) => 0u8.hash(state),
+            Inf(s) => {
+                1u8.hash(state);
+                s.hash(state);
+            }
+            NaN => 2u8.hash(state),
+            Rational(q) => {
+                3u8.hash(state);
+                q.hash(state);
+            }
+        }
+    }
+}
+
 impl PartialOrdStrict for MpqExt {
     fn partial_cmp_strict(&self, other: &Self) -> Option<Ordering> {
         use MpqExt::*;
@@ -1096,17 +1376,12 @@ impl Approx<Mpn> for &MpqExt {
 impl ApproxAssign<Mpn> for MpqExt {
     fn approx_assign(&mut self, max_den: &Mpn) {
         use MpqExt::*;
-        match self {
-            rational @ Rational(_) => {
-                if let Rational(q) = rational {
-                    let orig_sign = q.sign().is_gt();
-                    q.approx_assign(max_den);
-                    if q.sign().is_eq() {
-                        *rational = Zero(orig_sign);
-                    }
-                }
+        if let Rational(q) = self {
+            let orig_sign = q.sign().is_gt();
+            q.approx_assign(max_den);
+            if q.sign().is_eq() {
+                *self = Zero(orig_sign);
             }
-            _ => {}
         }
     }
 }
@@ -1194,3 +1469,262 @@ impl Floor for &MpqExt {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every kind of value: signed zeros and infinities, `NaN` and rationals exact as floats.
+    const VALUES: [f64; 8] = [
+        0.0,
+        -0.0,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NAN,
+        1.5,
+        -1.5,
+        0.25,
+    ];
+
+    fn ext(x: f64) -> MpqExt {
+        MpqExt::try_from(x).unwrap()
+    }
+
+    /// Checks `result` against the IEEE 754 result `expected`, telling apart signed zeros.
+    fn check(result: &MpqExt, expected: f64, case: &str) {
+        if let MpqExt::Rational(q) = result {
+            assert_ne!(*q, 0u32, "{case}: zero stored as a rational");
+        }
+        let actual = result.to_f64_nearest();
+        assert!(
+            actual.to_bits() == expected.to_bits() || actual.is_nan() && expected.is_nan(),
+            "{case}: got {result:?}, expected {expected:?}"
+        );
+    }
+
+    type BinaryOp = fn(MpqExt, MpqExt) -> MpqExt;
+
+    /// Checks every path computing `a ∘ b` for every pair of values against `f`.
+    fn check_table(name: &str, f: fn(f64, f64) -> f64, paths: &[(&str, BinaryOp)]) {
+        for a in VALUES {
+            for b in VALUES {
+                for (path, op) in paths {
+                    let case = format!("{a:?} {name} {b:?} via {path}");
+                    check(&op(ext(a), ext(b)), f(a, b), &case);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn add() {
+        check_table(
+            "+",
+            |a, b| a + b,
+            &[
+                ("owned", |a, b| a + b),
+                ("borrowed rhs", |a, b| a + &b),
+                ("borrowed lhs", |a, b| &a + b),
+                ("borrowed", |a, b| &a + &b),
+                ("AddAssign", |mut a, b| {
+                    a += b;
+                    a
+                }),
+                ("AddAssign<&>", |mut a, b| {
+                    a += &b;
+                    a
+                }),
+                ("Sum", |a, b| [a, b].into_iter().sum()),
+            ],
+        );
+    }
+
+    #[test]
+    fn sub() {
+        check_table(
+            "-",
+            |a, b| a - b,
+            &[
+                ("owned", |a, b| a - b),
+                ("borrowed rhs", |a, b| a - &b),
+                ("borrowed lhs", |a, b| &a - b),
+                ("borrowed", |a, b| &a - &b),
+            ],
+        );
+    }
+
+    #[test]
+    fn mul() {
+        check_table(
+            "*",
+            |a, b| a * b,
+            &[
+                ("owned", |a, b| a * b),
+                ("borrowed rhs", |a, b| a * &b),
+                ("borrowed lhs", |a, b| &a * b),
+                ("borrowed", |a, b| &a * &b),
+                ("MulAssign", |mut a, b| {
+                    a *= b;
+                    a
+                }),
+                ("MulAssign<&>", |mut a, b| {
+                    a *= &b;
+                    a
+                }),
+                ("Product", |a, b| [a, b].into_iter().product()),
+            ],
+        );
+    }
+
+    #[test]
+    fn div() {
+        check_table("/", |a, b| a / b, &[("owned", |a, b| a / b)]);
+    }
+
+    #[test]
+    fn exact_rational_results() {
+        let (a, b) = (
+            MpqExt::from(Mpq::from_signeds(1, 3)),
+            MpqExt::from(Mpq::from_signeds(-1, 6)),
+        );
+        assert_eq!(a.clone() + b.clone(), MpqExt::from(Mpq::from_signeds(1, 6)));
+        assert_eq!(a.clone() - b.clone(), MpqExt::from(Mpq::from_signeds(1, 2)));
+        assert_eq!(
+            a.clone() * b.clone(),
+            MpqExt::from(Mpq::from_signeds(-1, 18))
+        );
+        assert_eq!(a.clone() / b.clone(), MpqExt::from(-2));
+        // an exact cancellation gives +0 on every path
+        let mut sum = a.clone();
+        sum += -a.clone();
+        check(&sum, 0.0, "1/3 += -1/3");
+        check(&(&a - &a), 0.0, "1/3 - 1/3");
+        check(&(-a.clone() + a), 0.0, "-1/3 + 1/3");
+    }
+
+    fn expansion(n: i64, d: i64, base: u32, max_digits: usize) -> RadixExpansion {
+        let x = MpqExt::from(Mpq::from_signeds(n, d));
+        x.to_radix_expansion(base, max_digits).unwrap()
+    }
+
+    fn radix(int: &str, frac: &str, repeating: &str, truncated: bool) -> RadixExpansion {
+        RadixExpansion {
+            negative: false,
+            int_digits: int.into(),
+            frac_digits: frac.into(),
+            repeating_digits: repeating.into(),
+            truncated,
+        }
+    }
+
+    #[test]
+    fn radix_expansions() {
+        // terminating, purely periodic and mixed
+        assert_eq!(expansion(1, 8, 2, 100), radix("0", "001", "", false));
+        assert_eq!(expansion(1, 7, 10, 100), radix("0", "", "142857", false));
+        assert_eq!(expansion(1, 6, 10, 100), radix("0", "1", "6", false));
+        assert_eq!(expansion(1, 3, 2, 100), radix("0", "", "01", false));
+        assert_eq!(expansion(1, 10, 16, 100), radix("0", "1", "9", false));
+        assert_eq!(expansion(255, 16, 16, 100), radix("f", "f", "", false));
+        let negative = RadixExpansion {
+            negative: true,
+            ..radix("3", "5", "", false)
+        };
+        assert_eq!(expansion(-7, 2, 10, 100), negative);
+        let zero = MpqExt::Zero(false).to_radix_expansion(10, 10).unwrap();
+        assert_eq!(
+            zero,
+            RadixExpansion {
+                negative: true,
+                ..radix("0", "", "", false)
+            }
+        );
+    }
+
+    #[test]
+    fn radix_expansion_truncation() {
+        // a period that ends exactly at the limit is still found
+        assert_eq!(expansion(1, 7, 10, 6), radix("0", "", "142857", false));
+        assert_eq!(expansion(1, 7, 10, 5), radix("0", "14285", "", true));
+        // the period of 1/7919 in base 10 is 3959 digits long
+        let e = expansion(1, 7919, 10, 1000);
+        assert!(e.truncated && e.repeating_digits.is_empty());
+        assert_eq!(e.frac_digits.len(), 1000);
+        let e = expansion(1, 7919, 10, 4000);
+        assert!(!e.truncated && e.frac_digits.is_empty());
+        assert_eq!(e.repeating_digits.len(), 3959);
+    }
+
+    #[test]
+    fn radix_expansion_errors() {
+        let x = MpqExt::from(Mpq::from_signeds(1, 3));
+        assert_eq!(
+            x.to_radix_expansion(1, 10).unwrap_err().to_string(),
+            "base 1 is out of range [2, 36]"
+        );
+        assert!(x.to_radix_expansion(37, 10).is_err());
+        assert!(MpqExt::Inf(true).to_radix_expansion(10, 10).is_err());
+        assert!(MpqExt::NaN.to_radix_expansion(10, 10).is_err());
+    }
+
+    #[test]
+    fn float_comparisons() {
+        let tenth = MpqExt::from(Mpq::from_unsigneds(1u32, 10u32));
+        let exact_tenth = MpqExt::from(Mpq::from_unsigneds(
+            3602879701896397u64,
+            36028797018963968u64,
+        ));
+        assert_eq!(exact_tenth.partial_cmp(&0.1), Some(Ordering::Equal));
+        assert!(exact_tenth == 0.1);
+        assert_eq!(tenth.partial_cmp(&0.1), Some(Ordering::Less));
+        assert!(tenth < 0.1);
+        assert_eq!(0.1.partial_cmp(&tenth), Some(Ordering::Greater));
+        assert!(tenth != 0.1);
+        // the float just below 0.1 is already below 1/10
+        assert!(tenth > 0.1f64.next_down());
+        // agrees with comparing the exact value of the float, signed zeros and infinities alike
+        for x in VALUES {
+            for y in VALUES {
+                assert_eq!(ext(x).partial_cmp(&y), x.partial_cmp(&y), "{x} <=> {y}");
+                assert_eq!(x.partial_cmp(&ext(y)), x.partial_cmp(&y), "{x} <=> {y}");
+                assert_eq!(ext(x) == y, x == y, "{x} == {y}");
+            }
+        }
+        assert_eq!(tenth.partial_cmp(&f64::NAN), None);
+        assert!(MpqExt::Inf(true) > f64::MAX);
+        assert!(-MpqExt::from(Mpq::from(u128::MAX)) > f64::NEG_INFINITY);
+        assert!(tenth > 5e-324);
+    }
+
+    #[test]
+    fn square_root_approximations_are_bounded() {
+        let two = MpqExt::from(Mpq::from(2u32));
+        assert_eq!(
+            two.sqrt_approx(&Mpn::from(12u32)).unwrap(),
+            MpqExt::from(Mpq::from_unsigneds(17u32, 12u32))
+        );
+        let max_den = Mpn::ONE << 1000u64;
+        let MpqExt::Rational(root) = two.sqrt_approx(&max_den).unwrap() else {
+            panic!("√2 is a positive rational approximation");
+        };
+        assert!(*root.denominator_ref() <= max_den);
+        assert!((&root * &root - Mpq::from(2u32)).abs() < Mpq::from_naturals(Mpn::ONE, max_den));
+        let too_big = Mpn::ONE << (MAX_POWER_BITS as u64 / 4);
+        let error = two.sqrt_approx(&too_big).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "a maximum denominator of {} bits is above the limit of {:e} bits",
+                MAX_POWER_BITS as u64 / 4 + 1,
+                MAX_POWER_BITS / 4.0
+            )
+        );
+        // the bits of the numerator and the denominator together
+        let huge = MpqExt::from(Mpq::from(Mpn::from(3u32) << 10_000_000u64));
+        let error = huge.sqrt_approx(&Mpn::ONE).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "a value of 10000003 bits is above the limit of 1e7 bits"
+        );
+    }
+}
@@ -12,18 +12,19 @@ use malachite::{
     base::{
         comparison::traits::{Max, Min},
         num::{
-            arithmetic::traits::{NegAssign, Pow, PowAssign, Sign, UnsignedAbs},
+            arithmetic::traits::{NegAssign, Parity, Pow, PowAssign, Sign, UnsignedAbs},
             basic::traits::{
                 Infinity, NaN, NegativeInfinity, NegativeOne, NegativeZero, One, Two, Zero,
             },
-            conversion::traits::FromStringBase,
+            conversion::traits::{FromStringBase, RoundingFrom},
         },
+        rounding_modes::RoundingMode,
     },
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    MpnExt, impl_product, impl_sum,
+    MAX_POWER_BITS, MpnExt, impl_product, impl_sum, log2_natural,
     traits::{ExtendedNumber, PartialOrdStrict, SignStrict, Ten},
 };
 
@@ -71,10 +72,10 @@ impl TryFrom<SerdeMpzExt> for MpzExt {
         } else if src == "-inf" {
             Ok(MpzExt::NEGATIVE_INFINITY)
         } else if src.starts_with('-') {
-            if src.starts_with("-0x") {
+            if let Some(digits) = src.strip_prefix("-0x") {
                 Ok(Integer(Mpz::from_sign_and_abs(
                     false,
-                    Mpn::from_string_base(16, &src[3..])
+                    Mpn::from_string_base(16, digits)
                         .ok_or_else(|| anyhow!("Unrecognized digits in {}", src))?,
                 )))
             } else {
@@ -83,9 +84,9 @@ impl TryFrom<SerdeMpzExt> for MpzExt {
                     src
                 )))
             }
-        } else if src.starts_with("0x") {
+        } else if let Some(digits) = src.strip_prefix("0x") {
             Ok(Integer(Mpz::from(
-                Mpn::from_string_base(16, &src[2..])
+                Mpn::from_string_base(16, digits)
                     .ok_or_else(|| anyhow!("Unrecognized digits in {}", src))?,
             )))
         } else {
@@ -224,6 +225,52 @@ impl_mpz_ext_from_int!(
     i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
 );
 
+macro_rules! impl_mpz_ext_float_conversion {
+    ($($t:ty),+$(,)?) => {
+        $(
+            /// Truncates toward zero. Infinities and `NaN` are rejected.
+            impl TryFrom<$t> for MpzExt {
+                type Error = anyhow::Error;
+                fn try_from(value: $t) -> Result<Self, Self::Error> {
+                    use MpzExt::*;
+                    if !value.is_finite() {
+                        return Err(anyhow!("infinity or NaN cannot be converted"));
+                    }
+                    let n = Mpz::rounding_from(value, RoundingMode::Down).0;
+                    Ok(match n {
+                        Mpz::ZERO => Zero(value.is_sign_positive()),
+                        n => Integer(n),
+                    })
+                }
+            }
+
+            /// Rounds to the nearest float. Fails if a finite integer is too large for the
+            /// float type.
+            impl TryInto<$t> for MpzExt {
+                type Error = anyhow::Error;
+                fn try_into(self) -> Result<$t, Self::Error> {
+                    use MpzExt::*;
+                    match self {
+                        NaN => Ok(<$t>::NAN),
+                        Zero(s) => Ok(if s { 0.0 } else { -0.0 }),
+                        Inf(s) => Ok(if s { <$t>::INFINITY } else { <$t>::NEG_INFINITY }),
+                        Integer(n) => {
+                            let x = <$t>::rounding_from(&n, RoundingMode::Nearest).0;
+                            if x.is_finite() {
+                                Ok(x)
+                            } else {
+                                Err(anyhow!("integer is too large to be converted"))
+                            }
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_mpz_ext_float_conversion!(f32, f64);
+
 impl FromStringBase for MpzExt {
     fn from_string_base(base: u8, s: &str) -> Option<Self> {
         use MpzExt::*;
@@ -701,7 +748,7 @@ impl PowAssign<u64> for MpzExt {
                 if exp == 0 {
                     *self = MpzExt::ONE;
                 } else {
-                    *s = exp % 2 == 0;
+                    *s = exp.is_multiple_of(2);
                 }
             }
             Integer(n) => n.pow_assign(exp),
@@ -727,3 +774,95 @@ impl Pow<u64> for &MpzExt {
         result
     }
 }
+
+// malachite has no powers with `Natural` exponents, so the exponent goes through `u64`. Powers
+// that would take more than [`MAX_POWER_BITS`] bits, which includes every exponent beyond a
+// `u64`, saturate to infinity unless the base is 0 or ±1, as they could not be stored anyway. An
+// infinite exponent follows IEEE 754 `pow`.
+impl PowAssign<&MpnExt> for MpzExt {
+    fn pow_assign(&mut self, exp: &MpnExt) {
+        use MpzExt::*;
+        match exp {
+            MpnExt::NaN => *self = MpzExt::NAN,
+            MpnExt::Zero => self.pow_assign(0u64),
+            MpnExt::Integer(n) => {
+                let storable = u64::try_from(n).ok().filter(|&e| match self {
+                    Integer(m) if *m.unsigned_abs_ref() > 1u32 => {
+                        log2_natural(m.unsigned_abs_ref()) * e as f64 <= MAX_POWER_BITS
+                    }
+                    _ => true,
+                });
+                match storable {
+                    Some(n) => self.pow_assign(n),
+                    None => {
+                        let even = n.even();
+                        match self {
+                            NaN => {}
+                            Zero(s) | Inf(s) => *s |= even,
+                            Integer(m) => {
+                                if *m == Mpz::NEGATIVE_ONE {
+                                    if even {
+                                        *self = MpzExt::ONE;
+                                    }
+                                } else if *m != Mpz::ONE {
+                                    *self = Inf(m.sign().is_gt() | even);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            MpnExt::Inf => match self {
+                NaN => {}
+                Zero(_) => *self = MpzExt::ZERO,
+                Inf(_) => *self = MpzExt::INFINITY,
+                Integer(m) => {
+                    if *m == Mpz::ONE || *m == Mpz::NEGATIVE_ONE {
+                        *self = MpzExt::ONE;
+                    } else {
+                        *self = MpzExt::INFINITY;
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl PowAssign<MpnExt> for MpzExt {
+    fn pow_assign(&mut self, exp: MpnExt) {
+        self.pow_assign(&exp);
+    }
+}
+
+impl PowAssign<Mpn> for MpzExt {
+    fn pow_assign(&mut self, exp: Mpn) {
+        self.pow_assign(&MpnExt::from(exp));
+    }
+}
+
+macro_rules! impl_pow_for_mpz_ext {
+    ($($t:ty),+$(,)?) => {
+        $(
+            impl Pow<$t> for MpzExt {
+                type Output = Self;
+
+                fn pow(mut self, exp: $t) -> Self::Output {
+                    self.pow_assign(exp);
+                    self
+                }
+            }
+
+            impl Pow<$t> for &MpzExt {
+                type Output = MpzExt;
+
+                fn pow(self, exp: $t) -> Self::Output {
+                    let mut result = self.clone();
+                    result.pow_assign(exp);
+                    result
+                }
+            }
+        )*
+    };
+}
+
+impl_pow_for_mpz_ext!(MpnExt, Mpn);
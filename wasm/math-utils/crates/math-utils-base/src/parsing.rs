@@ -18,7 +18,7 @@ struct FractionFromDecimalResult {
 }
 
 fn split_decimal_notation(src: &str) -> Result<FractionFromDecimalResult, anyhow::Error> {
-    let (src, mut exp) = if let Some(idx) = src.find(&['E', 'e']) {
+    let (src, mut exp) = if let Some(idx) = src.find(['E', 'e']) {
         (
             &src[..idx],
             src[idx + 1..]
@@ -26,12 +26,12 @@ fn split_decimal_notation(src: &str) -> Result<FractionFromDecimalResult, anyhow
                 .context("Invalid exponent value.")?,
         )
     } else {
-        (&src[..], 0)
+        (src, 0)
     };
-    let (src, sign) = match src.chars().nth(0) {
+    let (src, sign) = match src.chars().next() {
         Some('-') => (&src[1..], false),
         Some('+') => (&src[1..], true),
-        _ => (&src[..], true),
+        _ => (src, true),
     };
     if let Some(idx) = src.find('.') {
         // has decimal point
@@ -160,10 +160,10 @@ where
     } = from_decimal_result;
     let int_part = &int_part[..];
     let repeating_part = &repeating_part[..];
-    if !int_part.chars().all(|c| c.is_digit(10)) {
+    if !int_part.chars().all(|c| c.is_ascii_digit()) {
         bail!("Invalid integer part")
     }
-    if !repeating_part.chars().all(|c| c.is_digit(10)) {
+    if !repeating_part.chars().all(|c| c.is_ascii_digit()) {
         bail!("Invalid repeating part")
     }
     let repeating_part_len = repeating_part.len() as u64;
@@ -216,7 +216,7 @@ where
         + One
         + From<u8>,
 {
-    Ok(fraction_from_decimal(split_decimal_notation(src)?)?)
+    fraction_from_decimal(split_decimal_notation(src)?)
 }
 
 fn parse_fraction<T, E>(src: &str) -> Result<ParseFractionResult<T>, anyhow::Error>
@@ -257,7 +257,7 @@ where
                     sign = !sign;
                     &num_src[1..]
                 }
-                _ => &num_src[..],
+                _ => num_src,
             };
             let den_src = match den_src.chars().next() {
                 Some('+') => &den_src[1..],
@@ -265,7 +265,7 @@ where
                     sign = !sign;
                     &den_src[1..]
                 }
-                _ => &den_src[..],
+                _ => den_src,
             };
 
             let num = if num_src.is_empty() {
@@ -0,0 +1,155 @@
+//! Continued fractions of quadratic irrationals and Pell's equation `x² − d·y² = 1`.
+
+use anyhow::bail;
+use malachite::{
+    Natural as Mpn,
+    base::num::{
+        arithmetic::traits::FloorSqrt,
+        basic::traits::{One, Zero},
+        logic::traits::SignificantBits,
+    },
+};
+
+/// The longest period expanded. Periods grow like `√d`, and the fundamental solution of
+/// Pell's equation has about as many digits as the period of `√d` has terms.
+pub const MAX_PERIOD: usize = 10_000;
+
+/// The most bits [`pell_solutions`] returns in total. The `n`-th solution is `n` times as long
+/// as the first, so the total grows quadratically with the number of solutions.
+pub const MAX_SOLUTION_BITS: u64 = 100_000_000;
+
+/// Expands `√d` as `[a₀; a₁, …, aᵣ]` with the block `a₁, …, aᵣ` repeating forever. Fails if
+/// `d` is a perfect square, in which case the expansion is just `[√d]`, or if the period is
+/// longer than [`MAX_PERIOD`].
+pub fn sqrt_continued_fraction(d: &Mpn) -> Result<(Mpn, Vec<Mpn>), anyhow::Error> {
+    let a0 = d.floor_sqrt();
+    if &a0 * &a0 == *d {
+        bail!("{d} is a perfect square");
+    }
+    // the complete quotients are (√d + m) / q, and the period ends at the first aᵢ = 2a₀
+    let two_a0 = &a0 << 1u32;
+    let (mut m, mut q, mut a) = (Mpn::ZERO, Mpn::ONE, a0.clone());
+    let mut period = Vec::new();
+    while a != two_a0 {
+        if period.len() == MAX_PERIOD {
+            bail!("the period of √{d} is longer than the limit of {MAX_PERIOD} terms");
+        }
+        m = &q * &a - m;
+        q = (d - &m * &m) / q;
+        a = (&a0 + &m) / &q;
+        period.push(a.clone());
+    }
+    Ok((a0, period))
+}
+
+/// The smallest positive solution `(x, y)` of `x² − d·y² = 1`, read off from the convergents of
+/// `√d`. Fails if `d` is a perfect square, since then only the trivial solution exists, and
+/// where [`sqrt_continued_fraction`] does.
+pub fn pell_fundamental_solution(d: &Mpn) -> Result<(Mpn, Mpn), anyhow::Error> {
+    let (a0, period) = sqrt_continued_fraction(d)?;
+    // p₋₁ / q₋₁ = 1 / 0 and p₀ / q₀ = a₀ / 1
+    let (mut p_prev, mut q_prev) = (Mpn::ONE, Mpn::ZERO);
+    let (mut p, mut q) = (a0, Mpn::ONE);
+    // the solution is the convergent just before the end of the first period if its length is
+    // even, and of the second period otherwise
+    let steps = if period.len() % 2 == 0 {
+        period.len() - 1
+    } else {
+        2 * period.len() - 1
+    };
+    for a in period.iter().cycle().take(steps) {
+        (p_prev, p) = (p.clone(), a * &p + p_prev);
+        (q_prev, q) = (q.clone(), a * &q + q_prev);
+    }
+    Ok((p, q))
+}
+
+/// The first `k` positive solutions of `x² − d·y² = 1` in increasing order, generated from the
+/// fundamental solution `(x₁, y₁)` by `xₙ₊₁ + yₙ₊₁√d = (x₁ + y₁√d)(xₙ + yₙ√d)`. Fails if they
+/// would take more than [`MAX_SOLUTION_BITS`] bits.
+pub fn pell_solutions(d: &Mpn, k: u64) -> Result<Vec<(Mpn, Mpn)>, anyhow::Error> {
+    let (x1, y1) = pell_fundamental_solution(d)?;
+    // xₙ has about n times the bits of x₁, and yₙ fewer
+    let bits = (k as u128 * (k as u128 + 1)).saturating_mul(x1.significant_bits() as u128);
+    if bits > MAX_SOLUTION_BITS as u128 {
+        bail!(
+            "{k} solutions would take about {bits} bits, more than the limit of {MAX_SOLUTION_BITS}"
+        );
+    }
+    let mut solutions = Vec::with_capacity(k as usize);
+    let (mut x, mut y) = (x1.clone(), y1.clone());
+    for _ in 0..k {
+        let next = (&x1 * &x + d * &y1 * &y, &x1 * &y + &y1 * &x);
+        solutions.push((x, y));
+        (x, y) = next;
+    }
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mpn(n: u64) -> Mpn {
+        Mpn::from(n)
+    }
+
+    #[test]
+    fn continued_fraction_of_sqrt_7() {
+        let (a0, period) = sqrt_continued_fraction(&mpn(7)).unwrap();
+        assert_eq!(a0, 2);
+        assert_eq!(period, [1, 1, 1, 4].map(mpn));
+    }
+
+    #[test]
+    fn perfect_squares_are_rejected() {
+        assert!(sqrt_continued_fraction(&mpn(49)).is_err());
+        assert!(pell_fundamental_solution(&mpn(1)).is_err());
+    }
+
+    #[test]
+    fn long_periods_are_rejected() {
+        // the period of √1000000007 has 12352 terms
+        assert!(sqrt_continued_fraction(&mpn(1_000_000_007)).is_err());
+        assert!(pell_fundamental_solution(&mpn(1_000_000_007)).is_err());
+    }
+
+    #[test]
+    fn fundamental_solutions() {
+        assert_eq!(
+            pell_fundamental_solution(&mpn(2)).unwrap(),
+            (mpn(3), mpn(2))
+        );
+        // odd period
+        assert_eq!(
+            pell_fundamental_solution(&mpn(61)).unwrap(),
+            (mpn(1_766_319_049), mpn(226_153_980))
+        );
+        assert_eq!(
+            pell_fundamental_solution(&mpn(109)).unwrap(),
+            (mpn(158_070_671_986_249), mpn(15_140_424_455_100))
+        );
+        // √123456791 has a period of 2500 terms
+        let d = mpn(123_456_791);
+        let (x, y) = pell_fundamental_solution(&d).unwrap();
+        assert_eq!(&x * &x, &d * &y * &y + Mpn::ONE);
+    }
+
+    #[test]
+    fn solutions_in_order() {
+        let d = mpn(7);
+        let solutions = pell_solutions(&d, 4).unwrap();
+        assert_eq!(solutions[..2], [(mpn(8), mpn(3)), (mpn(127), mpn(48))]);
+        for ((x, y), (next_x, _)) in solutions.iter().zip(&solutions[1..]) {
+            assert_eq!(x * x, &d * y * y + Mpn::ONE);
+            assert!(x < next_x);
+        }
+        assert!(pell_solutions(&d, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn too_many_solutions_are_rejected() {
+        assert!(pell_solutions(&mpn(7), u64::MAX).is_err());
+        assert!(pell_solutions(&mpn(61), 10_000).is_err());
+    }
+}
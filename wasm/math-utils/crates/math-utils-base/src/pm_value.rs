@@ -0,0 +1,194 @@
+use std::ops::*;
+
+use malachite::base::num::{arithmetic::traits::Abs, basic::traits::Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::{MpqExt, traits::*};
+
+/// `center ± delta`, the two values `center + delta` and `center - delta` carried through exact
+/// arithmetic together. The delta is kept as a magnitude, so it is never negative.
+///
+/// Sums and differences add the deltas, as the branches of independent `±` signs are not
+/// matched up: `(a ± b) - (c ± d) = (a - c) ± (b + d)` covers all four combinations. Scaling by
+/// `s` scales the delta by `|s|`, so a negative factor swaps the branches: the plus branch of
+/// `-(c ± d)` is `-(c - d)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "(MpqExt, MpqExt)", into = "(MpqExt, MpqExt)")]
+pub struct PmValue {
+    pub center: MpqExt,
+    pub delta: MpqExt,
+}
+
+impl PmValue {
+    pub fn new(center: MpqExt, delta: MpqExt) -> Self {
+        Self {
+            center,
+            delta: delta.abs(),
+        }
+    }
+
+    /// Whether both branches coincide, which makes this a plain value.
+    pub fn is_exact(&self) -> bool {
+        self.delta.is_zero()
+    }
+
+    /// The plus and minus branches `(center + delta, center - delta)`.
+    pub fn split(&self) -> (MpqExt, MpqExt) {
+        (&self.center + &self.delta, &self.center - &self.delta)
+    }
+}
+
+impl From<MpqExt> for PmValue {
+    fn from(value: MpqExt) -> Self {
+        Self::new(value, MpqExt::ZERO)
+    }
+}
+
+impl From<(MpqExt, MpqExt)> for PmValue {
+    fn from((center, delta): (MpqExt, MpqExt)) -> Self {
+        Self::new(center, delta)
+    }
+}
+
+impl From<PmValue> for (MpqExt, MpqExt) {
+    fn from(value: PmValue) -> Self {
+        (value.center, value.delta)
+    }
+}
+
+impl Neg for PmValue {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            center: -self.center,
+            delta: self.delta,
+        }
+    }
+}
+
+impl Add for PmValue {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            center: self.center + rhs.center,
+            delta: self.delta + rhs.delta,
+        }
+    }
+}
+
+impl Sub for PmValue {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            center: self.center - rhs.center,
+            delta: self.delta + rhs.delta,
+        }
+    }
+}
+
+impl Add<MpqExt> for PmValue {
+    type Output = Self;
+
+    fn add(self, rhs: MpqExt) -> Self::Output {
+        Self {
+            center: self.center + rhs,
+            delta: self.delta,
+        }
+    }
+}
+
+impl Sub<MpqExt> for PmValue {
+    type Output = Self;
+
+    fn sub(self, rhs: MpqExt) -> Self::Output {
+        Self {
+            center: self.center - rhs,
+            delta: self.delta,
+        }
+    }
+}
+
+impl Mul<MpqExt> for PmValue {
+    type Output = Self;
+
+    fn mul(self, rhs: MpqExt) -> Self::Output {
+        // an exact value stays exact even when scaled by an infinity
+        let delta = if self.is_exact() {
+            self.delta
+        } else {
+            self.delta * (&rhs).abs()
+        };
+        Self {
+            center: self.center * rhs,
+            delta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::Rational as Mpq;
+
+    use super::*;
+
+    fn q(n: i64, d: i64) -> MpqExt {
+        MpqExt::from(Mpq::from_signeds(n, d))
+    }
+
+    fn pm(center: MpqExt, delta: MpqExt) -> PmValue {
+        PmValue::new(center, delta)
+    }
+
+    #[test]
+    fn deltas_add_up() {
+        let x = pm(q(1, 2), q(1, 3));
+        let y = pm(q(2, 1), q(-1, 4));
+        assert_eq!(y.delta, q(1, 4));
+        assert_eq!(x.clone() + y.clone(), pm(q(5, 2), q(7, 12)));
+        assert_eq!(x.clone() - y, pm(q(-3, 2), q(7, 12)));
+        assert_eq!(x.clone() + q(1, 2), pm(q(1, 1), q(1, 3)));
+        assert_eq!(x.clone() - q(1, 2), pm(MpqExt::ZERO, q(1, 3)));
+        assert_eq!(-x.clone(), pm(q(-1, 2), q(1, 3)));
+        assert_eq!(x.split(), (q(5, 6), q(1, 6)));
+    }
+
+    #[test]
+    fn scaling_swaps_branches_by_sign() {
+        let x = pm(q(1, 1), q(1, 2));
+        let scaled = x.clone() * q(-2, 1);
+        assert_eq!(scaled, pm(q(-2, 1), q(1, 1)));
+        // the plus branch of the product is the product of the minus branch
+        assert_eq!(scaled.split(), (q(-1, 1), q(-3, 1)));
+        assert_eq!(x.split().1 * q(-2, 1), scaled.split().0);
+        assert_eq!(x.clone() * MpqExt::ZERO, pm(MpqExt::ZERO, MpqExt::ZERO));
+    }
+
+    #[test]
+    fn exact_values() {
+        let x = PmValue::from(q(3, 4));
+        assert!(x.is_exact());
+        assert_eq!(x.split(), (q(3, 4), q(3, 4)));
+        assert!((x.clone() + x.clone()).is_exact());
+        // an exact value stays exact even when scaled by an infinity
+        let scaled = x * MpqExt::Inf(true);
+        assert!(scaled.is_exact());
+        assert_eq!(scaled.center, MpqExt::Inf(true));
+        assert!(!pm(q(1, 1), q(-1, 5)).is_exact());
+    }
+
+    #[test]
+    fn special_values_propagate() {
+        let x = pm(q(1, 1), MpqExt::NaN);
+        assert!(!x.is_exact());
+        assert!(matches!(x.split(), (MpqExt::NaN, MpqExt::NaN)));
+        let x = pm(q(1, 1), MpqExt::Inf(true)) * q(-3, 1);
+        assert_eq!(x.delta, MpqExt::Inf(true));
+        assert!(matches!(x.split(), (MpqExt::Inf(true), MpqExt::Inf(false))));
+        let x = pm(MpqExt::Inf(true), q(1, 1)) + pm(MpqExt::Inf(false), q(1, 1));
+        assert!(matches!(x.center, MpqExt::NaN));
+        assert_eq!(pm(q(1, 1), MpqExt::Inf(false)).delta, MpqExt::Inf(true));
+    }
+}
@@ -0,0 +1,486 @@
+//! Primality testing and integer factorization for arbitrary-precision naturals.
+//!
+//! Everything here is deterministic: the WASM host offers no source of randomness, so
+//! Miller–Rabin uses a fixed set of bases, extended for large inputs by bases drawn from a
+//! generator seeded by the input, and Pollard's rho walks through fixed seeds.
+
+use anyhow::bail;
+use malachite::{
+    Natural as Mpn,
+    base::num::{
+        arithmetic::traits::{FloorSqrt, Gcd, ModMul, ModPow, ModSquare, Pow},
+        basic::traits::{One, Two, Zero},
+        factorization::traits::{IsPrime, Primes},
+        logic::traits::{BitAccess, SignificantBits},
+    },
+};
+
+/// The first 13 primes. Using all of them as Miller–Rabin bases gives a correct answer for every
+/// `n < 3.3 × 10²⁴`; above that bound the test is a strong probable-prime test.
+const WITNESSES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// The least strong pseudoprime to all of [`WITNESSES`].
+const DETERMINISTIC_BOUND: u128 = 3_317_044_064_679_887_385_961_981;
+
+/// The number of extra Miller–Rabin bases [`is_prime`] tries at or above
+/// [`DETERMINISTIC_BOUND`], each passing a composite with probability at most 1/4.
+const EXTRA_WITNESS_COUNT: usize = 64;
+
+/// Primes below this bound are removed by trial division before Pollard's rho is used.
+const TRIAL_DIVISION_BOUND: u64 = 1000;
+
+/// The most numbers a sieve may cover, which bounds both its memory and the size of its output.
+pub const MAX_SIEVE_SIZE: u64 = 100_000_000;
+
+/// The most entries of a table of smallest prime factors.
+pub const MAX_FACTOR_TABLE_SIZE: u64 = 10_000_000;
+
+/// The largest argument [`prime_pi`] accepts, which it handles in a few seconds.
+pub const MAX_PRIME_PI: u64 = 1_000_000_000_000;
+
+/// Segments are sieved by the primes up to this bound; numbers left over are tested directly.
+const SEGMENT_SIEVING_BOUND: u64 = 1 << 20;
+
+fn check_sieve_size(size: u64) -> Result<(), anyhow::Error> {
+    if size > MAX_SIEVE_SIZE {
+        bail!("sieving {size} numbers exceeds the limit of {MAX_SIEVE_SIZE}");
+    }
+    Ok(())
+}
+
+/// The sieve of Eratosthenes: whether each of `0..n` is prime.
+fn sieve(n: u64) -> Vec<bool> {
+    let n = n as usize;
+    let mut is_prime = vec![true; n];
+    for flag in is_prime.iter_mut().take(2) {
+        *flag = false;
+    }
+    let mut p = 2;
+    while p * p < n {
+        if is_prime[p] {
+            for multiple in (p * p..n).step_by(p) {
+                is_prime[multiple] = false;
+            }
+        }
+        p += 1;
+    }
+    is_prime
+}
+
+/// The primes below `n`.
+pub fn primes_below(n: u64) -> Result<Vec<u64>, anyhow::Error> {
+    check_sieve_size(n)?;
+    Ok(sieve(n)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(k, is_prime)| is_prime.then_some(k as u64))
+        .collect())
+}
+
+/// The primes in `[a, b]` by a segmented sieve, so that `b` may be as large as `u64::MAX` as long
+/// as the range is at most [`MAX_SIEVE_SIZE`] long. The segment is sieved by the primes up to
+/// `min(√b, 2²⁰)`, and the numbers that survive are tested individually when `√b` is larger.
+pub fn primes_in_range(a: u64, b: u64) -> Result<Vec<u64>, anyhow::Error> {
+    if a > b {
+        bail!("the range [{a}, {b}] is empty");
+    }
+    let width = b - a;
+    check_sieve_size(width.saturating_add(1))?;
+    let root = b.floor_sqrt();
+    let bound = root.min(SEGMENT_SIEVING_BOUND);
+    let mut is_prime = vec![true; width as usize + 1];
+    for k in a..=b.min(1) {
+        is_prime[(k - a) as usize] = false;
+    }
+    for p in primes_below(bound + 1)? {
+        // the first multiple of p in the range that is not p itself
+        let Some(first) = a.div_ceil(p).max(p).checked_mul(p) else {
+            continue;
+        };
+        if first > b {
+            continue;
+        }
+        for i in ((first - a) as usize..=width as usize).step_by(p as usize) {
+            is_prime[i] = false;
+        }
+    }
+    Ok(is_prime
+        .into_iter()
+        .zip(a..=b)
+        .filter(|&(survived, k)| survived && (root <= bound || k.is_prime()))
+        .map(|(_, k)| k)
+        .collect())
+}
+
+/// The number of primes up to `n`, by the Legendre-style recurrence behind the Meissel–Lehmer
+/// method: `S(v, p)`, the count of `2..=v` left after sieving by the primes below `p`, is only
+/// needed at the `2√n` values `v = ⌊n / i⌋`, and each prime `p ≤ √n` updates it by
+/// `S(v, p + 1) = S(v, p) - (S(⌊v / p⌋, p) - S(p - 1, p))` for `v ≥ p²`. This takes
+/// `O(n^(3/4))` time and `O(√n)` memory instead of sieving all of `[2, n]`.
+pub fn prime_pi(n: u64) -> Result<u64, anyhow::Error> {
+    if n > MAX_PRIME_PI {
+        bail!("counting the primes up to {n} exceeds the limit of {MAX_PRIME_PI}");
+    }
+    if n < 2 {
+        return Ok(0);
+    }
+    let root = n.floor_sqrt();
+    // small[v] = S(v, p) for v ≤ √n and large[i] = S(⌊n / i⌋, p) for i ≤ √n
+    let mut small: Vec<u64> = (0..=root).map(|v| v.saturating_sub(1)).collect();
+    let mut large: Vec<u64> = (0..=root).map(|i| (n / i.max(1)) - 1).collect();
+    for p in 2..=root {
+        // p is prime exactly when sieving by the smaller primes left it
+        if small[p as usize] == small[p as usize - 1] {
+            continue;
+        }
+        let below_p = small[p as usize - 1];
+        let square = p * p;
+        for i in 1..=root.min(n / square) {
+            let d = i * p;
+            let count = if d <= root {
+                large[d as usize]
+            } else {
+                small[(n / d) as usize]
+            };
+            large[i as usize] -= count - below_p;
+        }
+        for v in (square..=root).rev() {
+            small[v as usize] -= small[(v / p) as usize] - below_p;
+        }
+    }
+    Ok(large[1])
+}
+
+/// The smallest prime factor of each of `0..n`, with `0` for `0` and `1`, which have none.
+pub fn smallest_factor_table(n: u64) -> Result<Vec<u64>, anyhow::Error> {
+    if n > MAX_FACTOR_TABLE_SIZE {
+        bail!("a factor table of {n} entries exceeds the limit of {MAX_FACTOR_TABLE_SIZE}");
+    }
+    let mut table = vec![0; n as usize];
+    for p in 2..n {
+        if table[p as usize] != 0 {
+            continue;
+        }
+        for multiple in (p..n).step_by(p as usize) {
+            if table[multiple as usize] == 0 {
+                table[multiple as usize] = p;
+            }
+        }
+    }
+    Ok(table)
+}
+
+/// Whether the odd `n > 2` with `n - 1 = d · 2ˢ` is a strong probable prime to the base `a`.
+fn is_strong_probable_prime(n: &Mpn, d: &Mpn, s: u64, a: &Mpn) -> bool {
+    let n_minus_one = n - Mpn::ONE;
+    let mut x = a.mod_pow(d, n);
+    if x == 1u32 || x == n_minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = x.mod_square(n);
+        if x == n_minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+/// `(d, s)` with `n - 1 = d · 2ˢ` and `d` odd, for odd `n > 1`.
+fn split_power_of_two(n: &Mpn) -> (Mpn, u64) {
+    let n_minus_one = n - Mpn::ONE;
+    let mut s = 0u64;
+    while !n_minus_one.get_bit(s) {
+        s += 1;
+    }
+    (n_minus_one >> s, s)
+}
+
+/// Miller–Rabin primality test with the bases in [`WITNESSES`].
+pub fn is_probable_prime(n: &Mpn) -> bool {
+    if *n < 2u32 {
+        return false;
+    }
+    for p in WITNESSES {
+        if *n == p {
+            return true;
+        }
+        if (n % Mpn::from(p)) == 0u32 {
+            return false;
+        }
+    }
+    let (d, s) = split_power_of_two(n);
+    WITNESSES
+        .into_iter()
+        .all(|a| is_strong_probable_prime(n, &d, s, &Mpn::from(a)))
+}
+
+/// Primality test that is exact below [`DETERMINISTIC_BOUND`]. Larger `n` must also pass
+/// [`EXTRA_WITNESS_COUNT`] bases generated by SplitMix64 from a seed taken from `n`, so a
+/// composite is misjudged with probability at most `4⁻⁶⁴` for the bases drawn, though the same
+/// `n` always gets the same answer.
+pub fn is_prime(n: &Mpn) -> bool {
+    if !is_probable_prime(n) {
+        return false;
+    }
+    if *n < DETERMINISTIC_BOUND {
+        return true;
+    }
+    let (d, s) = split_power_of_two(n);
+    let mut state = n.limbs().next().unwrap() ^ n.significant_bits();
+    let mut next_base = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        // n exceeds 2⁶⁴, so every base from 2 up is below n - 1
+        Mpn::from((z ^ (z >> 31)).max(2))
+    };
+    (0..EXTRA_WITNESS_COUNT).all(|_| is_strong_probable_prime(n, &d, s, &next_base()))
+}
+
+/// Finds a non-trivial divisor of the odd composite `n` with Brent's variant of Pollard's rho,
+/// trying the polynomials `x² + c` for `c = 1, 2, …` in turn. Each step of the walk uses up one
+/// unit of `budget`; `None` is returned once it runs out.
+fn pollard_rho(n: &Mpn, budget: &mut u64) -> Option<Mpn> {
+    const BATCH: u64 = 128;
+    let mut spend = |steps: u64| -> Option<()> {
+        *budget = budget.checked_sub(steps)?;
+        Some(())
+    };
+    let mut c = Mpn::ONE;
+    loop {
+        let f = |x: &Mpn| (x.mod_square(n) + &c) % n;
+        let (mut x, mut y, mut ys) = (Mpn::TWO, Mpn::TWO, Mpn::TWO);
+        let mut g = Mpn::ONE;
+        let mut r = 1u64;
+        let mut q = Mpn::ONE;
+        while g == 1u32 {
+            x = y.clone();
+            spend(r)?;
+            for _ in 0..r {
+                y = f(&y);
+            }
+            let mut k = 0;
+            while k < r && g == 1u32 {
+                ys = y.clone();
+                spend(BATCH.min(r - k))?;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(&y);
+                    let diff = if x > y { &x - &y } else { &y - &x };
+                    q = q.mod_mul(diff, n);
+                }
+                g = (&q).gcd(n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == *n {
+            // the batch overshot; redo it one step at a time
+            loop {
+                spend(1)?;
+                ys = f(&ys);
+                let diff = if x > ys { &x - &ys } else { &ys - &x };
+                g = diff.gcd(n);
+                if g != 1u32 {
+                    break;
+                }
+            }
+        }
+        if g != *n {
+            return Some(g);
+        }
+        c += Mpn::ONE;
+    }
+}
+
+/// Adds the factorization of `n` with exponents multiplied by `exp` to `factors`, or returns
+/// `None` if the budget of [`pollard_rho`] runs out.
+fn factorize_into(n: Mpn, exp: u64, factors: &mut Vec<(Mpn, u64)>, budget: &mut u64) -> Option<()> {
+    if n == 1u32 {
+        return Some(());
+    }
+    if is_probable_prime(&n) {
+        match factors.iter_mut().find(|(p, _)| *p == n) {
+            Some((_, e)) => *e += exp,
+            None => factors.push((n, exp)),
+        }
+        return Some(());
+    }
+    let d = pollard_rho(&n, budget)?;
+    // divide out every copy of d at once so that prime powers are handled in a single step
+    let mut m = n;
+    let mut k = 0;
+    while (&m % &d) == 0u32 {
+        m /= &d;
+        k += 1;
+    }
+    factorize_into(d, exp * k, factors, budget)?;
+    factorize_into(m, exp, factors, budget)
+}
+
+/// Returns the prime factorization of `n` as `(prime, exponent)` pairs sorted by prime.
+/// Both `0` and `1` have an empty factorization.
+pub fn factorize(n: &Mpn) -> Vec<(Mpn, u64)> {
+    factorize_within(n, u64::MAX).unwrap()
+}
+
+/// [`factorize`] giving up with `None` after `max_steps` steps of Pollard's rho in total, which
+/// bounds the time spent on numbers with several large prime factors.
+pub fn factorize_within(n: &Mpn, max_steps: u64) -> Option<Vec<(Mpn, u64)>> {
+    let mut factors = Vec::new();
+    if *n == Mpn::ZERO {
+        return Some(factors);
+    }
+    let mut n = n.clone();
+    for p in u64::primes_less_than(&TRIAL_DIVISION_BOUND) {
+        let p = Mpn::from(p);
+        let mut k = 0;
+        while (&n % &p) == 0u32 {
+            n /= &p;
+            k += 1;
+        }
+        if k > 0 {
+            factors.push((p, k));
+        }
+    }
+    // the remaining factors are all at least `TRIAL_DIVISION_BOUND`
+    let (mut rest, mut budget) = (Vec::new(), max_steps);
+    factorize_into(n, 1, &mut rest, &mut budget)?;
+    rest.sort();
+    factors.extend(rest);
+    Some(factors)
+}
+
+/// The number of steps of Pollard's rho spent by [`factorize_bounded`], which gives up within a
+/// few seconds on numbers with two prime factors beyond 10¹⁵.
+pub const FACTOR_STEPS: u64 = 1 << 22;
+
+/// [`factorize_within`] with a budget of [`FACTOR_STEPS`], failing with an error when the
+/// budget runs out.
+pub fn factorize_bounded(n: &Mpn) -> Result<Vec<(Mpn, u64)>, anyhow::Error> {
+    match factorize_within(n, FACTOR_STEPS) {
+        Some(factors) => Ok(factors),
+        None => bail!("{n} could not be factored within {FACTOR_STEPS} steps"),
+    }
+}
+
+/// Writes `n = c² · r` with `r` square-free and returns `(c, r)`, so that `√n = c√r`. Fails
+/// like [`factorize_bounded`].
+pub fn square_free_decomposition(n: &Mpn) -> Result<(Mpn, Mpn), anyhow::Error> {
+    if *n == 0u32 {
+        return Ok((Mpn::ZERO, Mpn::ONE));
+    }
+    let mut c = Mpn::ONE;
+    let mut r = Mpn::ONE;
+    for (p, e) in factorize_bounded(n)? {
+        c *= (&p).pow(e / 2);
+        if e % 2 == 1 {
+            r *= p;
+        }
+    }
+    Ok((c, r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorize_within_gives_up() {
+        let (p, q) = (Mpn::from(1073741827u64), Mpn::from(2147483659u64));
+        let n = &p * &q;
+        assert_eq!(factorize_within(&n, 100), None);
+        assert_eq!(factorize_within(&n, u64::MAX), Some(vec![(p, 1), (q, 1)]));
+    }
+
+    #[test]
+    fn segmented_sieve_agrees_with_simple_sieve() {
+        let simple = primes_below(100_000).unwrap();
+        assert_eq!(simple.len(), 9592);
+        let ranges = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (4, 4),
+            (0, 100),
+            (97, 97),
+            (98, 100),
+            (1000, 5000),
+            (65_000, 65_600),
+            (99_000, 99_999),
+        ];
+        for (a, b) in ranges {
+            let expected: Vec<u64> = simple
+                .iter()
+                .copied()
+                .filter(|p| (a..=b).contains(p))
+                .collect();
+            assert_eq!(primes_in_range(a, b).unwrap(), expected, "[{a}, {b}]");
+        }
+        // past the sieving bound the survivors are tested one by one
+        for a in [1 << 41, u64::MAX - 1000] {
+            let b = a.saturating_add(1000);
+            let expected: Vec<u64> = (a..=b).filter(|k| k.is_prime()).collect();
+            assert_eq!(primes_in_range(a, b).unwrap(), expected);
+        }
+        let top = primes_in_range(u64::MAX - 100, u64::MAX).unwrap();
+        assert_eq!(top.last(), Some(&18446744073709551557));
+    }
+
+    #[test]
+    fn sieve_limits() {
+        assert!(primes_below(0).unwrap().is_empty());
+        assert_eq!(primes_below(3).unwrap(), [2]);
+        assert!(primes_below(MAX_SIEVE_SIZE + 1).is_err());
+        assert_eq!(
+            primes_in_range(5, 4).unwrap_err().to_string(),
+            "the range [5, 4] is empty"
+        );
+        assert!(primes_in_range(0, MAX_SIEVE_SIZE).is_err());
+        assert!(primes_in_range(0, u64::MAX).is_err());
+        assert!(smallest_factor_table(MAX_FACTOR_TABLE_SIZE + 1).is_err());
+    }
+
+    #[test]
+    fn smallest_factors() {
+        let table = smallest_factor_table(2000).unwrap();
+        assert_eq!(table[..8], [0, 0, 2, 3, 2, 5, 2, 7]);
+        for (k, &p) in (0u64..).zip(&table).skip(2) {
+            assert_eq!(k % p, 0);
+            assert!(p.is_prime());
+            assert!((2..p).all(|d| k % d != 0), "{k}");
+        }
+    }
+
+    #[test]
+    fn prime_counts_at_powers_of_ten() {
+        let published = [
+            0, 4, 25, 168, 1229, 9592, 78498, 664579, 5761455, 50847534, 455052511,
+        ];
+        for (k, &count) in published.iter().enumerate() {
+            assert_eq!(prime_pi(10u64.pow(k as u32)).unwrap(), count, "π(10^{k})");
+        }
+        // the sieve agrees just around a prime
+        assert_eq!(prime_pi(7918).unwrap(), 999);
+        assert_eq!(prime_pi(7919).unwrap(), 1000);
+        assert_eq!(prime_pi(0).unwrap(), 0);
+        assert_eq!(prime_pi(2).unwrap(), 1);
+        assert!(prime_pi(MAX_PRIME_PI + 1).is_err());
+    }
+
+    #[test]
+    fn square_free_decompositions() {
+        let decompose = |n: u64| square_free_decomposition(&Mpn::from(n)).unwrap();
+        assert_eq!(decompose(0), (Mpn::ZERO, Mpn::ONE));
+        assert_eq!(decompose(1), (Mpn::ONE, Mpn::ONE));
+        assert_eq!(decompose(72), (Mpn::from(6u32), Mpn::from(2u32)));
+        assert_eq!(
+            decompose(3 * 5 * 5 * 5 * 7),
+            (Mpn::from(5u32), Mpn::from(105u32))
+        );
+        assert_eq!(decompose(1 << 40), (Mpn::from(1u32 << 20), Mpn::ONE));
+    }
+}
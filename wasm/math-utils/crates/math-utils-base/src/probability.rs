@@ -0,0 +1,226 @@
+//! Exact probabilities of discrete distributions as rational numbers.
+
+use anyhow::bail;
+use malachite::{
+    Natural as Mpn, Rational as Mpq,
+    base::num::{
+        arithmetic::traits::{BinomialCoefficient, DivExact, Pow},
+        basic::traits::{One, Zero},
+    },
+};
+
+use crate::{MAX_POWER_BITS, log2_natural};
+
+/// The largest number of trials or population size accepted, which bounds the size of the
+/// binomial coefficients and the number of terms summed.
+pub const MAX_TRIALS: u64 = 1 << 16;
+
+fn check_trials(name: &str, n: u64) -> Result<(), anyhow::Error> {
+    if n > MAX_TRIALS {
+        bail!("{name} {n} is above the limit of {MAX_TRIALS}");
+    }
+    Ok(())
+}
+
+fn binom(n: u64, k: u64) -> Mpn {
+    if k > n {
+        Mpn::ZERO
+    } else {
+        Mpn::binomial_coefficient(Mpn::from(n), Mpn::from(k))
+    }
+}
+
+/// Checks `0 ≤ p ≤ 1` and that the powers of `p` up to the `n`-th stay within
+/// [`MAX_POWER_BITS`].
+fn check_probability(p: &Mpq, n: u64) -> Result<(), anyhow::Error> {
+    if *p < 0u32 || *p > 1u32 {
+        bail!("probability {p} is out of range [0, 1]");
+    }
+    check_trials("number of trials", n)?;
+    let bits = log2_natural(p.denominator_ref()) * n as f64;
+    if bits > MAX_POWER_BITS {
+        bail!(
+            "the powers of {p} would take about {bits:.3e} bits, more than the limit of \
+             {MAX_POWER_BITS:e}"
+        );
+    }
+    Ok(())
+}
+
+/// `P(X = k)` for `X ~ B(n, p)`, which is zero for `k > n`.
+pub fn binomial_pmf(n: u64, k: u64, p: &Mpq) -> Result<Mpq, anyhow::Error> {
+    check_probability(p, n)?;
+    if k > n {
+        return Ok(Mpq::ZERO);
+    }
+    let q = Mpq::ONE - p;
+    Ok(Mpq::from(binom(n, k)) * p.pow(k) * q.pow(n - k))
+}
+
+/// `P(X ≤ k)` for `X ~ B(n, p)`, which is one for `k ≥ n`.
+pub fn binomial_cdf(n: u64, k: u64, p: &Mpq) -> Result<Mpq, anyhow::Error> {
+    check_probability(p, n)?;
+    if k >= n {
+        return Ok(Mpq::ONE);
+    }
+    // with p = a/d and 1 - p = b/d, the sum is Σ binom(n, i) aⁱ bⁿ⁻ⁱ / dⁿ, whose numerator is
+    // bⁿ⁻ᵏ times sₖ = Σ binom(n, i) aⁱ bᵏ⁻ⁱ over i ≤ k, and sᵢ = sᵢ₋₁ b + binom(n, i) aⁱ
+    let (a, d) = p.numerator_and_denominator_ref();
+    let b = d - a;
+    let (mut sum, mut coeff, mut a_power) = (Mpn::ONE, Mpn::ONE, Mpn::ONE);
+    for i in 1..=k {
+        coeff = (coeff * Mpn::from(n - i + 1)).div_exact(Mpn::from(i));
+        a_power *= a;
+        sum = sum * &b + &coeff * &a_power;
+    }
+    Ok(Mpq::from_naturals(sum * (&b).pow(n - k), d.pow(n)))
+}
+
+fn check_hypergeometric(big_n: u64, big_k: u64, n: u64) -> Result<(), anyhow::Error> {
+    check_trials("population size", big_n)?;
+    if big_k > big_n {
+        bail!("number of successes {big_k} exceeds the population size {big_n}");
+    }
+    if n > big_n {
+        bail!("number of draws {n} exceeds the population size {big_n}");
+    }
+    Ok(())
+}
+
+/// `P(X = k)` for the number `X` of successes among `n` draws without replacement from a
+/// population of `big_n` containing `big_k` successes. Values of `k` outside the support give
+/// zero.
+pub fn hypergeometric_pmf(big_n: u64, big_k: u64, n: u64, k: u64) -> Result<Mpq, anyhow::Error> {
+    check_hypergeometric(big_n, big_k, n)?;
+    if k > n {
+        return Ok(Mpq::ZERO);
+    }
+    Ok(Mpq::from_naturals(
+        binom(big_k, k) * binom(big_n - big_k, n - k),
+        binom(big_n, n),
+    ))
+}
+
+/// `P(X ≤ k)` for the hypergeometric distribution of [`hypergeometric_pmf`].
+pub fn hypergeometric_cdf(big_n: u64, big_k: u64, n: u64, k: u64) -> Result<Mpq, anyhow::Error> {
+    check_hypergeometric(big_n, big_k, n)?;
+    if k >= n.min(big_k) {
+        return Ok(Mpq::ONE);
+    }
+    // the terms t = binom(K, i) binom(N - K, n - i) vanish below i₀ = n - (N - K), and each
+    // next one is t (K - i)(n - i) / (i + 1)(N - K + i + 1 - n)
+    let others = big_n - big_k;
+    let start = n.saturating_sub(others);
+    if k < start {
+        return Ok(Mpq::ZERO);
+    }
+    let mut term = binom(big_k, start) * binom(others, n - start);
+    let mut favorable = term.clone();
+    for i in start..k {
+        term = (term * Mpn::from(big_k - i) * Mpn::from(n - i))
+            .div_exact(Mpn::from(i + 1) * Mpn::from(others + i + 1 - n));
+        favorable += &term;
+    }
+    Ok(Mpq::from_naturals(favorable, binom(big_n, n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mpq(num: i64, den: i64) -> Mpq {
+        Mpq::from_signeds(num, den)
+    }
+
+    #[test]
+    fn tea_tasting() {
+        // the lady picks the 4 cups with milk poured first out of 8 and gets all of them right;
+        // the one-sided Fisher exact p-value is P(X ≥ 4)
+        assert_eq!(hypergeometric_pmf(8, 4, 4, 4).unwrap(), mpq(1, 70));
+        let p_value = Mpq::ONE - hypergeometric_cdf(8, 4, 4, 3).unwrap();
+        assert_eq!(p_value, mpq(1, 70));
+    }
+
+    #[test]
+    fn hypergeometric() {
+        assert_eq!(hypergeometric_pmf(8, 4, 4, 2).unwrap(), mpq(36, 70));
+        assert_eq!(hypergeometric_pmf(8, 4, 4, 5).unwrap(), 0u32);
+        assert_eq!(hypergeometric_cdf(8, 4, 4, 4).unwrap(), 1u32);
+        let total: Mpq = (0..=4)
+            .map(|k| hypergeometric_pmf(10, 3, 4, k).unwrap())
+            .sum();
+        assert_eq!(total, 1u32);
+        assert!(hypergeometric_pmf(8, 9, 4, 2).is_err());
+        assert!(hypergeometric_cdf(8, 4, 9, 2).is_err());
+    }
+
+    #[test]
+    fn binomial() {
+        let half = mpq(1, 2);
+        assert_eq!(binomial_pmf(4, 2, &half).unwrap(), mpq(3, 8));
+        assert_eq!(binomial_cdf(4, 1, &half).unwrap(), mpq(5, 16));
+        assert_eq!(binomial_pmf(4, 5, &half).unwrap(), 0u32);
+        assert_eq!(binomial_cdf(4, 5, &half).unwrap(), 1u32);
+        assert_eq!(binomial_pmf(3, 0, &Mpq::ZERO).unwrap(), 1u32);
+        assert_eq!(binomial_pmf(3, 3, &Mpq::ONE).unwrap(), 1u32);
+        let p = mpq(1, 3);
+        let total: Mpq = (0..=6).map(|k| binomial_pmf(6, k, &p).unwrap()).sum();
+        assert_eq!(total, 1u32);
+        assert_eq!(
+            binomial_cdf(6, 5, &p).unwrap(),
+            Mpq::ONE - binomial_pmf(6, 6, &p).unwrap()
+        );
+        assert!(binomial_pmf(4, 2, &mpq(3, 2)).is_err());
+        assert!(binomial_cdf(4, 2, &mpq(-1, 2)).is_err());
+    }
+
+    #[test]
+    fn sums_match_the_terms() {
+        let p = mpq(2, 7);
+        for k in 0..12 {
+            let expected: Mpq = (0..=k).map(|i| binomial_pmf(11, i, &p).unwrap()).sum();
+            assert_eq!(binomial_cdf(11, k, &p).unwrap(), expected, "k = {k}");
+        }
+        assert_eq!(binomial_cdf(5, 2, &Mpq::ZERO).unwrap(), 1u32);
+        assert_eq!(binomial_cdf(5, 2, &Mpq::ONE).unwrap(), 0u32);
+        // with 3 successes among 10 and 8 draws, at least one success is drawn
+        for (n, k) in [(8, 0), (8, 1), (8, 2), (4, 0), (4, 2), (7, 3)] {
+            let expected: Mpq = (0..=k)
+                .map(|i| hypergeometric_pmf(10, 3, n, i).unwrap())
+                .sum();
+            assert_eq!(
+                hypergeometric_cdf(10, 3, n, k).unwrap(),
+                expected,
+                "n = {n}, k = {k}"
+            );
+        }
+        assert_eq!(hypergeometric_cdf(10, 3, 8, 0).unwrap(), 0u32);
+    }
+
+    #[test]
+    fn sizes_are_limited() {
+        let half = mpq(1, 2);
+        assert!(binomial_pmf(MAX_TRIALS, 3, &half).is_ok());
+        let error = binomial_pmf(u64::MAX, 3, &half).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "number of trials {} is above the limit of {MAX_TRIALS}",
+                u64::MAX
+            )
+        );
+        assert!(binomial_cdf(u64::MAX, u64::MAX - 1, &half).is_err());
+        // (2⁻²⁰⁰)ⁿ for n = 60000 takes 1.2e7 bits
+        let tiny = Mpq::from_naturals(Mpn::ONE, Mpn::ONE << 200u32);
+        assert!(binomial_pmf(60000, 0, &tiny).is_err());
+        assert!(binomial_cdf(60000, 1, &tiny).is_err());
+        assert!(hypergeometric_pmf(u64::MAX, 1, 1, 1).is_err());
+        assert!(hypergeometric_cdf(MAX_TRIALS + 1, 1, 1, 0).is_err());
+        assert_eq!(
+            hypergeometric_cdf(MAX_TRIALS, 1, 1, 0)
+                .unwrap()
+                .denominator_ref(),
+            &MAX_TRIALS
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use std::cmp::Ordering;
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn, Rational as Mpq,
+    base::num::{
+        arithmetic::traits::{Lcm, Pow, Sign},
+        basic::traits::{One, Zero},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{MpqExt, primes, traits::*};
+
+/// The exact number `a + b√d` with rational `a`, `b` and a squarefree radicand `d`. Rational
+/// values have `b = 0` and `d = 1`, so equal numbers have equal parts.
+///
+/// Two irrational values can only be combined if they share the radicand, as the sum of square
+/// roots of different radicands is not of this form; rational values combine with any radicand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "(MpqExt, MpqExt, Mpn)", into = "(MpqExt, MpqExt, Mpn)")]
+pub struct QuadExt {
+    a: MpqExt,
+    b: MpqExt,
+    d: Mpn,
+}
+
+impl QuadExt {
+    /// `a + b√d`, with the square factors of `d` moved into `b`. The parts must be finite, and
+    /// `d` must factor within [`primes::FACTOR_STEPS`] steps.
+    pub fn new(a: MpqExt, b: MpqExt, d: Mpn) -> Result<Self, anyhow::Error> {
+        if !(a.is_finite() && b.is_finite()) {
+            bail!("the parts of a + b√d must be finite");
+        }
+        let (a, b): (Mpq, Mpq) = (a.try_into()?, b.try_into()?);
+        if d == 0u32 || b == 0u32 {
+            return Ok(Self::from_rational_parts(a, Mpq::ZERO, Mpn::ONE));
+        }
+        let (mut root, mut radicand) = (Mpn::ONE, Mpn::ONE);
+        for (p, e) in primes::factorize_bounded(&d)? {
+            root *= (&p).pow(e / 2);
+            if e % 2 == 1 {
+                radicand *= p;
+            }
+        }
+        Ok(Self::from_rational_parts(a, b * Mpq::from(root), radicand))
+    }
+
+    /// `a + b√d` for a squarefree `d`.
+    fn from_rational_parts(a: Mpq, b: Mpq, d: Mpn) -> Self {
+        if b == 0u32 || d == 1u32 {
+            let a = a + b * Mpq::from(d);
+            return Self {
+                a: a.into(),
+                b: MpqExt::ZERO,
+                d: Mpn::ONE,
+            };
+        }
+        Self {
+            a: a.into(),
+            b: b.into(),
+            d,
+        }
+    }
+
+    pub fn a(&self) -> &MpqExt {
+        &self.a
+    }
+
+    pub fn b(&self) -> &MpqExt {
+        &self.b
+    }
+
+    pub fn d(&self) -> &Mpn {
+        &self.d
+    }
+
+    pub fn is_rational(&self) -> bool {
+        self.d == 1u32
+    }
+
+    fn parts(&self) -> (Mpq, Mpq) {
+        (
+            self.a.clone().try_into().unwrap(),
+            self.b.clone().try_into().unwrap(),
+        )
+    }
+
+    /// The radicand shared by `self` and `other`.
+    fn common_radicand(&self, other: &Self) -> Result<Mpn, anyhow::Error> {
+        match (self.is_rational(), other.is_rational()) {
+            (true, _) => Ok(other.d.clone()),
+            (_, true) => Ok(self.d.clone()),
+            _ if self.d == other.d => Ok(self.d.clone()),
+            _ => bail!("√{} and √{} cannot be combined in a + b√d", self.d, other.d),
+        }
+    }
+
+    pub fn try_add(&self, other: &Self) -> Result<Self, anyhow::Error> {
+        let d = self.common_radicand(other)?;
+        let ((a1, b1), (a2, b2)) = (self.parts(), other.parts());
+        Ok(Self::from_rational_parts(a1 + a2, b1 + b2, d))
+    }
+
+    pub fn try_sub(&self, other: &Self) -> Result<Self, anyhow::Error> {
+        self.try_add(&-other.clone())
+    }
+
+    pub fn try_mul(&self, other: &Self) -> Result<Self, anyhow::Error> {
+        let d = self.common_radicand(other)?;
+        let ((a1, b1), (a2, b2)) = (self.parts(), other.parts());
+        let a = &a1 * &a2 + Mpq::from(&d) * &b1 * &b2;
+        Ok(Self::from_rational_parts(a, a1 * b2 + a2 * b1, d))
+    }
+
+    /// The quotient, with the denominator rationalized by multiplying both sides by its
+    /// conjugate.
+    pub fn try_div(&self, other: &Self) -> Result<Self, anyhow::Error> {
+        let d = self.common_radicand(other)?;
+        let norm = other.norm();
+        if norm == 0u32 {
+            bail!("division by zero");
+        }
+        let numerator = self.try_mul(&other.conj())?;
+        let (a, b) = numerator.parts();
+        Ok(Self::from_rational_parts(a / &norm, b / norm, d))
+    }
+
+    /// `a - b√d`.
+    pub fn conj(&self) -> Self {
+        Self {
+            a: self.a.clone(),
+            b: -self.b.clone(),
+            d: self.d.clone(),
+        }
+    }
+
+    /// `(a + b√d)(a - b√d) = a² - d b²`, which is zero only for zero.
+    pub fn norm(&self) -> Mpq {
+        let (a, b) = self.parts();
+        &a * &a - Mpq::from(&self.d) * &b * &b
+    }
+
+    /// The sign of `a + b√d`, decided by the norm when `a` and `b` have opposite signs.
+    pub fn sign(&self) -> Ordering {
+        let (a, b) = self.parts();
+        match (a.sign(), b.sign()) {
+            (sa, sb) if sa == sb || sb == Ordering::Equal => sa,
+            (Ordering::Equal, sb) => sb,
+            // |a| > |b|√d exactly when the norm is positive
+            (sa, sb) => match self.norm().sign() {
+                Ordering::Greater => sa,
+                _ => sb,
+            },
+        }
+    }
+
+    /// The order of `self` and `other`, which must share the radicand unless one is rational.
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, anyhow::Error> {
+        Ok(self.try_sub(other)?.sign())
+    }
+
+    /// `(p, q, r)` with `a + b√d = (p + q√d) / r` for integers `p`, `q` and the least positive
+    /// `r`.
+    pub fn over_common_denominator(&self) -> (Mpz, Mpz, Mpn) {
+        let (a, b) = self.parts();
+        let r = a.denominator_ref().lcm(b.denominator_ref());
+        let scale = Mpq::from(&r);
+        let to_integer = |x: Mpq| Mpz::try_from(x * &scale).unwrap();
+        (to_integer(a), to_integer(b), r)
+    }
+
+    /// The nearest `f64` up to a few ulps. When `a` and `b√d` nearly cancel, the value is taken
+    /// from `norm / (a - b√d)` instead, whose terms add up.
+    pub fn to_f64(&self) -> f64 {
+        let (a, b) = self.parts();
+        let to_f64 = |x: &Mpq| MpqExt::from(x).to_f64_nearest();
+        let root = MpqExt::from(Mpq::from(&self.d)).to_f64_nearest().sqrt();
+        if a.sign() == b.sign() || a == 0u32 || b == 0u32 {
+            to_f64(&a) + to_f64(&b) * root
+        } else {
+            to_f64(&self.norm()) / (to_f64(&a) - to_f64(&b) * root)
+        }
+    }
+}
+
+impl std::ops::Neg for QuadExt {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            a: -self.a,
+            b: -self.b,
+            d: self.d,
+        }
+    }
+}
+
+impl TryFrom<(MpqExt, MpqExt, Mpn)> for QuadExt {
+    type Error = anyhow::Error;
+
+    fn try_from((a, b, d): (MpqExt, MpqExt, Mpn)) -> Result<Self, Self::Error> {
+        Self::new(a, b, d)
+    }
+}
+
+impl From<QuadExt> for (MpqExt, MpqExt, Mpn) {
+    fn from(value: QuadExt) -> Self {
+        (value.a, value.b, value.d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(n: i64, d: i64) -> MpqExt {
+        MpqExt::from(Mpq::from_signeds(n, d))
+    }
+
+    fn quad(a: MpqExt, b: MpqExt, d: u64) -> QuadExt {
+        QuadExt::new(a, b, Mpn::from(d)).unwrap()
+    }
+
+    #[test]
+    fn golden_ratio() {
+        let phi = quad(q(1, 2), q(1, 2), 5);
+        let square = phi.try_mul(&phi).unwrap();
+        assert_eq!(square, quad(q(3, 2), q(1, 2), 5));
+        assert_eq!(
+            square.over_common_denominator(),
+            (Mpz::from(3), Mpz::from(1), Mpn::from(2u32))
+        );
+        // φ² = φ + 1 and φ·φ̄ = -1
+        assert_eq!(
+            square,
+            phi.try_add(&quad(q(1, 1), MpqExt::ZERO, 1)).unwrap()
+        );
+        assert_eq!(phi.norm(), -1);
+        assert_eq!(
+            phi.try_mul(&phi.conj()).unwrap(),
+            quad(q(-1, 1), MpqExt::ZERO, 1)
+        );
+        // 1/φ = φ - 1
+        let one = quad(q(1, 1), MpqExt::ZERO, 1);
+        assert_eq!(one.try_div(&phi).unwrap(), phi.try_sub(&one).unwrap());
+        assert!((phi.to_f64() - 1.618033988749895).abs() < 1e-15);
+    }
+
+    #[test]
+    fn radicands_are_normalized() {
+        // √12 = 2√3 and √(4·9) = 6
+        assert_eq!(quad(q(0, 1), q(1, 1), 12), quad(q(0, 1), q(2, 1), 3));
+        let six = quad(q(0, 1), q(1, 1), 36);
+        assert!(six.is_rational());
+        assert_eq!(six.a(), &q(6, 1));
+        assert_eq!(six.b(), &MpqExt::ZERO);
+        assert_eq!(quad(q(1, 3), q(5, 1), 0), quad(q(1, 3), MpqExt::ZERO, 1));
+        // a zero coefficient needs no factoring, however large the radicand
+        let big = QuadExt::new(q(1, 1), MpqExt::ZERO, Mpn::from(u128::MAX)).unwrap();
+        assert!(big.is_rational());
+        assert!(QuadExt::new(MpqExt::Inf(true), q(1, 1), Mpn::from(2u32)).is_err());
+        assert!(QuadExt::new(q(1, 1), MpqExt::NaN, Mpn::from(2u32)).is_err());
+    }
+
+    #[test]
+    fn comparisons() {
+        let root2 = quad(q(0, 1), q(1, 1), 2);
+        let rational = |x: MpqExt| quad(x, MpqExt::ZERO, 1);
+        assert_eq!(
+            root2.try_cmp(&rational(q(141, 100))).unwrap(),
+            Ordering::Greater
+        );
+        assert_eq!(
+            root2.try_cmp(&rational(q(142, 100))).unwrap(),
+            Ordering::Less
+        );
+        // 1 + √2 - 2√2 = 1 - √2 < 0, decided by the norm
+        let x = quad(q(1, 1), q(1, 1), 2);
+        let y = quad(q(0, 1), q(2, 1), 2);
+        assert_eq!(x.try_cmp(&y).unwrap(), Ordering::Less);
+        assert_eq!(x.try_cmp(&x).unwrap(), Ordering::Equal);
+        assert_eq!(quad(q(3, 1), q(-2, 1), 2).sign(), Ordering::Greater);
+        assert_eq!(quad(q(-3, 1), q(2, 1), 2).sign(), Ordering::Less);
+        let error = root2.try_cmp(&quad(q(0, 1), q(1, 1), 3)).unwrap_err();
+        assert_eq!(error.to_string(), "√2 and √3 cannot be combined in a + b√d");
+        assert!(root2.try_div(&rational(MpqExt::ZERO)).is_err());
+    }
+
+    #[test]
+    fn floats_of_nearly_cancelling_values() {
+        // (1 + √2)⁻¹⁰ = (√2 - 1)¹⁰ is small, and a - b√d would lose most of its digits
+        let mut x = quad(q(1, 1), MpqExt::ZERO, 1);
+        let silver = quad(q(-1, 1), q(1, 1), 2);
+        for _ in 0..10 {
+            x = x.try_mul(&silver).unwrap();
+        }
+        let expected = (2f64.sqrt() - 1.0).powi(10);
+        assert!((x.to_f64() - expected).abs() <= 1e-14 * expected);
+    }
+}
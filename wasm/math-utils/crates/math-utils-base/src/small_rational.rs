@@ -0,0 +1,310 @@
+//! A fast path for the rationals whose numerator and denominator both fit in 64 bits, which are
+//! most of those seen in practice. Their sums, products and comparisons are computed with 64-bit
+//! GCDs and `u128` products, falling back to malachite as soon as an intermediate result no
+//! longer fits. The results are identical to those of the general [`MpqExt`] operations, signed
+//! zeros included.
+
+use std::cmp::Ordering;
+
+use malachite::{Natural as Mpn, Rational as Mpq};
+
+use crate::MpqExt;
+
+/// The fraction `±num / den` in lowest terms with `den > 0`, where zero is `+0 / 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Small {
+    negative: bool,
+    num: u64,
+    den: u64,
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        return a | b;
+    }
+    // binary GCD, which avoids division
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            (a, b) = (b, a);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+impl Small {
+    const ZERO: Self = Small {
+        negative: false,
+        num: 0,
+        den: 1,
+    };
+    const ONE: Self = Small {
+        negative: false,
+        num: 1,
+        den: 1,
+    };
+
+    fn from_mpq(q: &Mpq) -> Option<Self> {
+        let (num, den) = q.numerator_and_denominator_ref();
+        Some(Small {
+            negative: *q < 0u32,
+            num: u64::try_from(num).ok()?,
+            den: u64::try_from(den).ok()?,
+        })
+    }
+
+    /// `±num / den`, which must be in lowest terms, or `None` if it does not fit.
+    fn new(negative: bool, num: u128, den: u128) -> Option<Self> {
+        Some(Small {
+            negative: negative && num != 0,
+            num: u64::try_from(num).ok()?,
+            den: u64::try_from(den).ok()?,
+        })
+    }
+
+    fn into_mpq(self) -> Mpq {
+        Mpq::from_sign_and_naturals(!self.negative, Mpn::from(self.num), Mpn::from(self.den))
+    }
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        // Henrici's algorithm: with g = gcd(b, d), a/b + c/d = (a·d/g + c·b/g) / (b·d/g), and
+        // what is left to cancel is a common factor of the numerator and g
+        let g = gcd(self.den, rhs.den);
+        let (b, d) = (self.den / g, rhs.den / g);
+        let x = self.num as u128 * d as u128;
+        let y = rhs.num as u128 * b as u128;
+        let (negative, num) = if self.negative == rhs.negative {
+            (self.negative, x.checked_add(y)?)
+        } else if x >= y {
+            (self.negative, x - y)
+        } else {
+            (rhs.negative, y - x)
+        };
+        if num == 0 {
+            return Some(Small::ZERO);
+        }
+        // h divides g, and so the denominator d
+        let h = gcd((num % g as u128) as u64, g);
+        Self::new(negative, num / h as u128, b as u128 * (rhs.den / h) as u128)
+    }
+
+    fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // cancelling across first leaves the product in lowest terms
+        let g1 = gcd(self.num, rhs.den);
+        let g2 = gcd(rhs.num, self.den);
+        Self::new(
+            self.negative != rhs.negative,
+            (self.num / g1) as u128 * (rhs.num / g2) as u128,
+            (self.den / g2) as u128 * (rhs.den / g1) as u128,
+        )
+    }
+
+    fn cmp(self, rhs: Self) -> Ordering {
+        match (self.negative, rhs.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (negative, _) => {
+                let a = self.num as u128 * rhs.den as u128;
+                let b = rhs.num as u128 * self.den as u128;
+                if negative { b.cmp(&a) } else { a.cmp(&b) }
+            }
+        }
+    }
+}
+
+fn rational(x: &MpqExt) -> Option<&Mpq> {
+    match x {
+        MpqExt::Rational(q) => Some(q),
+        _ => None,
+    }
+}
+
+/// The sum of `xs`, the same as `xs.iter().sum::<MpqExt>()`. Zeros add nothing once there is a
+/// nonzero rational, so a sum of rationals and zeros takes the fast path.
+pub fn sum(xs: &[MpqExt]) -> MpqExt {
+    let fast = xs
+        .iter()
+        .all(|x| matches!(x, MpqExt::Rational(_) | MpqExt::Zero(_)))
+        && xs.iter().any(|x| matches!(x, MpqExt::Rational(_)));
+    if !fast {
+        return xs.iter().sum();
+    }
+    let mut total = Small::ZERO;
+    for (i, q) in xs.iter().filter_map(rational).enumerate() {
+        match Small::from_mpq(q).and_then(|q| total.checked_add(q)) {
+            Some(next) => total = next,
+            None => {
+                // the exact sum does not depend on the order, so the rest is simply added on
+                let rest: Mpq = xs.iter().filter_map(rational).skip(i).sum();
+                return MpqExt::from(total.into_mpq() + rest);
+            }
+        }
+    }
+    MpqExt::from(total.into_mpq())
+}
+
+/// The product of `xs`, the same as `xs.iter().product::<MpqExt>()`. Only products of nonzero
+/// rationals take the fast path; any other factor makes the product cheap anyway.
+pub fn product(xs: &[MpqExt]) -> MpqExt {
+    if !xs.iter().all(|x| matches!(x, MpqExt::Rational(_))) {
+        return xs.iter().product();
+    }
+    let mut total = Small::ONE;
+    for (i, q) in xs.iter().filter_map(rational).enumerate() {
+        match Small::from_mpq(q).and_then(|q| total.checked_mul(q)) {
+            Some(next) => total = next,
+            None => {
+                let rest: Mpq = xs.iter().filter_map(rational).skip(i).product();
+                return MpqExt::from(total.into_mpq() * rest);
+            }
+        }
+    }
+    MpqExt::from(total.into_mpq())
+}
+
+/// The same as `x.partial_cmp(y)`.
+pub fn partial_cmp(x: &MpqExt, y: &MpqExt) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (rational(x), rational(y))
+        && let (Some(a), Some(b)) = (Small::from_mpq(a), Small::from_mpq(b))
+    {
+        return Some(a.cmp(b));
+    }
+    x.partial_cmp(y)
+}
+
+/// The decimal digits of `n`, the same as `n.to_string()`.
+pub fn to_decimal(n: &Mpn) -> String {
+    match u64::try_from(n) {
+        Ok(n) => n.to_string(),
+        Err(_) => n.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::base::num::{
+        arithmetic::traits::PowerOf2,
+        basic::traits::{One, Zero},
+    };
+
+    use super::*;
+
+    /// xorshift64*, enough to pick test cases reproducibly.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        /// A positive integer of up to `max_bits` bits, with small sizes as likely as large ones.
+        fn natural(&mut self, max_bits: u64) -> Mpn {
+            let bits = self.below(max_bits) + 1;
+            let limbs = bits.div_ceil(64);
+            let mut n = Mpn::ZERO;
+            for _ in 0..limbs {
+                n = (n << 64u32) + Mpn::from(self.next());
+            }
+            (n >> (64 * limbs - bits)).max(Mpn::ONE)
+        }
+
+        /// Mostly rationals near the 64-bit limit, with some zeros, infinities, `NaN`s and big
+        /// rationals mixed in.
+        fn value(&mut self) -> MpqExt {
+            match self.below(20) {
+                0 => MpqExt::Zero(true),
+                1 => MpqExt::Zero(false),
+                2 => MpqExt::Inf(self.below(2) == 0),
+                3 => MpqExt::NaN,
+                4 => MpqExt::from(Mpq::from_naturals(self.natural(200), self.natural(200))),
+                _ => {
+                    let max_bits = [4, 32, 64][self.below(3) as usize];
+                    let q = Mpq::from_naturals(self.natural(max_bits), self.natural(max_bits));
+                    MpqExt::from(if self.below(2) == 0 { -q } else { q })
+                }
+            }
+        }
+    }
+
+    /// Equality that also tells the variants and the signs of zeros and infinities apart.
+    fn same(a: &MpqExt, b: &MpqExt) -> bool {
+        match (a, b) {
+            (MpqExt::NaN, MpqExt::NaN) => true,
+            (MpqExt::Zero(s1), MpqExt::Zero(s2)) | (MpqExt::Inf(s1), MpqExt::Inf(s2)) => s1 == s2,
+            (MpqExt::Rational(q1), MpqExt::Rational(q2)) => q1 == q2,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn agrees_with_malachite() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..20_000 {
+            let xs: Vec<MpqExt> = (0..rng.below(6)).map(|_| rng.value()).collect();
+            let expected = xs.iter().sum::<MpqExt>();
+            assert!(same(&sum(&xs), &expected), "sum of {xs:?}: {expected:?}");
+            let expected = xs.iter().product::<MpqExt>();
+            assert!(
+                same(&product(&xs), &expected),
+                "product of {xs:?}: {expected:?}"
+            );
+            if let [x, y, ..] = &xs[..] {
+                assert_eq!(partial_cmp(x, y), x.partial_cmp(y), "{x:?} <=> {y:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn overflow_falls_back() {
+        let max = MpqExt::from(Mpq::from(u64::MAX));
+        let tiny = MpqExt::from(Mpq::from_naturals(Mpn::ONE, Mpn::from(u64::MAX)));
+        assert!(same(
+            &sum(&[max.clone(), max.clone()]),
+            &MpqExt::from(Mpq::from(u64::MAX) * Mpq::from(2u32))
+        ));
+        assert!(same(
+            &product(&[max.clone(), max.clone(), tiny.clone()]),
+            &max
+        ));
+        let third = MpqExt::from(Mpq::from_signeds(1, 3));
+        assert!(same(
+            &sum(&[third.clone(), -third.clone()]),
+            &MpqExt::Zero(true)
+        ));
+        assert!(same(
+            &sum(&[MpqExt::Zero(false), MpqExt::Zero(false)]),
+            &MpqExt::Zero(false)
+        ));
+        assert!(same(&sum(&[]), &MpqExt::Zero(true)));
+        assert!(same(&product(&[]), &MpqExt::from(1u32)));
+        assert_eq!(partial_cmp(&tiny, &max), Some(Ordering::Less));
+        assert_eq!(partial_cmp(&-max.clone(), &-tiny), Some(Ordering::Less));
+        assert_eq!(to_decimal(&Mpn::from(u64::MAX)), u64::MAX.to_string());
+        let big = Mpn::power_of_2(64);
+        assert_eq!(to_decimal(&big), "18446744073709551616");
+    }
+
+    #[test]
+    fn binary_gcd() {
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(0, 12), 12);
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(1 << 60, 3 << 50), 1 << 50);
+        assert_eq!(
+            gcd(u32::MAX as u64 * 7, u32::MAX as u64 * 5),
+            u32::MAX as u64
+        );
+    }
+}
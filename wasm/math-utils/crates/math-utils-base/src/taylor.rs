@@ -0,0 +1,155 @@
+//! Exact Taylor coefficients at 0 of a few elementary functions, and the tangent and secant
+//! numbers behind those of `tan` and `sec`.
+
+use anyhow::bail;
+use malachite::{
+    Natural as Mpn, Rational as Mpq,
+    base::num::{
+        arithmetic::traits::{Factorial, Parity},
+        basic::traits::{One, Zero},
+    },
+};
+
+/// The highest order computed, as the coefficients of `tan` and `sec` take `O(n²)` additions of
+/// numbers with `O(n log n)` digits.
+pub const MAX_ORDER: u64 = 1000;
+
+/// The functions [`taylor_coeff`] knows.
+pub const FUNCTIONS: [&str; 8] = ["exp", "sin", "cos", "tan", "sec", "ln1p", "atan", "asin"];
+
+fn check_order(n: u64) -> Result<(), anyhow::Error> {
+    if n > MAX_ORDER {
+        bail!("orders are limited to {MAX_ORDER}, but got {n}");
+    }
+    Ok(())
+}
+
+/// The zigzag number `Eₙ`, the number of alternating permutations of `n` elements, from the
+/// Seidel–Entringer–Arnold triangle: each row starts with 0 and adds up the previous row read
+/// backwards, and ends with the next zigzag number. `tan x + sec x = Σ Eₙ xⁿ / n!`.
+fn zigzag(n: u64) -> Mpn {
+    let mut row = vec![Mpn::ONE];
+    for k in 1..=n as usize {
+        let mut next = Vec::with_capacity(k + 1);
+        next.push(Mpn::ZERO);
+        for j in 1..=k {
+            next.push(&next[j - 1] + &row[k - j]);
+        }
+        row = next;
+    }
+    row.pop().unwrap()
+}
+
+/// The tangent number `Tₙ = E₂ₙ₋₁` for `n ≥ 1`, with `tan x = Σ Tₙ x²ⁿ⁻¹ / (2n - 1)!`: 1, 2, 16,
+/// 272, ….
+pub fn tangent_number(n: u64) -> Result<Mpn, anyhow::Error> {
+    if n == 0 {
+        bail!("tangent numbers are numbered from 1");
+    }
+    check_order(2 * n - 1)?;
+    Ok(zigzag(2 * n - 1))
+}
+
+/// The secant number `Sₙ = E₂ₙ`, with `sec x = Σ Sₙ x²ⁿ / (2n)!`: 1, 1, 5, 61, ….
+pub fn secant_number(n: u64) -> Result<Mpn, anyhow::Error> {
+    check_order(n.saturating_mul(2))?;
+    Ok(zigzag(2 * n))
+}
+
+/// `±1` by the parity of `k`.
+fn alternating(k: u64) -> Mpq {
+    if k.even() { Mpq::ONE } else { -Mpq::ONE }
+}
+
+/// The coefficient of `xⁿ` in the Taylor series at 0 of `function`, one of [`FUNCTIONS`].
+pub fn taylor_coeff(function: &str, n: u64) -> Result<Mpq, anyhow::Error> {
+    check_order(n)?;
+    let odd = n.odd();
+    let factorial = || Mpq::from(Mpn::factorial(n));
+    Ok(match function {
+        "exp" => Mpq::ONE / factorial(),
+        "sin" if odd => alternating(n / 2) / factorial(),
+        "cos" if !odd => alternating(n / 2) / factorial(),
+        "tan" if odd => Mpq::from(zigzag(n)) / factorial(),
+        "sec" if !odd => Mpq::from(zigzag(n)) / factorial(),
+        "ln1p" if n > 0 => alternating(n - 1) / Mpq::from(n),
+        "atan" if odd => alternating(n / 2) / Mpq::from(n),
+        "asin" if odd => {
+            // the coefficient of x²ᵏ⁺¹ is (2k)! / (4ᵏ k!² (2k + 1)), which starts at 1 and is
+            // multiplied by (2k + 1)² / ((2k + 2)(2k + 3)) from one odd power to the next
+            let mut c = Mpq::ONE;
+            for k in 0..n / 2 {
+                c *= Mpq::from_unsigneds((2 * k + 1).pow(2), (2 * k + 2) * (2 * k + 3));
+            }
+            c
+        }
+        _ if FUNCTIONS.contains(&function) => Mpq::ZERO,
+        _ => bail!(
+            "unknown function {function:?}, expected one of {}",
+            FUNCTIONS.join(", ")
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::base::num::arithmetic::traits::{BinomialCoefficient, Pow};
+
+    use super::*;
+
+    fn q(n: i64, d: i64) -> Mpq {
+        Mpq::from_signeds(n, d)
+    }
+
+    #[test]
+    fn tangent_coefficients() {
+        let expected = [q(1, 1), q(1, 3), q(2, 15), q(17, 315), q(62, 2835)];
+        for (k, c) in (0..).zip(expected) {
+            assert_eq!(taylor_coeff("tan", 2 * k + 1).unwrap(), c);
+            assert_eq!(taylor_coeff("tan", 2 * k).unwrap(), Mpq::ZERO);
+        }
+        let tangent: Vec<_> = (1..=5).map(|n| tangent_number(n).unwrap()).collect();
+        assert_eq!(tangent, [1u32, 2, 16, 272, 7936].map(Mpn::from));
+        let secant: Vec<_> = (0..=4).map(|n| secant_number(n).unwrap()).collect();
+        assert_eq!(secant, [1u32, 1, 5, 61, 1385].map(Mpn::from));
+        assert_eq!(taylor_coeff("sec", 4).unwrap(), q(5, 24));
+    }
+
+    #[test]
+    fn asin_closed_form() {
+        // the coefficient of x²ⁿ⁺¹ is binom(2n, n) / (4ⁿ (2n + 1))
+        for n in 0..60u64 {
+            let binom = Mpn::binomial_coefficient(Mpn::from(2 * n), Mpn::from(n));
+            let den = Mpn::from(4u32).pow(n) * Mpn::from(2 * n + 1);
+            let expected = Mpq::from_naturals(binom, den);
+            assert_eq!(
+                taylor_coeff("asin", 2 * n + 1).unwrap(),
+                expected,
+                "n = {n}"
+            );
+            assert_eq!(taylor_coeff("asin", 2 * n).unwrap(), Mpq::ZERO);
+        }
+    }
+
+    #[test]
+    fn other_series() {
+        assert_eq!(taylor_coeff("exp", 4).unwrap(), q(1, 24));
+        assert_eq!(taylor_coeff("sin", 3).unwrap(), q(-1, 6));
+        assert_eq!(taylor_coeff("cos", 4).unwrap(), q(1, 24));
+        assert_eq!(taylor_coeff("ln1p", 0).unwrap(), Mpq::ZERO);
+        assert_eq!(taylor_coeff("ln1p", 4).unwrap(), q(-1, 4));
+        assert_eq!(taylor_coeff("atan", 5).unwrap(), q(1, 5));
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let error = taylor_coeff("sinh", 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown function \"sinh\", expected one of exp, sin, cos, tan, sec, ln1p, atan, asin"
+        );
+        assert!(taylor_coeff("exp", MAX_ORDER + 1).is_err());
+        assert!(tangent_number(0).is_err());
+        assert!(secant_number(u64::MAX).is_err());
+    }
+}
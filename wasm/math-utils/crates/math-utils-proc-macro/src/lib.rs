@@ -5,6 +5,33 @@ use proc_macro2::Span;
 use quote::quote;
 use syn::{Ident, Pat, parse_macro_input};
 
+/// Exports a function through `wasm_minimal_protocol::wasm_func` when building for wasm, and
+/// fails to compile unless the function is listed with its number of arguments in the
+/// `PLUGIN_FUNCTIONS` that `build.rs` collects, so that `plugin_info` cannot miss an export.
+///
+/// Other targets only build the plugin to check and test it, and export nothing: the host's C
+/// library already defines functions such as `trunc` and `atan2`, which the Rust standard
+/// library calls and which an export of the same name would take the place of.
+#[proc_macro_attribute]
+pub fn wasm_func(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(Span::call_site(), "`wasm_func` takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    let func = parse_macro_input!(item as syn::ItemFn);
+    let name = func.sig.ident.to_string();
+    let n_args = func.sig.inputs.len();
+    let message = format!("`{name}` with {n_args} arguments is missing from PLUGIN_FUNCTIONS");
+    let expanded = quote! {
+        #[cfg_attr(target_arch = "wasm32", ::wasm_minimal_protocol::wasm_func)]
+        #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+        #func
+        const _: () = assert!(crate::is_plugin_function(#name, #n_args), #message);
+    };
+    TokenStream::from(expanded)
+}
+
 #[proc_macro]
 pub fn define_func(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DefineFuncInput);
@@ -19,11 +46,25 @@ pub fn define_func(input: TokenStream) -> TokenStream {
         let arg_name = Ident::new(&format!("arg{}", i), Span::call_site());
         quote! { #arg_name: &[u8] }
     });
+    // trailing `Option<T>` arguments are optional: the caller passes empty bytes to omit them
+    let n_required = arg_types
+        .iter()
+        .rposition(|ty| option_inner_type(ty).is_none())
+        .map_or(0, |i| i + 1);
     let var_declarations = arg_types.iter().enumerate().map(|(i, &arg_type)| {
         let arg_name = Ident::new(&format!("arg{}", i), Span::call_site());
         let var_name = Ident::new(&format!("num{}", i), Span::call_site());
-        quote! {
-            let #var_name = <#arg_type>::from_wasm_input(#arg_name)?;
+        match option_inner_type(arg_type) {
+            Some(inner_type) if i >= n_required => quote! {
+                let #var_name = if #arg_name.is_empty() {
+                    None
+                } else {
+                    Some(<#inner_type>::from_wasm_input(#arg_name)?)
+                };
+            },
+            _ => quote! {
+                let #var_name = <#arg_type>::from_wasm_input(#arg_name)?;
+            },
         }
     });
     let closure_args = (0..n_args).map(|i| {
@@ -36,34 +77,68 @@ pub fn define_func(input: TokenStream) -> TokenStream {
         quote! { let result = (#closure)(#(#closure_args),*); }
     };
 
-    let expanded = quote! {
-        #[wasm_func]
-        fn #func_name(#(#arg_declarations),*) -> Result<Vec<u8>, anyhow::Error> {
-            #(#var_declarations)*
-            #calc_result_expr
-            Ok(result.into_wasm_output())
+    let expanded = if input.cached {
+        let arg_names = (0..n_args).map(|i| Ident::new(&format!("arg{}", i), Span::call_site()));
+        let name = func_name.to_string();
+        quote! {
+            #[wasm_func]
+            fn #func_name(#(#arg_declarations),*) -> Result<Vec<u8>, anyhow::Error> {
+                let cache_key = crate::cache::key(&[#(#arg_names),*]);
+                if let Some(output) = crate::cache::get(#name, cache_key.clone()) {
+                    return Ok(output);
+                }
+                #(#var_declarations)*
+                #calc_result_expr
+                let output = result.into_wasm_output();
+                crate::cache::put(#name, cache_key, output.clone());
+                Ok(output)
+            }
+        }
+    } else {
+        quote! {
+            #[wasm_func]
+            fn #func_name(#(#arg_declarations),*) -> Result<Vec<u8>, anyhow::Error> {
+                #(#var_declarations)*
+                #calc_result_expr
+                Ok(result.into_wasm_output())
+            }
         }
     };
 
     TokenStream::from(expanded)
 }
 
+/// The `T` of a type written `Option<T>`.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner_type) if args.args.len() == 1 => Some(inner_type),
+        _ => None,
+    }
+}
+
 fn get_arg_types(closure: &syn::ExprClosure) -> Vec<&syn::Type> {
     let mut arg_types = Vec::new();
 
-    for (_, input) in closure.inputs.iter().enumerate() {
-        match input {
-            Pat::Type(pat_type) => {
-                arg_types.push(pat_type.ty.as_ref());
-            }
-            // Pat::Ident(pat_ident) if pat_ident.ty.is_none() => {
-            //     bail!("Missing type annotation for closure parameter at index {}", index);
-            // }
-            // _ => {
-            //     bail!("Missing type annotation for closure parameter at index {}", index);
-            // }
-            _ => {}
+    for input in closure.inputs.iter() {
+        if let Pat::Type(pat_type) = input {
+            arg_types.push(pat_type.ty.as_ref());
         }
+        // Pat::Ident(pat_ident) if pat_ident.ty.is_none() => {
+        //     bail!("Missing type annotation for closure parameter at index {}", index);
+        // }
+        // _ => {
+        //     bail!("Missing type annotation for closure parameter at index {}", index);
+        // }
     }
 
     arg_types
@@ -73,6 +148,8 @@ struct DefineFuncInput {
     func_name: Ident,
     closure: syn::ExprClosure,
     failable: bool,
+    /// Whether results are memoized by `crate::cache`, set by a trailing `cached` flag.
+    cached: bool,
 }
 
 impl syn::parse::Parse for DefineFuncInput {
@@ -88,16 +165,29 @@ impl syn::parse::Parse for DefineFuncInput {
                 };
                 // allow trailing comma
                 let _ = input.parse::<syn::Token![,]>();
+                let cached = match input.parse::<Option<Ident>>()? {
+                    Some(flag) if flag == "cached" => true,
+                    Some(flag) => {
+                        return Err(syn::Error::new(
+                            flag.span(),
+                            "unknown flag, expected `cached`",
+                        ));
+                    }
+                    None => false,
+                };
+                let _ = input.parse::<syn::Token![,]>();
                 Ok(DefineFuncInput {
                     func_name,
                     closure,
                     failable,
+                    cached,
                 })
             }
             Err(_) => Ok(DefineFuncInput {
                 func_name,
                 closure,
                 failable: false,
+                cached: false,
             }),
         }
     }
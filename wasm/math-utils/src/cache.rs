@@ -0,0 +1,247 @@
+//! A bounded least-recently-used cache of plugin results, kept for as long as the plugin
+//! instance lives. Only pure functions that are expensive to recompute opt in, with the `cached`
+//! flag of `define_func!`; failed calls are never cached.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MAX_ENTRIES: usize = 256;
+pub const DEFAULT_MAX_BYTES: usize = 16 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    /// The bytes held by the keys and values of all entries.
+    pub bytes: usize,
+    pub max_entries: usize,
+    pub max_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+struct Entry {
+    output: Vec<u8>,
+    /// The value of the use counter when the entry was last read or written.
+    last_used: u64,
+}
+
+struct Cache {
+    entries: HashMap<(&'static str, Vec<u8>), Entry>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+fn entry_size(input: &[u8], output: &[u8]) -> usize {
+    input.len() + output.len()
+}
+
+impl Cache {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            clock: 0,
+            stats: CacheStats {
+                entries: 0,
+                bytes: 0,
+                max_entries: DEFAULT_MAX_ENTRIES,
+                max_bytes: DEFAULT_MAX_BYTES,
+                hits: 0,
+                misses: 0,
+                evictions: 0,
+            },
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn get(&mut self, func: &'static str, input: Vec<u8>) -> Option<Vec<u8>> {
+        let now = self.tick();
+        match self.entries.get_mut(&(func, input)) {
+            Some(entry) => {
+                entry.last_used = now;
+                self.stats.hits += 1;
+                Some(entry.output.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn evict_one(&mut self) {
+        let Some(key) = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone())
+        else {
+            return;
+        };
+        let entry = self.entries.remove(&key).unwrap();
+        self.stats.bytes -= entry_size(&key.1, &entry.output);
+        self.stats.entries -= 1;
+        self.stats.evictions += 1;
+    }
+
+    /// Evicts the least recently used entries until `extra_entries` more entries of
+    /// `extra_bytes` bytes in total fit.
+    fn make_room(&mut self, extra_entries: usize, extra_bytes: usize) {
+        while !self.entries.is_empty()
+            && (self.stats.entries + extra_entries > self.stats.max_entries
+                || self.stats.bytes + extra_bytes > self.stats.max_bytes)
+        {
+            self.evict_one();
+        }
+    }
+
+    fn put(&mut self, func: &'static str, input: Vec<u8>, output: Vec<u8>) {
+        let size = entry_size(&input, &output);
+        // an entry too large for the whole cache would only flush it
+        if self.stats.max_entries == 0 || size > self.stats.max_bytes {
+            return;
+        }
+        let now = self.tick();
+        let key = (func, input);
+        if let Some(old) = self.entries.remove(&key) {
+            self.stats.bytes -= entry_size(&key.1, &old.output);
+            self.stats.entries -= 1;
+        }
+        self.make_room(1, size);
+        self.entries.insert(
+            key,
+            Entry {
+                output,
+                last_used: now,
+            },
+        );
+        self.stats.entries += 1;
+        self.stats.bytes += size;
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::new(Cache::new());
+}
+
+/// The cache key of a call: each argument prefixed with its length, so that different splits of
+/// the same bytes stay apart.
+pub fn key(args: &[&[u8]]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(args.iter().map(|arg| arg.len() + 8).sum());
+    for arg in args {
+        key.extend_from_slice(&(arg.len() as u64).to_le_bytes());
+        key.extend_from_slice(arg);
+    }
+    key
+}
+
+pub fn get(func: &'static str, input: Vec<u8>) -> Option<Vec<u8>> {
+    CACHE.with_borrow_mut(|cache| cache.get(func, input))
+}
+
+pub fn put(func: &'static str, input: Vec<u8>, output: Vec<u8>) {
+    CACHE.with_borrow_mut(|cache| cache.put(func, input, output));
+}
+
+/// Drops every entry and resets the hit, miss and eviction counts, keeping the limits.
+pub fn clear() {
+    CACHE.with_borrow_mut(|cache| {
+        let (max_entries, max_bytes) = (cache.stats.max_entries, cache.stats.max_bytes);
+        *cache = Cache::new();
+        cache.stats.max_entries = max_entries;
+        cache.stats.max_bytes = max_bytes;
+    });
+}
+
+/// Sets the limits on the number of entries and their total size, evicting entries as needed.
+pub fn configure(max_entries: usize, max_bytes: usize) {
+    CACHE.with_borrow_mut(|cache| {
+        cache.stats.max_entries = max_entries;
+        cache.stats.max_bytes = max_bytes;
+        cache.make_room(0, 0);
+    });
+}
+
+pub fn stats() -> CacheStats {
+    CACHE.with_borrow(|cache| cache.stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limited(max_entries: usize, max_bytes: usize) -> Cache {
+        let mut cache = Cache::new();
+        cache.stats.max_entries = max_entries;
+        cache.stats.max_bytes = max_bytes;
+        cache
+    }
+
+    #[test]
+    fn hit_returns_stored_bytes() {
+        let mut cache = Cache::new();
+        assert_eq!(cache.get("f", vec![1]), None);
+        cache.put("f", vec![1], vec![7, 8, 9]);
+        assert_eq!(cache.get("f", vec![1]), Some(vec![7, 8, 9]));
+        assert_eq!(cache.get("f", vec![1]), Some(vec![7, 8, 9]));
+        // keys of other functions or other inputs stay apart
+        assert_eq!(cache.get("g", vec![1]), None);
+        assert_eq!(cache.get("f", vec![2]), None);
+        assert_eq!((cache.stats.hits, cache.stats.misses), (2, 3));
+        assert_eq!((cache.stats.entries, cache.stats.bytes), (1, 4));
+        // the length prefixes tell different splits of the same bytes apart
+        assert_ne!(key(&[&[1, 2], &[3]]), key(&[&[1], &[2, 3]]));
+    }
+
+    #[test]
+    fn entry_cap_evicts_least_recently_used() {
+        let mut cache = limited(2, usize::MAX);
+        cache.put("f", vec![1], vec![1]);
+        cache.put("f", vec![2], vec![2]);
+        assert!(cache.get("f", vec![1]).is_some());
+        cache.put("f", vec![3], vec![3]);
+        assert_eq!(cache.get("f", vec![2]), None);
+        assert!(cache.get("f", vec![1]).is_some());
+        assert!(cache.get("f", vec![3]).is_some());
+        assert_eq!((cache.stats.entries, cache.stats.evictions), (2, 1));
+    }
+
+    #[test]
+    fn byte_cap_evicts() {
+        let mut cache = limited(usize::MAX, 10);
+        cache.put("f", vec![1], vec![0; 4]);
+        cache.put("f", vec![2], vec![0; 4]);
+        assert_eq!(cache.stats.bytes, 10);
+        cache.put("f", vec![3], vec![0; 1]);
+        assert_eq!(cache.get("f", vec![1]), None);
+        assert_eq!((cache.stats.entries, cache.stats.bytes), (2, 7));
+        // an entry larger than the whole cache is not stored and evicts nothing
+        cache.put("f", vec![4], vec![0; 10]);
+        assert_eq!(cache.get("f", vec![4]), None);
+        assert_eq!((cache.stats.entries, cache.stats.evictions), (2, 1));
+        // replacing an entry does not count it twice
+        cache.put("f", vec![2], vec![0; 2]);
+        assert_eq!((cache.stats.entries, cache.stats.bytes), (2, 5));
+    }
+
+    #[test]
+    fn lowering_limits_evicts() {
+        let mut cache = Cache::new();
+        for i in 0..5 {
+            cache.put("f", vec![i], vec![i]);
+        }
+        cache.stats.max_entries = 2;
+        cache.make_room(0, 0);
+        assert_eq!((cache.stats.entries, cache.stats.evictions), (2, 3));
+        assert!(cache.get("f", vec![4]).is_some());
+        cache.stats.max_entries = 0;
+        cache.make_room(0, 0);
+        cache.put("f", vec![5], vec![5]);
+        assert_eq!(cache.stats.entries, 0);
+    }
+}
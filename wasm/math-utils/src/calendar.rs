@@ -0,0 +1,152 @@
+use anyhow::bail;
+
+// All dates are proleptic: the Gregorian (or Julian, when `julian` is set) rules are extended
+// indefinitely into the past, and years are astronomical, so 1 BC is year 0.
+
+pub fn is_leap_year(year: i64, julian: bool) -> bool {
+    if julian {
+        year.rem_euclid(4) == 0
+    } else {
+        year.rem_euclid(4) == 0 && (year.rem_euclid(100) != 0 || year.rem_euclid(400) == 0)
+    }
+}
+
+pub fn days_in_month(year: i64, month: i64, julian: bool) -> i64 {
+    match month {
+        2 if is_leap_year(year, julian) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn validate_date(year: i64, month: i64, day: i64, julian: bool) -> Result<(), anyhow::Error> {
+    if !(1..=12).contains(&month) {
+        bail!("month {month} is out of range");
+    }
+    if !(1..=days_in_month(year, month, julian)).contains(&day) {
+        bail!("day {day} is out of range for {year}-{month:02}");
+    }
+    Ok(())
+}
+
+/// The Julian day number of the given date, counting days from 1 January 4713 BC (Julian).
+pub fn julian_day_number(
+    year: i64,
+    month: i64,
+    day: i64,
+    julian: bool,
+) -> Result<i64, anyhow::Error> {
+    validate_date(year, month, day, julian)?;
+    // shift the year to start in March so that the leap day comes last
+    let a = (14 - month) / 12;
+    let y = year + 4800 - a;
+    let m = month + 12 * a - 3;
+    let jdn = day + (153 * m + 2) / 5 + 365 * y + y.div_euclid(4);
+    Ok(if julian {
+        jdn - 32083
+    } else {
+        jdn - y.div_euclid(100) + y.div_euclid(400) - 32045
+    })
+}
+
+/// The date `(year, month, day)` with the given Julian day number.
+pub fn date_from_julian_day_number(jdn: i64, julian: bool) -> (i64, i64, i64) {
+    let (b, c) = if julian {
+        (0, jdn + 32082)
+    } else {
+        let a = jdn + 32044;
+        let b = (4 * a + 3).div_euclid(146097);
+        (b, a - (146097 * b).div_euclid(4))
+    };
+    let d = (4 * c + 3).div_euclid(1461);
+    let e = c - (1461 * d).div_euclid(4);
+    let m = (5 * e + 2) / 153;
+    let day = e - (153 * m + 2) / 5 + 1;
+    let month = m + 3 - 12 * (m / 10);
+    let year = 100 * b + d - 4800 + m / 10;
+    (year, month, day)
+}
+
+/// The ISO weekday of the given date, from 1 for Monday to 7 for Sunday.
+pub fn day_of_week(year: i64, month: i64, day: i64, julian: bool) -> Result<i64, anyhow::Error> {
+    Ok(julian_day_number(year, month, day, julian)?.rem_euclid(7) + 1)
+}
+
+/// The date of (Western) Easter Sunday in the given year, by the Anonymous Gregorian algorithm.
+pub fn easter_date(year: i64) -> (i64, i64, i64) {
+    let a = year.rem_euclid(19);
+    let (b, c) = (year.div_euclid(100), year.rem_euclid(100));
+    let (d, e) = (b.div_euclid(4), b.rem_euclid(4));
+    let f = (b + 8).div_euclid(25);
+    let g = (b - f + 1).div_euclid(3);
+    let h = (19 * a + b - d - g + 15).rem_euclid(30);
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k).rem_euclid(7);
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn julian_day_number_anchors() {
+        assert_eq!(julian_day_number(2000, 1, 1, false).unwrap(), 2_451_545);
+        // the Gregorian calendar took over from the Julian one on 15 October 1582
+        assert_eq!(julian_day_number(1582, 10, 4, true).unwrap(), 2_299_160);
+        assert_eq!(julian_day_number(1582, 10, 15, false).unwrap(), 2_299_161);
+        // the epoch, 1 January 4713 BC in the Julian calendar
+        assert_eq!(julian_day_number(-4712, 1, 1, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn julian_day_number_round_trip() {
+        for julian in [false, true] {
+            for jdn in (-2_000_000..3_000_000).step_by(997) {
+                let (year, month, day) = date_from_julian_day_number(jdn, julian);
+                assert_eq!(julian_day_number(year, month, day, julian).unwrap(), jdn);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert!(julian_day_number(2023, 2, 29, false).is_err());
+        assert!(julian_day_number(1900, 2, 29, false).is_err());
+        assert!(julian_day_number(1900, 2, 29, true).is_ok());
+        assert!(julian_day_number(2024, 13, 1, false).is_err());
+        assert!(julian_day_number(2024, 4, 31, false).is_err());
+        assert!(julian_day_number(2024, 1, 0, false).is_err());
+    }
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2000, false));
+        assert!(!is_leap_year(1900, false));
+        assert!(is_leap_year(1900, true));
+        assert!(is_leap_year(0, false));
+        assert!(is_leap_year(-4, false));
+        assert!(!is_leap_year(-1, true));
+    }
+
+    #[test]
+    fn weekdays() {
+        // a Saturday and a Thursday
+        assert_eq!(day_of_week(2000, 1, 1, false).unwrap(), 6);
+        assert_eq!(day_of_week(1582, 10, 4, true).unwrap(), 4);
+        assert_eq!(day_of_week(1582, 10, 15, false).unwrap(), 5);
+    }
+
+    #[test]
+    fn easter() {
+        assert_eq!(easter_date(2024), (2024, 3, 31));
+        assert_eq!(easter_date(2025), (2025, 4, 20));
+        assert_eq!(easter_date(1961), (1961, 4, 2));
+        assert_eq!(easter_date(2285), (2285, 3, 22));
+        assert_eq!(easter_date(2038), (2038, 4, 25));
+    }
+}
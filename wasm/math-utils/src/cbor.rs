@@ -0,0 +1,214 @@
+//! Decoding of CBOR arguments behind limits on their size, so that a malformed or hostile
+//! document is rejected before it is turned into values instead of stalling the compilation.
+
+use std::cell::Cell;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+pub const DEFAULT_MAX_ELEMENTS: u64 = 1 << 20;
+/// Enough hex digits for an integer of 10⁷ bits.
+pub const DEFAULT_MAX_DIGITS: u64 = 2_500_000;
+
+/// The limits on a single CBOR argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputLimits {
+    /// The number of values in the document, counting every entry of every array and map, keys
+    /// included, and the values nested in them.
+    pub max_elements: u64,
+    /// The length of any string in the document. Big integers travel as hex strings, so this
+    /// bounds their number of digits.
+    pub max_digits: u64,
+}
+
+thread_local! {
+    static LIMITS: Cell<InputLimits> = const {
+        Cell::new(InputLimits {
+            max_elements: DEFAULT_MAX_ELEMENTS,
+            max_digits: DEFAULT_MAX_DIGITS,
+        })
+    };
+}
+
+pub fn limits() -> InputLimits {
+    LIMITS.get()
+}
+
+pub fn configure(limits: InputLimits) {
+    LIMITS.set(limits);
+}
+
+/// Reads the argument of a CBOR head, which follows the initial byte in the next 1, 2, 4 or 8
+/// bytes when it does not fit into the initial byte itself. `None` stands for an indefinite
+/// length.
+fn read_argument(input: &[u8], pos: &mut usize, info: u8) -> Result<Option<u64>, anyhow::Error> {
+    let width = match info {
+        0..24 => return Ok(Some(info as u64)),
+        24..28 => 1 << (info - 24),
+        31 => return Ok(None),
+        _ => bail!("malformed CBOR head at byte {}", *pos - 1),
+    };
+    let Some(bytes) = input.get(*pos..*pos + width) else {
+        bail!("CBOR document ends within a head");
+    };
+    *pos += width;
+    Ok(Some(
+        bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64),
+    ))
+}
+
+/// Walks the heads of a CBOR document without building any values, checking the claimed lengths
+/// against both the limits and the bytes actually left, so that no length read from the input is
+/// ever trusted for an allocation.
+fn check(input: &[u8], limits: InputLimits) -> Result<(), anyhow::Error> {
+    let mut pos = 0;
+    let mut elements = 0u64;
+    while pos < input.len() {
+        let initial = input[pos];
+        pos += 1;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        // the break that ends an indefinite-length item is not a value
+        if initial == 0xff {
+            continue;
+        }
+        elements += 1;
+        if elements > limits.max_elements {
+            bail!(
+                "the argument has more than the limit of {} elements",
+                limits.max_elements
+            );
+        }
+        let argument = read_argument(input, &mut pos, info)?;
+        let remaining = (input.len() - pos) as u64;
+        match (major, argument) {
+            (2 | 3, Some(len)) => {
+                if major == 3 && len > limits.max_digits {
+                    bail!(
+                        "a string of {len} bytes exceeds the limit of {} digits",
+                        limits.max_digits
+                    );
+                }
+                if len > remaining {
+                    bail!("a string claims {len} bytes, but only {remaining} are left");
+                }
+                pos += len as usize;
+            }
+            (4 | 5, Some(len)) => {
+                // every entry takes at least one byte
+                let entries = if major == 5 {
+                    len.saturating_mul(2)
+                } else {
+                    len
+                };
+                if entries > limits.max_elements {
+                    bail!(
+                        "a collection claims {entries} elements, more than the limit of {}",
+                        limits.max_elements
+                    );
+                }
+                if entries > remaining {
+                    bail!(
+                        "a collection claims {entries} elements, but only {remaining} bytes are left"
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a CBOR argument once it has passed the current [`InputLimits`].
+pub fn decode<T: DeserializeOwned>(input: &[u8]) -> Result<T, anyhow::Error> {
+    check(input, limits())?;
+    Ok(ciborium::de::from_reader(input)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: InputLimits = InputLimits {
+        max_elements: DEFAULT_MAX_ELEMENTS,
+        max_digits: DEFAULT_MAX_DIGITS,
+    };
+
+    fn error(input: &[u8]) -> String {
+        check(input, LIMITS).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn header_claiming_100_mb() {
+        // a byte string whose 4-byte length says 100 MB, followed by only 3 bytes
+        let mut input = vec![0x5a];
+        input.extend_from_slice(&100_000_000u32.to_be_bytes());
+        input.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(
+            error(&input),
+            "a string claims 100000000 bytes, but only 3 are left"
+        );
+        assert!(decode::<Vec<u8>>(&input).is_err());
+        // the same length on a text string is over the digit limit
+        input[0] = 0x7a;
+        assert_eq!(
+            error(&input),
+            "a string of 100000000 bytes exceeds the limit of 2500000 digits"
+        );
+        input[0] = 0x9a;
+        assert_eq!(
+            error(&input),
+            "a collection claims 100000000 elements, more than the limit of 1048576"
+        );
+        // a map of 2⁶⁴ - 1 pairs does not overflow the count
+        let mut input = vec![0xbb];
+        input.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert!(error(&input).contains("claims 18446744073709551615 elements"));
+    }
+
+    #[test]
+    fn collections_need_their_bytes() {
+        // 1000 entries claimed with 2 bytes left
+        assert_eq!(
+            error(&[0x99, 0x03, 0xe8, 0x01, 0x02]),
+            "a collection claims 1000 elements, but only 2 bytes are left"
+        );
+        assert_eq!(error(&[0x5a, 0x05]), "CBOR document ends within a head");
+        assert_eq!(error(&[0x1c]), "malformed CBOR head at byte 0");
+    }
+
+    #[test]
+    fn nested_elements_are_counted() {
+        let limits = InputLimits {
+            max_elements: 5,
+            max_digits: 4,
+        };
+        // [[1, 2], [3]] has 6 values in all
+        let input = [0x82, 0x82, 0x01, 0x02, 0x81, 0x03];
+        assert!(check(&input, limits).is_err());
+        assert!(check(&input[..4], limits).is_ok());
+        // "abcde" has too many digits
+        assert!(check(&[0x65, b'a', b'b', b'c', b'd', b'e'], limits).is_err());
+        assert!(check(&[0x64, b'a', b'b', b'c', b'd'], limits).is_ok());
+    }
+
+    #[test]
+    fn valid_documents_decode() {
+        let mut input = Vec::new();
+        ciborium::ser::into_writer(&(vec![1u64, 2, 3], "abc"), &mut input).unwrap();
+        let decoded: (Vec<u64>, String) = decode(&input).unwrap();
+        assert_eq!(decoded, (vec![1, 2, 3], "abc".to_string()));
+        // an indefinite-length array and its break
+        assert_eq!(
+            decode::<Vec<u64>>(&[0x9f, 0x01, 0x02, 0xff]).unwrap(),
+            [1, 2]
+        );
+        assert_eq!(limits(), LIMITS);
+        configure(InputLimits {
+            max_elements: 2,
+            max_digits: 0,
+        });
+        assert!(decode::<Vec<u64>>(&[0x82, 0x01, 0x02]).is_err());
+        configure(LIMITS);
+        assert!(decode::<Vec<u64>>(&[0x82, 0x01, 0x02]).is_ok());
+    }
+}
@@ -1,85 +1,235 @@
-// use num::complex::Complex64;
-// use std::num::ParseFloatError;
-// use thiserror::Error;
-
-// const COMPLEX_SYMBOL: char = 'i';
-// const SIGNS: [char; 2] = ['-', '+'];
-
-// #[derive(Debug, Error)]
-// pub enum ParseComplexError {
-//     #[error("Invalid complex number format")]
-//     InvalidFormat,
-//     #[error("Invalid float format")]
-//     ParseFloatError(#[from] ParseFloatError),
-// }
-
-// fn parse_imaginary(im_src: &str) -> Result<f64, ParseFloatError> {
-//     let im = if im_src == "" || im_src == "+" {
-//         1.0
-//     } else if im_src == "-" {
-//         -1.0
-//     } else {
-//         im_src.parse()?
-//     };
-//     Ok(im)
-// }
-
-// pub fn parse_complex(src: &str) -> Result<Complex64, ParseComplexError> {
-//     // see if the string starts with a sign
-//     let start: usize = if src.starts_with(&SIGNS) { 1 } else { 0 };
-//     // see if the string has imaginary part
-//     // by trying to find the sign before the imaginary part
-//     let im_start = match src[start..].find(&SIGNS) {
-//         Some(i) => {
-//             let sign_idx = start + i;
-//             match src.chars().nth(sign_idx - 1) {
-//                 Some('e') | Some('E') => {
-//                     // a sign before float exponent
-//                     // need to find a sign afterwards
-//                     match src[sign_idx + 1..].find(&SIGNS) {
-//                         Some(j) => {
-//                             let sign_idx = sign_idx + j + 1;
-//                             match src.chars().nth(sign_idx - 1) {
-//                                 // another sign before exponent
-//                                 // not a sign before imaginary part
-//                                 Some('e') | Some('E') => None,
-//                                 // found a sign before imaginary part
-//                                 _ => Some(sign_idx),
-//                             }
-//                         }
-//                         None => None,
-//                     }
-//                 }
-//                 _ => Some(sign_idx),
-//             }
-//         }
-//         None => None,
-//     };
-//     match im_start {
-//         // with both real and imaginary parts
-//         Some(i) => {
-//             if src.ends_with(COMPLEX_SYMBOL) {
-//                 let re: f64 = src[..i]
-//                     .parse()
-//                     .map_err(ParseComplexError::ParseFloatError)?;
-//                 let im = parse_imaginary(&src[i..src.len() - 1])
-//                     .map_err(ParseComplexError::ParseFloatError)?;
-//                 Ok(Complex64::new(re, im))
-//             } else {
-//                 // not a valid complex number format if the string doesn't end with 'i'
-//                 Err(ParseComplexError::InvalidFormat)
-//             }
-//         }
-//         None => {
-//             if src.ends_with(COMPLEX_SYMBOL) {
-//                 // with only imaginary part
-//                 let im = parse_imaginary(&src[..src.len() - 1])?;
-//                 Ok(Complex64::new(0.0, im))
-//             } else {
-//                 // with only real part
-//                 let re = src.parse().map_err(ParseComplexError::ParseFloatError)?;
-//                 Ok(Complex64::new(re, 0.0))
-//             }
-//         }
-//     }
-// }
+use std::{
+    cmp::Ordering,
+    f64::consts::{PI, TAU},
+};
+
+use anyhow::{anyhow, bail};
+use num::complex::Complex64;
+use serde::{Deserialize, Serialize};
+
+/// CBOR representation of a complex number, used when complex values are nested inside other
+/// outputs such as `Vec`s. Scalar arguments and results keep using the raw little-endian bytes.
+///
+/// Both components are encoded as CBOR floats, so `NaN` and `±inf` survive the round trip.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ComplexData {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl From<Complex64> for ComplexData {
+    fn from(value: Complex64) -> Self {
+        ComplexData {
+            re: value.re,
+            im: value.im,
+        }
+    }
+}
+
+impl From<ComplexData> for Complex64 {
+    fn from(value: ComplexData) -> Self {
+        Complex64::new(value.re, value.im)
+    }
+}
+
+/// The logarithm on branch `k`, `ln|z| + i(arg z + 2πk)`, where branch 0 is the principal one.
+pub fn log_branch(z: Complex64, k: i64) -> Complex64 {
+    Complex64::new(z.norm().ln(), z.arg() + TAU * k as f64)
+}
+
+/// `zⁿ` by repeated squaring, which takes `O(log n)` multiplications and does not go through
+/// `exp(n ln z)`. `z⁰` is 1 for every `z`, including 0.
+pub fn powi(z: Complex64, n: i64) -> Result<Complex64, anyhow::Error> {
+    if z == Complex64::ZERO && n < 0 {
+        bail!("zero cannot be raised to a negative power");
+    }
+    let mut base = z;
+    let mut exp = n.unsigned_abs();
+    let mut result = Complex64::ONE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result *= base;
+        }
+        base *= base;
+        exp >>= 1;
+    }
+    // inverting once at the end loses less than inverting the base
+    Ok(if n < 0 { result.inv() } else { result })
+}
+
+/// The `k`-th of the `n` `n`-th roots of `z`, `|z|^(1/n) e^(i(arg z + 2πk) / n)`, where `k` is
+/// taken modulo `n` and root 0 is the principal one.
+pub fn root(z: Complex64, n: u64, k: i64) -> Result<Complex64, anyhow::Error> {
+    if n == 0 {
+        bail!("the 0th root is undefined");
+    }
+    let k = (k as i128).rem_euclid(n as i128);
+    let n = n as f64;
+    Ok(Complex64::from_polar(
+        z.norm().powf(1.0 / n),
+        (z.arg() + TAU * k as f64) / n,
+    ))
+}
+
+/// The most roots [`roots`] lists at once.
+pub const MAX_ROOTS: u64 = 1 << 16;
+
+/// All `n` of the `n`-th roots of `z`, starting from the principal one and going
+/// counterclockwise. At most [`MAX_ROOTS`] roots are returned.
+pub fn roots(z: Complex64, n: u64) -> Result<Vec<Complex64>, anyhow::Error> {
+    if n == 0 {
+        bail!("the 0th root is undefined");
+    }
+    if n > MAX_ROOTS {
+        bail!("at most {MAX_ROOTS} roots can be listed, but got {n}");
+    }
+    (0..n as i64).map(|k| root(z, n, k)).collect()
+}
+
+/// Orders by magnitude, and numbers of equal magnitude by their argument in `(-π, π]`, so that
+/// the roots of a polynomial sort counterclockwise from the negative real axis. Every nonzero
+/// number orders after 0. NaN components cannot be ordered.
+pub fn cmp_abs(z1: Complex64, z2: Complex64) -> Result<Ordering, anyhow::Error> {
+    if z1.is_nan() || z2.is_nan() {
+        bail!("{z1} and {z2} cannot be ordered");
+    }
+    let by_abs = z1.norm().total_cmp(&z2.norm());
+    if by_abs != Ordering::Equal || z1.norm() == 0.0 {
+        return Ok(by_abs);
+    }
+    // `arg` gives -π for a negative real part with a zero imaginary part of -0.0
+    let arg = |z: Complex64| if z.arg() == -PI { PI } else { z.arg() };
+    Ok(arg(z1).total_cmp(&arg(z2)))
+}
+
+/// Whether `|z1 - z2| ≤ tol · max(1, |z1|, |z2|)`, so `tol` is an absolute tolerance near 0 and a
+/// relative one for large numbers. Equal infinities are equal, and NaN equals nothing.
+pub fn approx_eq(z1: Complex64, z2: Complex64, tol: f64) -> Result<bool, anyhow::Error> {
+    if tol.is_nan() || tol < 0.0 {
+        bail!("the tolerance must be non-negative, but got {tol}");
+    }
+    if z1 == z2 {
+        return Ok(true);
+    }
+    let scale = z1.norm().max(z2.norm()).max(1.0);
+    Ok((z1 - z2).norm() <= tol * scale)
+}
+
+/// The symbols accepted for the imaginary unit, `i` as in mathematics and `j` as in Python and
+/// Julia.
+const IMAGINARY_UNITS: [char; 2] = ['i', 'j'];
+/// The separators of the polar form `r∠θ`.
+const ANGLE_SYMBOLS: [char; 2] = ['\u{2220}', '<'];
+
+/// Parses a complex number written as `a+bi`, `a+bj` or in polar form `r∠θ` (also `r<θ`), where
+/// `θ` is in radians unless it ends with `°`. Whitespace is ignored and U+2212 MINUS SIGN is
+/// accepted for `-`.
+pub fn parse(src: &str) -> Result<Complex64, anyhow::Error> {
+    let src: String = src
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '\u{2212}' => '-',
+            c if IMAGINARY_UNITS.contains(&c) => 'i',
+            c => c,
+        })
+        .collect();
+    if let Some((r, theta)) = src.split_once(ANGLE_SYMBOLS) {
+        let r: f64 = r
+            .parse()
+            .map_err(|_| anyhow!("invalid modulus {r:?} in polar form"))?;
+        let theta = match theta.strip_suffix('\u{b0}') {
+            Some(degrees) => degrees.parse::<f64>().map(f64::to_radians),
+            None => theta.parse(),
+        }
+        .map_err(|_| anyhow!("invalid argument {theta:?} in polar form"))?;
+        return Ok(Complex64::from_polar(r, theta));
+    }
+    src.parse()
+        .map_err(|_| anyhow!("{src:?} cannot be parsed as a complex number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_roots_of_8() {
+        let roots = roots(Complex64::new(8.0, 0.0), 3).unwrap();
+        let sqrt_3 = 3f64.sqrt();
+        let expected = [(2.0, 0.0), (-1.0, sqrt_3), (-1.0, -sqrt_3)];
+        assert_eq!(roots.len(), 3);
+        for (root, (re, im)) in roots.into_iter().zip(expected) {
+            assert!((root - Complex64::new(re, im)).norm() < 1e-12, "{root}");
+        }
+    }
+
+    #[test]
+    fn powi_agrees_with_repeated_multiplication() {
+        // xorshift64*, enough for reproducible test inputs
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            (state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+        };
+        for _ in 0..1000 {
+            let z = Complex64::new(4.0 * next() - 2.0, 4.0 * next() - 2.0);
+            let expected = (0..10).fold(Complex64::ONE, |acc, _| acc * z);
+            let actual = powi(z, 10).unwrap();
+            assert!((actual - expected).norm() <= 1e-13 * expected.norm(), "{z}");
+        }
+    }
+
+    #[test]
+    fn powi_edge_cases() {
+        assert_eq!(powi(Complex64::ZERO, 0).unwrap(), Complex64::ONE);
+        assert_eq!(powi(Complex64::ZERO, 3).unwrap(), Complex64::ZERO);
+        assert!(powi(Complex64::ZERO, -1).is_err());
+        assert_eq!(powi(Complex64::I, -1).unwrap(), -Complex64::I);
+        assert_eq!(powi(Complex64::new(2.0, 0.0), -2).unwrap().re, 0.25);
+        assert_eq!(powi(Complex64::I, i64::MIN).unwrap(), Complex64::ONE);
+    }
+
+    #[test]
+    fn cube_roots_of_8i() {
+        let z = Complex64::new(0.0, 8.0);
+        let roots: Vec<_> = (0..3).map(|k| root(z, 3, k).unwrap()).collect();
+        // the principal root is 2 e^(iπ/6)
+        assert!((roots[0] - Complex64::new(3f64.sqrt(), 1.0)).norm() < 1e-12);
+        for r in &roots {
+            assert!((r * r * r - z).norm() < 1e-12, "{r}");
+        }
+        // the roots of w³ - 8i multiply to 8i and add up to 0
+        assert!((roots[0] * roots[1] * roots[2] - z).norm() < 1e-12);
+        assert!((roots[0] + roots[1] + roots[2]).norm() < 1e-12);
+        assert_eq!(root(z, 3, -1).unwrap(), roots[2]);
+        assert_eq!(root(z, 3, 4).unwrap(), roots[1]);
+        assert_eq!(root(z, u64::MAX, -1).unwrap().norm(), 1.0);
+        assert!(root(z, 0, 0).is_err());
+    }
+
+    #[test]
+    fn log_branches() {
+        let z = Complex64::new(-1.0, 0.0);
+        assert_eq!(log_branch(z, 0), Complex64::new(0.0, PI));
+        assert_eq!(log_branch(z, -1), Complex64::new(0.0, -PI));
+        let z = Complex64::new(3.0, -4.0);
+        for k in -3..=3 {
+            let log = log_branch(z, k);
+            assert!((log.exp() - z).norm() < 1e-12);
+            assert!((log.im - z.arg() - TAU * k as f64).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn root_count_is_checked() {
+        assert!(roots(Complex64::ONE, 0).is_err());
+        assert!(roots(Complex64::ONE, MAX_ROOTS + 1).is_err());
+        assert_eq!(
+            roots(Complex64::ONE, MAX_ROOTS).unwrap().len(),
+            MAX_ROOTS as usize
+        );
+    }
+}
@@ -0,0 +1,551 @@
+//! Evaluation of arithmetic expressions given as strings, either exactly over [`MpqExt`] or over
+//! `f64`, which also knows constants and functions such as `pi`, `sin` and `gamma`. From the
+//! loosest binding to the tightest, the grammar is
+//!
+//! ```text
+//! expr  := term (("+" | "-") term)*
+//! term  := unary (("*" | "/") unary)*
+//! unary := ("+" | "-") unary | power
+//! power := atom ("^" unary)?
+//! atom  := number | name | name "(" expr ("," expr)* ")" | "(" expr ")"
+//! ```
+//!
+//! so `-2^2 = -4`, `2^3^2 = 2^9` and `2^-1 = 1/2`. `×` and `·` stand for `*`, `÷` for `/` and
+//! `−` for `-`. Numbers are read by the fraction parser, so `0.[3]` is `1/3` and `1.5e3` is 1500.
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+use malachite::base::num::arithmetic::traits::Pow;
+use math_utils_base::MpqExt;
+use special::LambertW;
+
+use crate::{
+    ReciprocalTrig, check_power_size, log2_natural, principal_angle, real_log_base, real_root,
+};
+
+/// How deeply parentheses, signs and exponents may nest, so that a hostile expression cannot
+/// exhaust the stack.
+const MAX_DEPTH: usize = 256;
+
+/// The longest excerpt of a token quoted in an error.
+const MAX_EXCERPT: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Name,
+    /// One of `+ - * / ^`, with the Unicode operators folded into these.
+    Op(char),
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    /// The position of the first character, counting characters from 1.
+    column: usize,
+}
+
+fn error_at(token: &Token, message: impl Display) -> anyhow::Error {
+    if token.kind == Kind::End {
+        return anyhow!("{message} at column {}, the end of the input", token.column);
+    }
+    let mut excerpt: String = token.text.chars().take(MAX_EXCERPT).collect();
+    if token.text.chars().count() > MAX_EXCERPT {
+        excerpt.push('…');
+    }
+    anyhow!("{message} at column {}, near {excerpt:?}", token.column)
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token<'_>>, anyhow::Error> {
+    let chars: Vec<(usize, char)> = src.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(src.len(), |&(offset, _)| offset);
+    let is_digit = |i: usize| chars.get(i).is_some_and(|&(_, c)| c.is_ascii_digit());
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let column = i + 1;
+        let skip_while = |mut i: usize, pred: fn(char) -> bool| {
+            while chars.get(i).is_some_and(|&(_, c)| pred(c)) {
+                i += 1;
+            }
+            i
+        };
+        let (kind, end) = match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '0'..='9' | '.' | '[' => {
+                let mut end = skip_while(i + 1, |c| c.is_ascii_digit() || ".[]".contains(c));
+                // an exponent only counts when digits follow, possibly after a sign
+                if chars.get(end).is_some_and(|&(_, c)| c == 'e' || c == 'E') {
+                    let sign = chars
+                        .get(end + 1)
+                        .is_some_and(|&(_, c)| c == '+' || c == '-');
+                    let digits = end + 1 + sign as usize;
+                    if is_digit(digits) {
+                        end = skip_while(digits, |c| c.is_ascii_digit());
+                    }
+                }
+                (Kind::Number, end)
+            }
+            _ if c.is_alphabetic() || c == '_' => (
+                Kind::Name,
+                skip_while(i + 1, |c| c.is_alphanumeric() || c == '_'),
+            ),
+            '+' | '/' | '^' => (Kind::Op(c), i + 1),
+            '-' | '\u{2212}' => (Kind::Op('-'), i + 1),
+            '*' | '×' | '·' => (Kind::Op('*'), i + 1),
+            '÷' => (Kind::Op('/'), i + 1),
+            '(' => (Kind::Open, i + 1),
+            ')' => (Kind::Close, i + 1),
+            ',' => (Kind::Comma, i + 1),
+            _ => {
+                let token = Token {
+                    kind: Kind::Number,
+                    text: &src[start..offset(i + 1)],
+                    column,
+                };
+                return Err(error_at(&token, "unexpected character"));
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: &src[start..offset(end)],
+            column,
+        });
+        i = end;
+    }
+    tokens.push(Token {
+        kind: Kind::End,
+        text: "",
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+/// The arithmetic an expression is evaluated with. Errors are reported without a position,
+/// which the parser adds from the token at fault.
+trait Value: Sized {
+    fn literal(src: &str) -> Result<Self, anyhow::Error>;
+    fn constant(name: &str) -> Option<Self>;
+    /// `None` for an unknown function.
+    fn call(name: &str, args: Vec<Self>) -> Option<Result<Self, anyhow::Error>>;
+    fn negate(self) -> Self;
+    fn binary(op: char, lhs: Self, rhs: Self) -> Result<Self, anyhow::Error>;
+}
+
+impl Value for MpqExt {
+    fn literal(src: &str) -> Result<Self, anyhow::Error> {
+        MpqExt::from_str(src)
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        match name {
+            "inf" => Some(MpqExt::Inf(true)),
+            "nan" => Some(MpqExt::NaN),
+            _ => None,
+        }
+    }
+
+    fn call(_: &str, _: Vec<Self>) -> Option<Result<Self, anyhow::Error>> {
+        None
+    }
+
+    fn negate(self) -> Self {
+        -self
+    }
+
+    /// Division by zero gives the extended values, e.g. `1/0 = inf` and `0/0 = nan`, as the
+    /// operators of [`MpqExt`] do, but powers need an integer exponent.
+    fn binary(op: char, lhs: Self, rhs: Self) -> Result<Self, anyhow::Error> {
+        Ok(match op {
+            '+' => lhs + rhs,
+            '-' => lhs - rhs,
+            '*' => lhs * rhs,
+            '/' => lhs / rhs,
+            _ => {
+                let exp = match &rhs {
+                    MpqExt::Zero(_) => 0,
+                    MpqExt::Rational(q) if *q.denominator_ref() == 1u32 => {
+                        i64::try_from(q).map_err(|_| anyhow!("the exponent {rhs} is too large"))?
+                    }
+                    _ => return Err(anyhow!("the exponent {rhs} is not an integer")),
+                };
+                if let MpqExt::Rational(q) = &lhs {
+                    let log2_base =
+                        log2_natural(q.numerator_ref()).max(log2_natural(q.denominator_ref()));
+                    check_power_size(log2_base, exp.unsigned_abs())?;
+                }
+                lhs.pow(exp)
+            }
+        })
+    }
+}
+
+enum FloatFunction {
+    Unary(fn(f64) -> f64),
+    FallibleUnary(fn(f64) -> Result<f64, anyhow::Error>),
+    Binary(fn(f64, f64) -> f64),
+}
+
+/// The real functions known to [`eval_float`], under the names of their plugin functions.
+fn float_function(name: &str) -> Option<FloatFunction> {
+    use FloatFunction::*;
+    Some(match name {
+        "sin" => Unary(f64::sin),
+        "cos" => Unary(f64::cos),
+        "tan" => Unary(f64::tan),
+        "cot" => Unary(ReciprocalTrig::cot),
+        "sec" => Unary(ReciprocalTrig::sec),
+        "csc" => Unary(ReciprocalTrig::csc),
+        "asin" => Unary(f64::asin),
+        "acos" => Unary(f64::acos),
+        "atan" => Unary(f64::atan),
+        "acot" => Unary(ReciprocalTrig::acot),
+        "asec" => Unary(ReciprocalTrig::asec),
+        "acsc" => Unary(ReciprocalTrig::acsc),
+        "sinh" => Unary(f64::sinh),
+        "cosh" => Unary(f64::cosh),
+        "tanh" => Unary(f64::tanh),
+        "coth" => Unary(ReciprocalTrig::coth),
+        "sech" => Unary(ReciprocalTrig::sech),
+        "csch" => Unary(ReciprocalTrig::csch),
+        "asinh" => Unary(f64::asinh),
+        "acosh" => Unary(f64::acosh),
+        "atanh" => Unary(f64::atanh),
+        "acoth" => Unary(ReciprocalTrig::acoth),
+        "asech" => Unary(ReciprocalTrig::asech),
+        "acsch" => Unary(ReciprocalTrig::acsch),
+        "exp" => Unary(f64::exp),
+        "ln" => Unary(f64::ln),
+        "log2" => Unary(f64::log2),
+        "log10" => Unary(f64::log10),
+        "sqrt" => Unary(f64::sqrt),
+        "cbrt" => Unary(f64::cbrt),
+        "signum" => Unary(f64::signum),
+        "fract" => Unary(f64::fract),
+        "trunc" => Unary(f64::trunc),
+        "round_ties_even" => Unary(f64::round_ties_even),
+        "gamma" => Unary(scirs2_special::gamma),
+        "digamma" => Unary(scirs2_special::digamma),
+        "erf" => Unary(scirs2_special::erf),
+        "zeta" => FallibleUnary(|x| Ok(scirs2_special::zeta(x)?)),
+        "lambert_w" => Unary(|x| x.lambert_w0()),
+        "lambert_wm1" => Unary(|x| x.lambert_wm1()),
+        "atan2" => Binary(principal_angle),
+        "hypot" => Binary(f64::hypot),
+        "log_base" => Binary(real_log_base),
+        "copysign" => Binary(f64::copysign),
+        "beta" => Binary(scirs2_special::beta),
+        "nth_root" => Binary(|x, n| {
+            if n >= 0.0 && n.fract() == 0.0 {
+                real_root(x, n as u64)
+            } else {
+                f64::NAN
+            }
+        }),
+        _ => return None,
+    })
+}
+
+impl Value for f64 {
+    fn literal(src: &str) -> Result<Self, anyhow::Error> {
+        Ok(MpqExt::from_str(src)?.to_f64_nearest())
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        match name {
+            "pi" | "π" => Some(std::f64::consts::PI),
+            "tau" | "τ" => Some(std::f64::consts::TAU),
+            "e" => Some(std::f64::consts::E),
+            "inf" => Some(f64::INFINITY),
+            "nan" => Some(f64::NAN),
+            _ => None,
+        }
+    }
+
+    fn call(name: &str, args: Vec<Self>) -> Option<Result<Self, anyhow::Error>> {
+        let function = float_function(name)?;
+        Some(match (function, &args[..]) {
+            (FloatFunction::Unary(f), &[x]) => Ok(f(x)),
+            (FloatFunction::FallibleUnary(f), &[x]) => f(x),
+            (FloatFunction::Binary(f), &[x, y]) => Ok(f(x, y)),
+            (function, _) => {
+                let expected = match function {
+                    FloatFunction::Unary(_) | FloatFunction::FallibleUnary(_) => "1 argument",
+                    FloatFunction::Binary(_) => "2 arguments",
+                };
+                Err(anyhow!("{name} takes {expected}, but got {}", args.len()))
+            }
+        })
+    }
+
+    fn negate(self) -> Self {
+        -self
+    }
+
+    fn binary(op: char, lhs: Self, rhs: Self) -> Result<Self, anyhow::Error> {
+        Ok(match op {
+            '+' => lhs + rhs,
+            '-' => lhs - rhs,
+            '*' => lhs * rhs,
+            '/' => lhs / rhs,
+            _ => lhs.powf(rhs),
+        })
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Token<'a> {
+        self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token<'a> {
+        let token = self.peek();
+        if token.kind != Kind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, kind: Kind, what: &str) -> Result<Token<'a>, anyhow::Error> {
+        let token = self.next();
+        if token.kind != kind {
+            return Err(error_at(&token, format!("expected {what}")));
+        }
+        Ok(token)
+    }
+
+    /// Folds a chain of operators of one precedence level from the left.
+    fn chain<V: Value>(
+        &mut self,
+        ops: &[char],
+        operand: fn(&mut Self) -> Result<V, anyhow::Error>,
+    ) -> Result<V, anyhow::Error> {
+        let mut value = operand(self)?;
+        while let Kind::Op(op) = self.peek().kind
+            && ops.contains(&op)
+        {
+            let token = self.next();
+            let rhs = operand(self)?;
+            value = V::binary(op, value, rhs).map_err(|e| error_at(&token, e))?;
+        }
+        Ok(value)
+    }
+
+    fn expr<V: Value>(&mut self) -> Result<V, anyhow::Error> {
+        self.chain(&['+', '-'], Self::term)
+    }
+
+    fn term<V: Value>(&mut self) -> Result<V, anyhow::Error> {
+        self.chain(&['*', '/'], Self::unary)
+    }
+
+    fn unary<V: Value>(&mut self) -> Result<V, anyhow::Error> {
+        let token = self.peek();
+        if self.depth == MAX_DEPTH {
+            return Err(error_at(
+                &token,
+                format!("the expression nests deeper than {MAX_DEPTH} levels"),
+            ));
+        }
+        self.depth += 1;
+        let value = match token.kind {
+            Kind::Op('+') => {
+                self.next();
+                self.unary()
+            }
+            Kind::Op('-') => {
+                self.next();
+                self.unary().map(V::negate)
+            }
+            _ => self.power(),
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn power<V: Value>(&mut self) -> Result<V, anyhow::Error> {
+        let base = self.atom()?;
+        if self.peek().kind != Kind::Op('^') {
+            return Ok(base);
+        }
+        let token = self.next();
+        // the exponent is parsed by `unary`, which makes `^` right-associative
+        let exp = self.unary()?;
+        V::binary('^', base, exp).map_err(|e| error_at(&token, e))
+    }
+
+    fn atom<V: Value>(&mut self) -> Result<V, anyhow::Error> {
+        let token = self.next();
+        match token.kind {
+            Kind::Number => V::literal(token.text)
+                .map_err(|e| error_at(&token, format!("invalid number ({e})"))),
+            Kind::Name if self.peek().kind == Kind::Open => {
+                self.next();
+                let mut args = vec![self.expr()?];
+                while self.peek().kind == Kind::Comma {
+                    self.next();
+                    args.push(self.expr()?);
+                }
+                self.expect(Kind::Close, "\")\" or \",\"")?;
+                V::call(token.text, args)
+                    .ok_or_else(|| error_at(&token, "unknown function"))?
+                    .map_err(|e| error_at(&token, e))
+            }
+            Kind::Name => V::constant(token.text).ok_or_else(|| error_at(&token, "unknown name")),
+            Kind::Open => {
+                let value = self.expr()?;
+                self.expect(Kind::Close, "\")\"")?;
+                Ok(value)
+            }
+            _ => Err(error_at(&token, "expected a number")),
+        }
+    }
+}
+
+fn eval<V: Value>(src: &str) -> Result<V, anyhow::Error> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    let token = parser.peek();
+    if token.kind != Kind::End {
+        return Err(error_at(&token, "expected an operator"));
+    }
+    Ok(value)
+}
+
+/// Evaluates an expression exactly. Besides numbers it knows only `inf` and `nan`.
+pub fn eval_exact(src: &str) -> Result<MpqExt, anyhow::Error> {
+    eval(src)
+}
+
+/// Evaluates an expression in `f64`, where numbers are rounded to the nearest `f64` once they
+/// are read.
+pub fn eval_float(src: &str) -> Result<f64, anyhow::Error> {
+    eval(src)
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::Rational as Mpq;
+
+    use super::*;
+
+    fn exact(src: &str) -> MpqExt {
+        eval_exact(src).unwrap()
+    }
+
+    fn q(n: i64, d: i64) -> MpqExt {
+        MpqExt::from(Mpq::from_signeds(n, d))
+    }
+
+    fn error(src: &str) -> String {
+        eval_exact(src).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(exact("2 + 3 * 4"), q(14, 1));
+        assert_eq!(exact("(2 + 3) * 4"), q(20, 1));
+        assert_eq!(exact("2 * 3 + 4 * 5"), q(26, 1));
+        // the other operators associate to the left
+        assert_eq!(exact("10 - 4 - 3"), q(3, 1));
+        assert_eq!(exact("12 / 3 / 2"), q(2, 1));
+        assert_eq!(exact("3/4 + 2^10 / (1 - 1/3)"), q(6147, 4));
+        // signs bind looser than powers
+        assert_eq!(exact("-2^2"), q(-4, 1));
+        assert_eq!(exact("(-2)^2"), q(4, 1));
+        assert_eq!(exact("2 * -3"), q(-6, 1));
+        assert_eq!(exact("--2"), q(2, 1));
+        assert_eq!(exact("2×3 − 1÷2"), q(11, 2));
+        assert_eq!(exact("0.[3] * 3"), q(1, 1));
+        assert_eq!(exact("1.5e3"), q(1500, 1));
+    }
+
+    #[test]
+    fn powers_associate_to_the_right() {
+        assert_eq!(exact("2^3^2"), q(512, 1));
+        assert_eq!(exact("(2^3)^2"), q(64, 1));
+        assert_eq!(exact("2^-1"), q(1, 2));
+        assert_eq!(exact("2^-2^2"), q(1, 16));
+        assert_eq!(eval_float("2^3^2").unwrap(), 512.0);
+        assert_eq!(eval_float("-2^2").unwrap(), -4.0);
+    }
+
+    #[test]
+    fn division_by_zero_gives_extended_values() {
+        assert!(matches!(exact("1/0"), MpqExt::Inf(true)));
+        assert!(matches!(exact("-1/0"), MpqExt::Inf(false)));
+        assert!(matches!(exact("0/0"), MpqExt::NaN));
+        assert!(matches!(exact("1/(1/0)"), MpqExt::Zero(true)));
+        assert!(matches!(exact("inf - inf"), MpqExt::NaN));
+        assert_eq!(eval_float("1/0").unwrap(), f64::INFINITY);
+        assert!(eval_float("0/0").unwrap().is_nan());
+    }
+
+    #[test]
+    fn float_functions() {
+        assert_eq!(eval_float("sin(pi / 2)").unwrap(), 1.0);
+        assert_eq!(
+            eval_float("atan2(1, 1)").unwrap(),
+            std::f64::consts::FRAC_PI_4
+        );
+        assert_eq!(eval_float("2 * gamma(4)").unwrap(), 12.0);
+        assert_eq!(
+            eval_float("hypot(3)").unwrap_err().to_string(),
+            "hypot takes 2 arguments, but got 1 at column 1, near \"hypot\""
+        );
+        assert!(eval_exact("sin(1)").is_err());
+        assert!(eval_exact("pi").is_err());
+    }
+
+    #[test]
+    fn errors_carry_positions() {
+        assert_eq!(
+            error("1 +"),
+            "expected a number at column 4, the end of the input"
+        );
+        assert_eq!(
+            error("1 $ 2"),
+            "unexpected character at column 3, near \"$\""
+        );
+        assert_eq!(
+            error("2^(1/2)"),
+            "the exponent 1/2 is not an integer at column 2, near \"^\""
+        );
+        assert_eq!(
+            error("(1 + 2"),
+            "expected \")\" at column 7, the end of the input"
+        );
+        assert_eq!(error("1 2"), "expected an operator at column 3, near \"2\"");
+        assert_eq!(error("foo + 1"), "unknown name at column 1, near \"foo\"");
+        // long tokens are cut short in the excerpt
+        let long = error("12345678901234567890.]");
+        assert!(long.starts_with("invalid number ("), "{long}");
+        assert!(
+            long.ends_with(") at column 1, near \"1234567890123456…\""),
+            "{long}"
+        );
+        assert!(error("2^10000000000").contains("more than the limit"));
+        let nested = format!("{}1{}", "(".repeat(300), ")".repeat(300));
+        assert!(error(&nested).contains("nests deeper than 256 levels"));
+    }
+}
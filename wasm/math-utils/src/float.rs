@@ -0,0 +1,214 @@
+//! Dissection of IEEE 754 binary64 floats.
+
+use anyhow::bail;
+use malachite::{Rational as Mpq, base::num::arithmetic::traits::PowerOf2};
+use math_utils_base::MpqExt;
+use serde::{Deserialize, Serialize};
+
+const EXPONENT_BITS: u32 = 11;
+const MANTISSA_BITS: u32 = 52;
+const EXPONENT_MASK: u64 = (1 << EXPONENT_BITS) - 1;
+const MANTISSA_MASK: u64 = (1 << MANTISSA_BITS) - 1;
+const BIAS: i64 = 1023;
+/// The exponent of the subnormals, which is also that of the smallest normal numbers.
+const MIN_EXPONENT: i64 = 1 - BIAS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FloatClass {
+    Zero,
+    Subnormal,
+    Normal,
+    Infinite,
+    NaN,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FloatParts {
+    /// The raw sign bit, 1 for negative numbers including `-0`.
+    pub sign: u64,
+    /// The raw 11-bit biased exponent field.
+    pub exponent_bits: u64,
+    /// The raw 52-bit fraction field, without the implicit leading bit.
+    pub mantissa_bits: u64,
+    /// The unbiased exponent `e` in `±1.m · 2ᵉ` (or `±0.m · 2ᵉ` for zero and subnormals), which
+    /// is absent for infinities and `NaN`.
+    pub exponent: Option<i64>,
+    pub class: FloatClass,
+    /// The exact value of the float.
+    pub value: MpqExt,
+}
+
+pub fn decompose(x: f64) -> FloatParts {
+    let bits = x.to_bits();
+    let sign = bits >> 63;
+    let exponent_bits = (bits >> MANTISSA_BITS) & EXPONENT_MASK;
+    let mantissa_bits = bits & MANTISSA_MASK;
+    let (class, exponent) = match (exponent_bits, mantissa_bits) {
+        (0, 0) => (FloatClass::Zero, Some(MIN_EXPONENT)),
+        (0, _) => (FloatClass::Subnormal, Some(MIN_EXPONENT)),
+        (EXPONENT_MASK, 0) => (FloatClass::Infinite, None),
+        (EXPONENT_MASK, _) => (FloatClass::NaN, None),
+        (e, _) => (FloatClass::Normal, Some(e as i64 - BIAS)),
+    };
+    FloatParts {
+        sign,
+        exponent_bits,
+        mantissa_bits,
+        exponent,
+        class,
+        value: MpqExt::try_from(x).unwrap(),
+    }
+}
+
+/// The float with the given raw fields, each of which must fit in its width.
+pub fn from_parts(sign: u64, exponent_bits: u64, mantissa_bits: u64) -> Result<f64, anyhow::Error> {
+    if sign > 1 {
+        bail!("sign bit must be 0 or 1");
+    }
+    if exponent_bits > EXPONENT_MASK {
+        bail!("exponent field {exponent_bits} is out of range [0, {EXPONENT_MASK}]");
+    }
+    if mantissa_bits > MANTISSA_MASK {
+        bail!("mantissa field {mantissa_bits} is out of range [0, {MANTISSA_MASK}]");
+    }
+    Ok(f64::from_bits(
+        sign << 63 | exponent_bits << MANTISSA_BITS | mantissa_bits,
+    ))
+}
+
+/// The float adjacent to `x` in the direction of `toward`, as C's `nextafter`.
+pub fn next_after(x: f64, toward: f64) -> f64 {
+    if x.is_nan() || toward.is_nan() {
+        return f64::NAN;
+    }
+    if x == toward {
+        return toward;
+    }
+    if x == 0.0 {
+        // the smallest subnormal on the side of `toward`
+        return f64::from_bits(1).copysign(toward);
+    }
+    // for floats of one sign the bit patterns are ordered like the magnitudes
+    let bits = x.to_bits();
+    if (toward > x) == (x > 0.0) {
+        f64::from_bits(bits + 1)
+    } else {
+        f64::from_bits(bits - 1)
+    }
+}
+
+/// The unit in the last place of `x`, i.e. the spacing of the floats with the same exponent as
+/// `x`. It is infinite for infinities and `NaN` for `NaN`.
+pub fn ulp(x: f64) -> MpqExt {
+    match decompose(x) {
+        FloatParts {
+            exponent: Some(e), ..
+        } => MpqExt::from(Mpq::power_of_2(e - MANTISSA_BITS as i64)),
+        FloatParts {
+            class: FloatClass::Infinite,
+            ..
+        } => MpqExt::Inf(true),
+        _ => MpqExt::NaN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn power_of_2(e: i64) -> MpqExt {
+        MpqExt::from(Mpq::power_of_2(e))
+    }
+
+    /// The exact value survives the trip to a rational and back, and the fields rebuild `x`.
+    fn round_trips(x: f64) {
+        let parts = decompose(x);
+        assert_eq!(parts.value.to_f64_nearest().to_bits(), x.to_bits());
+        let rebuilt = from_parts(parts.sign, parts.exponent_bits, parts.mantissa_bits).unwrap();
+        assert_eq!(rebuilt.to_bits(), x.to_bits());
+    }
+
+    #[test]
+    fn negative_zero() {
+        let parts = decompose(-0.0);
+        assert_eq!(
+            (parts.sign, parts.exponent_bits, parts.mantissa_bits),
+            (1, 0, 0)
+        );
+        assert_eq!(
+            (parts.class, parts.exponent),
+            (FloatClass::Zero, Some(-1022))
+        );
+        assert!(matches!(parts.value, MpqExt::Zero(false)));
+        round_trips(-0.0);
+        round_trips(0.0);
+        assert!(next_after(-0.0, 0.0).is_sign_positive());
+    }
+
+    #[test]
+    fn subnormals() {
+        let min = f64::from_bits(1);
+        let parts = decompose(min);
+        assert_eq!(
+            (parts.class, parts.exponent),
+            (FloatClass::Subnormal, Some(-1022))
+        );
+        assert_eq!(parts.value, power_of_2(-1074));
+        round_trips(min);
+        let max_subnormal = f64::from_bits(MANTISSA_MASK);
+        assert_eq!(decompose(max_subnormal).class, FloatClass::Subnormal);
+        round_trips(max_subnormal);
+        assert_eq!(decompose(f64::MIN_POSITIVE).class, FloatClass::Normal);
+        assert_eq!(next_after(f64::MIN_POSITIVE, 0.0), max_subnormal);
+        assert_eq!(next_after(0.0, -1.0), -min);
+        assert_eq!(next_after(-0.0, 1.0), min);
+        assert_eq!(ulp(min), power_of_2(-1074));
+        assert_eq!(ulp(0.0), power_of_2(-1074));
+        assert_eq!(ulp(f64::MIN_POSITIVE), power_of_2(-1074));
+    }
+
+    #[test]
+    fn extremes() {
+        let parts = decompose(f64::MAX);
+        assert_eq!(
+            (parts.exponent_bits, parts.mantissa_bits),
+            (2046, MANTISSA_MASK)
+        );
+        assert_eq!(parts.exponent, Some(1023));
+        let exact = Mpq::from((1u64 << 53) - 1) * Mpq::power_of_2(971i64);
+        assert_eq!(parts.value, MpqExt::from(exact));
+        round_trips(f64::MAX);
+        round_trips(-f64::MAX);
+        assert_eq!(ulp(f64::MAX), power_of_2(971));
+        assert_eq!(next_after(f64::MAX, f64::INFINITY), f64::INFINITY);
+        assert_eq!(next_after(f64::INFINITY, 0.0), f64::MAX);
+        assert_eq!(next_after(1.0, 2.0), 1.0 + f64::EPSILON);
+        assert_eq!(next_after(-1.0, 0.0), -(1.0 - f64::EPSILON / 2.0));
+        assert_eq!(ulp(1.0), power_of_2(-52));
+    }
+
+    #[test]
+    fn special_values() {
+        let parts = decompose(f64::NEG_INFINITY);
+        assert_eq!(
+            (parts.sign, parts.class, parts.exponent),
+            (1, FloatClass::Infinite, None)
+        );
+        assert_eq!(decompose(f64::NAN).class, FloatClass::NaN);
+        assert!(matches!(ulp(f64::INFINITY), MpqExt::Inf(true)));
+        assert!(matches!(ulp(f64::NAN), MpqExt::NaN));
+        assert!(next_after(f64::NAN, 1.0).is_nan());
+        assert!(from_parts(0, EXPONENT_MASK, 1).unwrap().is_nan());
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert!(from_parts(2, 0, 0).is_err());
+        assert_eq!(
+            from_parts(0, 2048, 0).unwrap_err().to_string(),
+            "exponent field 2048 is out of range [0, 2047]"
+        );
+        assert!(from_parts(0, 0, 1 << 52).is_err());
+    }
+}
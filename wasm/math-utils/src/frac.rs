@@ -85,33 +85,33 @@ where
     }
 }
 
-impl<T> Into<GenericFraction<T>> for FracData<T>
+impl<T> From<FracData<T>> for GenericFraction<T>
 where
     T: Integer + Clone + Copy,
 {
-    fn into(self) -> GenericFraction<T> {
+    fn from(value: FracData<T>) -> Self {
         use GenericFraction::*;
         use fraction::Sign::*;
-        if self.den == T::zero() {
-            if self.num == T::zero() {
+        if value.den == T::zero() {
+            if value.num == T::zero() {
                 NaN
             } else {
-                let sign = if self.sign { Plus } else { Minus };
+                let sign = if value.sign { Plus } else { Minus };
                 Infinity(sign)
             }
         } else {
-            let sign = if self.sign { Plus } else { Minus };
-            GenericFraction::new_raw_signed(sign, self.num, self.den)
+            let sign = if value.sign { Plus } else { Minus };
+            GenericFraction::new_raw_signed(sign, value.num, value.den)
         }
     }
 }
 
-impl<T> Into<Frac<T>> for FracData<T>
+impl<T> From<FracData<T>> for Frac<T>
 where
     T: Integer + Clone + Copy,
 {
-    fn into(self) -> Frac<T> {
-        <FracData<T> as Into<GenericFraction<T>>>::into(self).into()
+    fn from(value: FracData<T>) -> Self {
+        GenericFraction::<T>::from(value).into()
     }
 }
 
@@ -175,41 +175,36 @@ where
 //     }
 // }
 
-// impl<T> Frac<T>
-// where
-//     T: Integer + Clone + Copy,
-// {
-//     pub fn limit_den(self: Frac<T>, max_den: T) -> Frac<T> {
-//         match self.0 {
-//             GenericFraction::Rational(sign, ratio) => {
-//                 let (num, den) = ratio.into_raw();
-//                 let (num, den) = limit_den_helper((num, den), max_den).unwrap();
-//                 GenericFraction::Rational(sign, Ratio::new_raw(num, den)).into()
-//             }
-//             special => special.into(),
-//         }
-//     }
-// }
-
-impl<T> Approx<T> for Frac<T>
+impl<T> Frac<T>
 where
     T: Integer + Clone + Copy + Zero + One + Sign,
 {
-    type Output = Self;
-
-    fn approx(self, max_den: &T) -> Self::Output {
+    /// The closest fraction whose denominator is at most `max_den`, which must be positive.
+    /// Infinities and `NaN` are returned unchanged.
+    pub fn limit_den(self, max_den: T) -> Result<Frac<T>, String> {
         use GenericFraction::*;
         match self.0 {
             Rational(sign, ratio) => {
                 let (num, den) = ratio.into_raw();
-                let (num, den) = limit_den_helper((num, den), *max_den).unwrap();
-                Rational(sign, Ratio::new_raw(num, den)).into()
+                let (num, den) = limit_den_helper((num, den), max_den)?;
+                Ok(Rational(sign, Ratio::new_raw(num, den)).into())
             }
-            special => special.into(),
+            special => Ok(special.into()),
         }
     }
 }
 
+impl<T> Approx<T> for Frac<T>
+where
+    T: Integer + Clone + Copy + Zero + One + Sign,
+{
+    type Output = Self;
+
+    fn approx(self, max_den: &T) -> Self::Output {
+        self.limit_den(*max_den).unwrap()
+    }
+}
+
 impl Pow<i64> for Frac<u64> {
     type Output = Self;
 
@@ -264,7 +259,7 @@ where
             Rational(s, num, den) => {
                 GenericFraction::Rational(if s { Plus } else { Minus }, Ratio::new(num, den))
             }
-            Inf(s) => GenericFraction::Infinity(if s { Plus } else { Minus }).into(),
+            Inf(s) => GenericFraction::Infinity(if s { Plus } else { Minus }),
             Zero(s) => GenericFraction::Rational(
                 if s { Plus } else { Minus },
                 Ratio::new_raw(T::ZERO, T::ONE),
@@ -294,4 +289,3 @@ where
         Ok(ParseFractionResult::from_str(src)?.into())
     }
 }
-
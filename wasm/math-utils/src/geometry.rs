@@ -0,0 +1,281 @@
+//! Plane geometry on points passed as flat coordinate lists `[x₀, y₀, x₁, y₁, …]`.
+
+use anyhow::bail;
+use malachite::{Rational as Mpq, base::num::basic::traits::Zero};
+use math_utils_base::MpqExt;
+
+type Point = (Mpq, Mpq);
+
+fn pair_up<T: Clone>(coords: &[T]) -> Result<Vec<(T, T)>, anyhow::Error> {
+    if !coords.len().is_multiple_of(2) {
+        bail!(
+            "points are given as coordinate pairs, but got {} coordinates",
+            coords.len()
+        );
+    }
+    Ok(coords
+        .chunks_exact(2)
+        .map(|p| (p[0].clone(), p[1].clone()))
+        .collect())
+}
+
+fn exact_points(coords: Vec<MpqExt>) -> Result<Vec<Point>, anyhow::Error> {
+    pair_up(&coords)?
+        .into_iter()
+        .map(|(x, y)| Ok((x.try_into()?, y.try_into()?)))
+        .collect()
+}
+
+/// `(b - a) × (c - a)`, which is positive if `a`, `b`, `c` turn counterclockwise, negative if
+/// they turn clockwise and zero if they are collinear.
+fn orientation(a: &Point, b: &Point, c: &Point) -> Mpq {
+    (&b.0 - &a.0) * (&c.1 - &a.1) - (&b.1 - &a.1) * (&c.0 - &a.0)
+}
+
+/// Twice the signed area by the shoelace formula, with the last vertex joined to the first.
+fn double_area(vertices: &[Point]) -> Mpq {
+    let next = vertices.iter().cycle().skip(1);
+    vertices
+        .iter()
+        .zip(next)
+        .map(|((x0, y0), (x1, y1))| x0 * y1 - x1 * y0)
+        .sum()
+}
+
+/// The signed area of the polygon with the given vertices, positive if they run
+/// counterclockwise. Fewer than 3 vertices enclose no area. For a self-intersecting polygon this
+/// is the plain shoelace sum, in which regions wound clockwise count negatively.
+pub fn polygon_area(coords: Vec<MpqExt>) -> Result<MpqExt, anyhow::Error> {
+    let vertices = exact_points(coords)?;
+    Ok(MpqExt::from(double_area(&vertices) / Mpq::from(2u32)))
+}
+
+/// The centroid of the region enclosed by the polygon, which must have a nonzero area.
+pub fn polygon_centroid(coords: Vec<MpqExt>) -> Result<(MpqExt, MpqExt), anyhow::Error> {
+    let vertices = exact_points(coords)?;
+    let double_area = double_area(&vertices);
+    if double_area == 0u32 {
+        bail!("the centroid of a polygon with zero area is undefined");
+    }
+    let (mut cx, mut cy) = (Mpq::ZERO, Mpq::ZERO);
+    for ((x0, y0), (x1, y1)) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+        let cross = x0 * y1 - x1 * y0;
+        cx += (x0 + x1) * &cross;
+        cy += (y0 + y1) * cross;
+    }
+    let scale = double_area * Mpq::from(3u32);
+    Ok((MpqExt::from(cx / &scale), MpqExt::from(cy / scale)))
+}
+
+/// Whether `(x, y)` lies in the closed region of the polygon: points on an edge or a vertex count
+/// as inside. A self-intersecting polygon encloses the points it winds around an odd number of
+/// times. Fewer than 3 vertices form a degenerate polygon of which only the edges count.
+pub fn point_in_polygon(coords: Vec<MpqExt>, x: MpqExt, y: MpqExt) -> Result<bool, anyhow::Error> {
+    let vertices = exact_points(coords)?;
+    let p: Point = (x.try_into()?, y.try_into()?);
+    let between = |lo: &Mpq, hi: &Mpq, t: &Mpq| (t - lo) * (t - hi) <= 0u32;
+    let mut inside = false;
+    for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+        let turn = orientation(a, b, &p);
+        if turn == 0u32 && between(&a.0, &b.0, &p.0) && between(&a.1, &b.1, &p.1) {
+            return Ok(true);
+        }
+        // cast a ray toward +x; each edge is closed at its lower end so that a vertex on the ray
+        // is counted once
+        if (a.1 > p.1) != (b.1 > p.1) {
+            // the crossing is right of p when the turn has the sign of b.y - a.y
+            if (turn > 0u32) == (b.1 > a.1) {
+                inside = !inside;
+            }
+        }
+    }
+    Ok(inside)
+}
+
+/// The indices of the vertices of the convex hull, counterclockwise from the lowest of the
+/// leftmost points. Points on the hull's edges but not at its corners are left out, and of
+/// repeated points only the first is used. The turns are decided exactly, so nearly collinear
+/// points cannot make the hull self-intersect.
+pub fn convex_hull(coords: &[f64]) -> Result<Vec<u64>, anyhow::Error> {
+    let points = pair_up(coords)?
+        .into_iter()
+        .map(|(x, y)| match (Mpq::try_from(x), Mpq::try_from(y)) {
+            (Ok(x), Ok(y)) => Ok((x, y)),
+            _ => bail!("the point ({x}, {y}) is not finite"),
+        })
+        .collect::<Result<Vec<Point>, _>>()?;
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    // the sort is stable, so the first of repeated points comes first
+    order.sort_by(|&i, &j| points[i].cmp(&points[j]));
+    order.dedup_by(|&mut j, &mut i| points[i] == points[j]);
+    if order.len() < 3 {
+        return Ok(order.into_iter().map(|i| i as u64).collect());
+    }
+    // Andrew's monotone chain: the lower hull left to right, then the upper hull right to left
+    let turns_left = |hull: &[usize], i: usize| {
+        orientation(
+            &points[hull[hull.len() - 2]],
+            &points[hull[hull.len() - 1]],
+            &points[i],
+        ) > 0u32
+    };
+    let mut hull: Vec<usize> = Vec::with_capacity(order.len() + 1);
+    for &i in &order {
+        while hull.len() >= 2 && !turns_left(&hull, i) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    let lower_len = hull.len();
+    for &i in order.iter().rev().skip(1) {
+        while hull.len() > lower_len && !turns_left(&hull, i) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    // the upper hull ends where the lower one started
+    hull.pop();
+    Ok(hull.into_iter().map(|i| i as u64).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coords(points: &[(i64, i64)]) -> Vec<MpqExt> {
+        points
+            .iter()
+            .flat_map(|&(x, y)| [MpqExt::from(Mpq::from(x)), MpqExt::from(Mpq::from(y))])
+            .collect()
+    }
+
+    fn q(n: i64, d: i64) -> MpqExt {
+        MpqExt::from(Mpq::from_signeds(n, d))
+    }
+
+    #[test]
+    fn areas_and_centroids() {
+        let square = [(0, 0), (2, 0), (2, 2), (0, 2)];
+        assert_eq!(polygon_area(coords(&square)).unwrap(), q(4, 1));
+        let clockwise: Vec<_> = square.iter().rev().copied().collect();
+        assert_eq!(polygon_area(coords(&clockwise)).unwrap(), q(-4, 1));
+        assert_eq!(
+            polygon_centroid(coords(&square)).unwrap(),
+            (q(1, 1), q(1, 1))
+        );
+        let triangle = [(0, 0), (3, 0), (0, 1)];
+        assert_eq!(polygon_area(coords(&triangle)).unwrap(), q(3, 2));
+        assert_eq!(
+            polygon_centroid(coords(&triangle)).unwrap(),
+            (q(1, 1), q(1, 3))
+        );
+        // the L-shaped hexagon is a 2×2 square with a unit square cut out of a corner
+        let l_shape = [(0, 0), (2, 0), (2, 1), (1, 1), (1, 2), (0, 2)];
+        assert_eq!(polygon_area(coords(&l_shape)).unwrap(), q(3, 1));
+        assert_eq!(
+            polygon_centroid(coords(&l_shape)).unwrap(),
+            (q(5, 6), q(5, 6))
+        );
+    }
+
+    #[test]
+    fn degenerate_polygons() {
+        assert_eq!(polygon_area(vec![]).unwrap(), MpqExt::ZERO);
+        assert_eq!(
+            polygon_area(coords(&[(1, 2), (3, 4)])).unwrap(),
+            MpqExt::ZERO
+        );
+        // a repeated vertex adds an edge of length zero, which changes nothing
+        let repeated = [(0, 0), (2, 0), (2, 0), (2, 2), (0, 2), (0, 0)];
+        assert_eq!(polygon_area(coords(&repeated)).unwrap(), q(4, 1));
+        // the two lobes of a bowtie are wound in opposite directions and cancel
+        let bowtie = [(0, 0), (2, 2), (2, 0), (0, 2)];
+        assert_eq!(polygon_area(coords(&bowtie)).unwrap(), MpqExt::ZERO);
+        assert!(polygon_centroid(coords(&bowtie)).is_err());
+        assert!(polygon_centroid(coords(&[(0, 0), (1, 1), (2, 2)])).is_err());
+        let error = polygon_area(vec![q(1, 1)]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "points are given as coordinate pairs, but got 1 coordinates"
+        );
+        assert!(polygon_area(vec![MpqExt::Inf(true), q(0, 1)]).is_err());
+    }
+
+    #[test]
+    fn points_in_polygons() {
+        let inside = |points: &[(i64, i64)], x: MpqExt, y: MpqExt| {
+            point_in_polygon(coords(points), x, y).unwrap()
+        };
+        let square = [(0, 0), (2, 0), (2, 2), (0, 2)];
+        assert!(inside(&square, q(1, 1), q(1, 1)));
+        assert!(!inside(&square, q(3, 1), q(1, 1)));
+        // the boundary is part of the region
+        assert!(inside(&square, q(2, 1), q(1, 1)));
+        assert!(inside(&square, q(0, 1), q(0, 1)));
+        assert!(!inside(&square, q(-1, 1), q(0, 1)));
+        // the ray from (-1, 1) passes through the vertex (1, 2) and must count it once
+        let diamond = [(1, 0), (2, 1), (1, 2), (0, 1)];
+        assert!(!inside(&diamond, q(-1, 1), q(2, 1)));
+        assert!(inside(&diamond, q(1, 1), q(1, 1)));
+        assert!(!inside(&diamond, q(1, 10), q(1, 10)));
+        // a segment only contains the points on it
+        let segment = [(0, 0), (2, 2)];
+        assert!(inside(&segment, q(1, 1), q(1, 1)));
+        assert!(!inside(&segment, q(1, 1), q(0, 1)));
+        assert!(!inside(&[], q(0, 1), q(0, 1)));
+        // the pentagram's center is wound around twice and so is outside
+        let star = [(0, 3), (2, -3), (-3, 1), (3, 1), (-2, -3)];
+        assert!(!inside(&star, q(0, 1), q(0, 1)));
+        assert!(inside(&star, q(0, 1), q(23, 10)));
+    }
+
+    #[test]
+    fn convex_hulls() {
+        let points = [0.0, 0.0, 2.0, 0.0, 1.0, 1.0, 2.0, 2.0, 0.0, 2.0, 1.0, 0.0];
+        // the interior point 2 and the edge point 5 are left out
+        assert_eq!(convex_hull(&points).unwrap(), [0, 1, 3, 4]);
+        // repeated points appear once, as their first occurrence
+        let points = [1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        assert_eq!(convex_hull(&points).unwrap(), [1, 2, 0, 4]);
+        assert_eq!(convex_hull(&[]).unwrap(), Vec::<u64>::new());
+        assert_eq!(convex_hull(&[3.0, 4.0, 3.0, 4.0]).unwrap(), [0]);
+        assert_eq!(convex_hull(&[3.0, 4.0, 1.0, 2.0]).unwrap(), [1, 0]);
+        // collinear points leave only the two ends
+        let line = [0.0, 0.0, 3.0, 3.0, 1.0, 1.0, 2.0, 2.0];
+        assert_eq!(convex_hull(&line).unwrap(), [0, 1]);
+        assert!(convex_hull(&[0.0, f64::NAN]).is_err());
+        assert!(convex_hull(&[0.0, 1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn nearly_collinear_points() {
+        let point = |coords: &[f64], i: u64| -> Point {
+            let i = i as usize;
+            let (x, y) = (coords[2 * i], coords[2 * i + 1]);
+            (Mpq::try_from(x).unwrap(), Mpq::try_from(y).unwrap())
+        };
+        // points on the line y = x with perturbations of about an ulp, which a float cross
+        // product would round to whichever sign
+        let mut coords = Vec::new();
+        for i in 0..200 {
+            let t = i as f64 / 7.0;
+            coords.extend([t, t + if i % 2 == 0 { 0.0 } else { 1e-15 * t }]);
+        }
+        let hull = convex_hull(&coords).unwrap();
+        assert!(hull.len() >= 3);
+        for (k, &i) in hull.iter().enumerate() {
+            let j = hull[(k + 1) % hull.len()];
+            let l = hull[(k + 2) % hull.len()];
+            let turn = orientation(&point(&coords, i), &point(&coords, j), &point(&coords, l));
+            assert!(turn > 0u32, "the hull does not turn left at {j}");
+        }
+        // every point is on the hull or to its left
+        for i in 0..200 {
+            for (k, &a) in hull.iter().enumerate() {
+                let b = hull[(k + 1) % hull.len()];
+                let turn = orientation(&point(&coords, a), &point(&coords, b), &point(&coords, i));
+                assert!(turn >= 0u32, "{i} is right of the edge from {a} to {b}");
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Interpolation of sampled curves `(xs[i], ys[i])` with strictly increasing `xs`.
+
+use std::cmp::Ordering;
+
+use anyhow::bail;
+
+/// The most samples [`resample_uniform`] produces.
+pub const MAX_SAMPLES: u64 = 1 << 20;
+
+/// What to return for queries outside `[xs[0], xs[n - 1]]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outside {
+    /// The value at the nearest end.
+    Clamp,
+    /// The continuation of the first or last piece.
+    Extrapolate,
+    NaN,
+}
+
+impl std::str::FromStr for Outside {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(Self::Clamp),
+            "extrapolate" => Ok(Self::Extrapolate),
+            "nan" => Ok(Self::NaN),
+            _ => bail!("unknown mode {s:?} for points outside the samples"),
+        }
+    }
+}
+
+fn check_samples(xs: &[f64], ys: &[f64]) -> Result<(), anyhow::Error> {
+    if xs.len() != ys.len() {
+        bail!("{} x values but {} y values", xs.len(), ys.len());
+    }
+    if xs.len() < 2 {
+        bail!("at least 2 samples are needed");
+    }
+    if let Some(i) =
+        (1..xs.len()).find(|&i| xs[i].partial_cmp(&xs[i - 1]) != Some(Ordering::Greater))
+    {
+        bail!(
+            "xs must be strictly increasing, but xs[{i}] = {} follows xs[{}] = {}",
+            xs[i],
+            i - 1,
+            xs[i - 1]
+        );
+    }
+    Ok(())
+}
+
+/// The index `i` of the piece `[xs[i], xs[i + 1]]` used for `x`, which is the first or last
+/// piece for points outside the samples.
+fn piece(xs: &[f64], x: f64) -> usize {
+    xs.partition_point(|&xi| xi <= x).clamp(1, xs.len() - 1) - 1
+}
+
+/// Evaluates `eval_piece(i, x)` at each query, handling the points outside the samples.
+fn interpolate(
+    xs: &[f64],
+    ys: &[f64],
+    queries: &[f64],
+    outside: Outside,
+    eval_piece: impl Fn(usize, f64) -> f64,
+) -> Vec<f64> {
+    let (first, last) = (xs[0], xs[xs.len() - 1]);
+    queries
+        .iter()
+        .map(|&x| {
+            if x.is_nan() {
+                return f64::NAN;
+            }
+            match outside {
+                _ if (first..=last).contains(&x) => eval_piece(piece(xs, x), x),
+                Outside::Clamp if x < first => ys[0],
+                Outside::Clamp => ys[ys.len() - 1],
+                Outside::Extrapolate => eval_piece(piece(xs, x), x),
+                Outside::NaN => f64::NAN,
+            }
+        })
+        .collect()
+}
+
+pub fn linear(
+    xs: &[f64],
+    ys: &[f64],
+    queries: &[f64],
+    outside: Outside,
+) -> Result<Vec<f64>, anyhow::Error> {
+    check_samples(xs, ys)?;
+    Ok(interpolate(xs, ys, queries, outside, |i, x| {
+        let t = (x - xs[i]) / (xs[i + 1] - xs[i]);
+        ys[i] + t * (ys[i + 1] - ys[i])
+    }))
+}
+
+/// Tangents at the samples for monotone cubic Hermite interpolation, by the method of Fritsch and
+/// Carlson: the interpolant is monotone on every piece on which the samples are.
+fn monotone_tangents(xs: &[f64], ys: &[f64]) -> Vec<f64> {
+    let n = xs.len();
+    let secants: Vec<f64> = (0..n - 1)
+        .map(|i| (ys[i + 1] - ys[i]) / (xs[i + 1] - xs[i]))
+        .collect();
+    let mut tangents = Vec::with_capacity(n);
+    tangents.push(secants[0]);
+    for i in 1..n - 1 {
+        let (d0, d1) = (secants[i - 1], secants[i]);
+        // a local extremum gets a horizontal tangent
+        tangents.push(if d0 * d1 > 0.0 { (d0 + d1) / 2.0 } else { 0.0 });
+    }
+    tangents.push(secants[n - 2]);
+    for (i, &d) in secants.iter().enumerate() {
+        if d == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        // keep (α, β) inside the circle of radius 3, which is sufficient for monotonicity
+        let (alpha, beta) = (tangents[i] / d, tangents[i + 1] / d);
+        let r = alpha.hypot(beta);
+        if r > 3.0 {
+            tangents[i] = 3.0 / r * alpha * d;
+            tangents[i + 1] = 3.0 / r * beta * d;
+        }
+    }
+    tangents
+}
+
+pub fn monotone_cubic(
+    xs: &[f64],
+    ys: &[f64],
+    queries: &[f64],
+    outside: Outside,
+) -> Result<Vec<f64>, anyhow::Error> {
+    check_samples(xs, ys)?;
+    let tangents = monotone_tangents(xs, ys);
+    let n = xs.len();
+    Ok(interpolate(xs, ys, queries, outside, |i, x| {
+        // extrapolate along the tangent at the end rather than the cubic, which grows quickly
+        if x < xs[0] {
+            return ys[0] + tangents[0] * (x - xs[0]);
+        }
+        if x > xs[n - 1] {
+            return ys[n - 1] + tangents[n - 1] * (x - xs[n - 1]);
+        }
+        let h = xs[i + 1] - xs[i];
+        let t = (x - xs[i]) / h;
+        let (t2, t3) = (t * t, t * t * t);
+        // the cubic Hermite basis
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+        h00 * ys[i] + h10 * h * tangents[i] + h01 * ys[i + 1] + h11 * h * tangents[i + 1]
+    }))
+}
+
+/// The `x` positions, in increasing order, where the piecewise-linear curve through the samples
+/// attains `level`. A flat stretch at `level` is reported by its sample points.
+pub fn crossings(xs: &[f64], ys: &[f64], level: f64) -> Result<Vec<f64>, anyhow::Error> {
+    check_samples(xs, ys)?;
+    let mut result = Vec::new();
+    for i in 0..xs.len() {
+        let y = ys[i] - level;
+        if y == 0.0 {
+            result.push(xs[i]);
+        } else if let Some(&y_next) = ys.get(i + 1) {
+            let y_next = y_next - level;
+            if y_next != 0.0 && (y < 0.0) != (y_next < 0.0) {
+                result.push(xs[i] + y / (y - y_next) * (xs[i + 1] - xs[i]));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// `n` evenly spaced samples of the piecewise-linear curve over the whole range of `xs`,
+/// returned as the new `xs` and `ys`. At most [`MAX_SAMPLES`] samples are produced.
+pub fn resample_uniform(
+    xs: &[f64],
+    ys: &[f64],
+    n: u64,
+) -> Result<(Vec<f64>, Vec<f64>), anyhow::Error> {
+    check_samples(xs, ys)?;
+    if n < 2 {
+        bail!("at least 2 samples are needed");
+    }
+    if n > MAX_SAMPLES {
+        bail!("at most {MAX_SAMPLES} samples can be produced, but got {n}");
+    }
+    let n = n as usize;
+    let (first, last) = (xs[0], xs[xs.len() - 1]);
+    let step = (last - first) / (n - 1) as f64;
+    let mut new_xs: Vec<f64> = (0..n).map(|i| first + step * i as f64).collect();
+    // avoid rounding past the last sample
+    new_xs[n - 1] = last;
+    let new_ys = linear(xs, ys, &new_xs, Outside::Clamp)?;
+    Ok((new_xs, new_ys))
+}
+
+fn check_uniform_samples(ys: &[f64], dx: f64) -> Result<(), anyhow::Error> {
+    if ys.len() < 2 {
+        bail!("at least 2 samples are needed");
+    }
+    if !dx.is_finite() {
+        bail!("the sample spacing must be finite, but got {dx}");
+    }
+    Ok(())
+}
+
+/// The integral of the samples `ys` taken every `dx` by the trapezoidal rule.
+pub fn integrate_trapezoid(ys: &[f64], dx: f64) -> Result<f64, anyhow::Error> {
+    check_uniform_samples(ys, dx)?;
+    let n = ys.len();
+    let inner: f64 = ys[1..n - 1].iter().sum();
+    Ok(dx * ((ys[0] + ys[n - 1]) / 2.0 + inner))
+}
+
+/// The integral of the samples `ys` taken every `dx` by Simpson's rule. An even number of
+/// samples leaves an odd number of intervals, of which the last three are integrated by
+/// Simpson's 3/8 rule instead; two samples fall back to the trapezoidal rule.
+pub fn integrate_simpson(ys: &[f64], dx: f64) -> Result<f64, anyhow::Error> {
+    check_uniform_samples(ys, dx)?;
+    let n = ys.len();
+    if n == 2 {
+        return integrate_trapezoid(ys, dx);
+    }
+    // with an even number of samples the last 4 are left for the 3/8 rule
+    let m = if n % 2 == 1 { n } else { n - 3 };
+    let mut sum = 0.0;
+    if m > 1 {
+        // the weights 1, 4, 2, 4, …, 2, 4, 1
+        let inner: f64 = (1..m - 1)
+            .map(|i| if i % 2 == 1 { 4.0 } else { 2.0 } * ys[i])
+            .sum();
+        sum += dx / 3.0 * (ys[0] + inner + ys[m - 1]);
+    }
+    if let [y0, y1, y2, y3] = ys[m - 1..] {
+        sum += 3.0 * dx / 8.0 * (y0 + 3.0 * y1 + 3.0 * y2 + y3);
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XS: [f64; 4] = [0.0, 1.0, 2.0, 4.0];
+    const YS: [f64; 4] = [0.0, 2.0, 1.0, 1.0];
+
+    #[test]
+    fn invalid_samples() {
+        let error = linear(&[0.0, 1.0, 1.0], &[0.0; 3], &[], Outside::Clamp).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "xs must be strictly increasing, but xs[2] = 1 follows xs[1] = 1"
+        );
+        assert!(linear(&[0.0, f64::NAN], &[0.0; 2], &[], Outside::Clamp).is_err());
+        assert!(linear(&XS, &YS[..3], &[], Outside::Clamp).is_err());
+        assert!(crossings(&[0.0], &[0.0], 0.0).is_err());
+    }
+
+    #[test]
+    fn linear_modes() {
+        let queries = [-1.0, 0.5, 1.5, 3.0, 5.0, f64::NAN];
+        let at = |outside| linear(&XS, &YS, &queries, outside).unwrap();
+        assert_eq!(at(Outside::Clamp)[..5], [0.0, 1.0, 1.5, 1.0, 1.0]);
+        assert_eq!(at(Outside::Extrapolate)[..5], [-2.0, 1.0, 1.5, 1.0, 1.0]);
+        let nan = at(Outside::NaN);
+        assert!(nan[0].is_nan() && nan[4].is_nan() && nan[5].is_nan());
+        assert_eq!(nan[1..4], [1.0, 1.5, 1.0]);
+        assert!("wrap".parse::<Outside>().is_err());
+    }
+
+    #[test]
+    fn monotone_cubic_stays_monotone() {
+        let (xs, ys) = ([0.0, 1.0, 2.0, 3.0], [0.0, 0.1, 0.2, 10.0]);
+        let queries: Vec<f64> = (0..=300).map(|i| i as f64 / 100.0).collect();
+        let values = monotone_cubic(&xs, &ys, &queries, Outside::Clamp).unwrap();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        // the interpolant passes through the samples
+        assert_eq!(monotone_cubic(&xs, &ys, &xs, Outside::Clamp).unwrap(), ys);
+    }
+
+    #[test]
+    fn level_crossings() {
+        assert_eq!(crossings(&XS, &YS, 1.5).unwrap(), [0.75, 1.5]);
+        // a flat stretch at the level is reported by its ends
+        assert_eq!(crossings(&XS, &YS, 1.0).unwrap(), [0.5, 2.0, 4.0]);
+        assert!(crossings(&XS, &YS, 3.0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn uniform_resampling() {
+        let (xs, ys) = resample_uniform(&XS, &YS, 5).unwrap();
+        assert_eq!(xs, [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(ys, [0.0, 2.0, 1.0, 1.0, 1.0]);
+        assert!(resample_uniform(&XS, &YS, 1).is_err());
+        assert!(resample_uniform(&XS, &YS, MAX_SAMPLES + 1).is_err());
+    }
+}
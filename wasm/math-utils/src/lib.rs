@@ -1,20 +1,22 @@
 use std::cmp::Ordering;
 use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use flagset::{FlagSet, Flags, flags};
 use malachite::base::num::arithmetic::traits::{
-    Abs, BinomialCoefficient, Ceiling, ExtendedGcd, Factorial, Floor, Gcd, Pow as MpPow, Sign,
-    UnsignedAbs,
+    Abs, BinomialCoefficient, Ceiling, CheckedLogBase, CheckedRoot, CheckedSqrt, DivExact,
+    DivRound, ExtendedGcd, Factorial, Floor, FloorLogBase, FloorLogBase2, Gcd, JacobiSymbol,
+    LegendreSymbol, Mod, Parity, Pow as MpPow, Sign, UnsignedAbs,
 };
+use malachite::base::num::basic::traits::{One, Zero};
 use malachite::base::num::conversion::traits::FromStringBase;
+use malachite::base::num::logic::traits::SignificantBits;
+use malachite::base::rounding_modes::RoundingMode;
 use paste::paste;
 
 use fraction::GenericFraction;
 use malachite::{Integer as Mpz, Natural as Mpn, Rational as Mpq};
 use num::complex::{Complex, Complex64 as c64, ComplexFloat};
-use num::{One as NumOne, Zero as NumZero};
-use num_prime::nt_funcs;
 use puruspe::bessel;
 use quaternion::Quaternion;
 use serde::de::DeserializeOwned;
@@ -22,13 +24,35 @@ use serde::{Deserialize, Serialize};
 use special::LambertW;
 use wasm_minimal_protocol::*;
 
-use math_utils_proc_macro::define_func;
+use math_utils_proc_macro::{define_func, wasm_func};
 
+use crate::complex::ComplexData;
 use crate::frac::FracData;
-use math_utils_base::{MpnExt, MpqExt, MpzExt, traits::*};
+use crate::tensor::Tensor;
+use math_utils_base::{
+    Dual, GaussianFactorization, GaussianInteger, GaussianRational, Interval, MAX_POWER_BITS,
+    MpnExt, MpqExt, MpzExt, PmValue, QuadExt, RadixExpansion, combinatorics, divisors, farey,
+    fixed_width, log2_natural, modular, pell, primes, probability, small_rational, taylor,
+    traits::*,
+};
+mod cache;
+mod calendar;
+mod cbor;
 mod complex;
+mod eval;
+mod float;
 mod frac;
+mod geometry;
+mod interp;
+mod matrix;
+mod poly;
+mod prime_count;
 mod quat;
+mod roman;
+mod series;
+mod stats;
+mod tensor;
+mod vector;
 
 initiate_protocol!();
 
@@ -82,7 +106,7 @@ macro_rules! impl_wasm_conversion_serialize {
         $(
             impl FromWasmInput for $t {
                 fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
-                    Ok(ciborium::de::from_reader(input)?)
+                    cbor::decode(input)
                 }
             }
             impl IntoWasmOutput for $t {
@@ -101,7 +125,30 @@ impl_wasm_conversion_for_num!(
 );
 impl_wasm_conversion_for_complex!(f64, 8);
 impl_wasm_conversion_for_complex!(f32, 4);
-impl_wasm_conversion_serialize!(Mpz, Mpn, Mpq, MpqExt, MpzExt, MpnExt);
+impl_wasm_conversion_serialize!(
+    Mpz,
+    Mpn,
+    Mpq,
+    MpqExt,
+    MpzExt,
+    MpnExt,
+    GaussianRational,
+    GaussianInteger,
+    GaussianFactorization,
+    ComplexData,
+    fixed_width::FixedWidthResult,
+    float::FloatParts,
+    stats::Histogram,
+    RadixExpansion,
+    Interval,
+    cache::CacheStats,
+    cbor::InputLimits,
+    divisors::AliquotSequence,
+    Option<Mpn>,
+    Option<u64>,
+    PmValue,
+    QuadExt
+);
 
 impl FromWasmInput for String {
     fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
@@ -144,7 +191,7 @@ impl IntoWasmOutput for Ordering {
 
 impl FromWasmInput for Option<Ordering> {
     fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
-        if input.len() == 0 {
+        if input.is_empty() {
             Ok(None)
         } else {
             Ok(Some((input[0] as i8).cmp(&0)))
@@ -161,6 +208,29 @@ impl IntoWasmOutput for Option<Ordering> {
     }
 }
 
+// also covers `Quaternion<T>`, which is an alias of `(T, [T; 3])`
+impl<T1, T2> FromWasmInput for (T1, T2)
+where
+    T1: DeserializeOwned,
+    T2: DeserializeOwned,
+{
+    fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
+        cbor::decode(input)
+    }
+}
+
+impl<T1, T2> IntoWasmOutput for (T1, T2)
+where
+    T1: serde::Serialize,
+    T2: serde::Serialize,
+{
+    fn into_wasm_output(self) -> Vec<u8> {
+        let mut out = Vec::new();
+        ciborium::ser::into_writer(&self, &mut out).unwrap();
+        out
+    }
+}
+
 impl<T1, T2, T3> IntoWasmOutput for (T1, T2, T3)
 where
     T1: serde::Serialize,
@@ -174,12 +244,38 @@ where
     }
 }
 
+impl<T1, T2, T3, T4> FromWasmInput for (T1, T2, T3, T4)
+where
+    T1: DeserializeOwned,
+    T2: DeserializeOwned,
+    T3: DeserializeOwned,
+    T4: DeserializeOwned,
+{
+    fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
+        cbor::decode(input)
+    }
+}
+
+impl<T1, T2, T3, T4> IntoWasmOutput for (T1, T2, T3, T4)
+where
+    T1: serde::Serialize,
+    T2: serde::Serialize,
+    T3: serde::Serialize,
+    T4: serde::Serialize,
+{
+    fn into_wasm_output(self) -> Vec<u8> {
+        let mut out = Vec::new();
+        ciborium::ser::into_writer(&self, &mut out).unwrap();
+        out
+    }
+}
+
 impl<T> FromWasmInput for Vec<T>
 where
     T: serde::de::DeserializeOwned,
 {
     fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
-        Ok(ciborium::de::from_reader(input)?)
+        cbor::decode(input)
     }
 }
 
@@ -194,44 +290,44 @@ where
     }
 }
 
-impl<T> FromWasmInput for GenericFraction<T>
+impl<T> FromWasmInput for Tensor<T>
 where
-    T: Clone + Copy + fraction::Integer + DeserializeOwned,
+    T: serde::de::DeserializeOwned,
 {
     fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
-        let frac: FracData<T> = ciborium::de::from_reader(input)?;
-        Ok(frac.into())
+        cbor::decode(input)
     }
 }
 
-impl<T> IntoWasmOutput for GenericFraction<T>
+impl<T> IntoWasmOutput for Tensor<T>
 where
-    T: Clone + Copy + fraction::Integer + Serialize,
+    T: serde::Serialize + Clone,
 {
     fn into_wasm_output(self) -> Vec<u8> {
-        let frac = FracData::from(self);
         let mut out = Vec::new();
-        ciborium::ser::into_writer(&frac, &mut out).unwrap();
+        ciborium::ser::into_writer(&self, &mut out).unwrap();
         out
     }
 }
 
-impl<T> FromWasmInput for Quaternion<T>
+impl<T> FromWasmInput for GenericFraction<T>
 where
-    T: DeserializeOwned,
+    T: Clone + Copy + fraction::Integer + DeserializeOwned,
 {
     fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
-        Ok(ciborium::de::from_reader(input)?)
+        let frac: FracData<T> = cbor::decode(input)?;
+        Ok(frac.into())
     }
 }
 
-impl<T> IntoWasmOutput for Quaternion<T>
+impl<T> IntoWasmOutput for GenericFraction<T>
 where
-    T: Serialize,
+    T: Clone + Copy + fraction::Integer + Serialize,
 {
     fn into_wasm_output(self) -> Vec<u8> {
+        let frac = FracData::from(self);
         let mut out = Vec::new();
-        ciborium::ser::into_writer(&self, &mut out).unwrap();
+        ciborium::ser::into_writer(&frac, &mut out).unwrap();
         out
     }
 }
@@ -257,6 +353,49 @@ where
     }
 }
 
+/// A sequence of numbers as consecutive little-endian values with no framing, like the
+/// `array.map(float.to-bytes).join()` of a Typst array. This skips CBOR for long float arrays.
+struct Packed<T>(Vec<T>);
+
+macro_rules! impl_wasm_conversion_for_packed {
+    ($($t: ty),+$(,)?) => {
+        $(
+            impl FromWasmInput for Packed<$t> {
+                fn from_wasm_input(input: &[u8]) -> Result<Self, anyhow::Error> {
+                    const SIZE: usize = size_of::<$t>();
+                    if input.len() % SIZE != 0 {
+                        bail!(
+                            "packed {} values take a multiple of {SIZE} bytes, but got {}",
+                            stringify!($t),
+                            input.len()
+                        );
+                    }
+                    Ok(Packed(
+                        input
+                            .chunks_exact(SIZE)
+                            .map(|it| <$t>::from_le_bytes(it.try_into().unwrap()))
+                            .collect(),
+                    ))
+                }
+            }
+            impl IntoWasmOutput for Packed<$t> {
+                fn into_wasm_output(self) -> Vec<u8> {
+                    self.0.iter().flat_map(|x| x.to_le_bytes()).collect()
+                }
+            }
+            // a CBOR byte string inside tuples and other structured outputs
+            impl Serialize for Packed<$t> {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    let bytes: Vec<u8> = self.0.iter().flat_map(|x| x.to_le_bytes()).collect();
+                    serializer.serialize_bytes(&bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_wasm_conversion_for_packed!(f64, i64);
+
 // impl<T> IntoWasmOutput for T where T: serde::Serialize {
 //     fn into_wasm_output(self) -> Vec<u8> {
 //         let mut out = Vec::new();
@@ -284,6 +423,184 @@ macro_rules! define_method_func_with_complex {
     };
 }
 
+macro_rules! define_dual_method_func {
+    ($method: ident) => {
+        paste! {define_func!([<dual_ $method>], |a: f64, b: f64| {
+            let y = Dual::new(a, b).$method();
+            (y.re, y.du)
+        });}
+    };
+}
+
+/// The reciprocal trigonometric and hyperbolic functions and their inverses, which `f64` and
+/// `Complex` lack. At a pole a real argument gives `±inf` and a complex one `NaN`.
+///
+/// The inverses follow DLMF §4.23 and §4.37 in being defined through the reciprocal argument,
+/// e.g. `acot(z) = atan(1/z)`, so they inherit their branch cuts from the principal `atan`,
+/// `acos`, `asin`, `atanh`, `acosh` and `asinh`. In particular `acot` takes values in
+/// `(-π/2, π/2]` and jumps at 0, with `acot(±0) = ±π/2`, rather than following the other
+/// convention `π/2 - atan(x)` with values in `(0, π)`.
+trait ReciprocalTrig: Sized {
+    fn cot(self) -> Self;
+    fn sec(self) -> Self;
+    fn csc(self) -> Self;
+    fn coth(self) -> Self;
+    fn sech(self) -> Self;
+    fn csch(self) -> Self;
+    fn acot(self) -> Self;
+    fn asec(self) -> Self;
+    fn acsc(self) -> Self;
+    fn acoth(self) -> Self;
+    fn asech(self) -> Self;
+    fn acsch(self) -> Self;
+}
+
+macro_rules! impl_reciprocal_trig {
+    ($($t: ty),+$(,)?) => {
+        $(
+            impl ReciprocalTrig for $t {
+                fn cot(self) -> Self {
+                    self.tan().recip()
+                }
+                fn sec(self) -> Self {
+                    self.cos().recip()
+                }
+                fn csc(self) -> Self {
+                    self.sin().recip()
+                }
+                fn coth(self) -> Self {
+                    self.tanh().recip()
+                }
+                fn sech(self) -> Self {
+                    self.cosh().recip()
+                }
+                fn csch(self) -> Self {
+                    self.sinh().recip()
+                }
+                fn acot(self) -> Self {
+                    self.recip().atan()
+                }
+                fn asec(self) -> Self {
+                    self.recip().acos()
+                }
+                fn acsc(self) -> Self {
+                    self.recip().asin()
+                }
+                fn acoth(self) -> Self {
+                    self.recip().atanh()
+                }
+                fn asech(self) -> Self {
+                    self.recip().acosh()
+                }
+                fn acsch(self) -> Self {
+                    self.recip().asinh()
+                }
+            }
+        )+
+    };
+}
+
+impl_reciprocal_trig!(f64, c64);
+
+// Plugin Information
+
+include!(concat!(env!("OUT_DIR"), "/plugin_functions.rs"));
+
+/// Whether `PLUGIN_FUNCTIONS` lists `name` with `n_args` arguments, which every `#[wasm_func]`
+/// asserts at compile time.
+const fn is_plugin_function(name: &str, n_args: usize) -> bool {
+    let name = name.as_bytes();
+    let mut i = 0;
+    'functions: while i < PLUGIN_FUNCTIONS.len() {
+        let (listed, listed_n_args, _) = PLUGIN_FUNCTIONS[i];
+        let listed = listed.as_bytes();
+        i += 1;
+        if listed_n_args != n_args || listed.len() != name.len() {
+            continue;
+        }
+        let mut j = 0;
+        while j < name.len() {
+            if listed[j] != name[j] {
+                continue 'functions;
+            }
+            j += 1;
+        }
+        return true;
+    }
+    false
+}
+
+/// Incremented whenever the encoding of arguments or results of an existing function changes.
+const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FunctionInfo {
+    name: String,
+    n_args: usize,
+    /// The number of leading arguments that must be given; the others may be passed as empty
+    /// bytes to leave them out.
+    n_required: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PluginInfo {
+    version: String,
+    protocol: u32,
+    functions: Vec<FunctionInfo>,
+}
+impl_wasm_conversion_serialize!(PluginInfo);
+
+define_func!(plugin_info, || PluginInfo {
+    version: env!("CARGO_PKG_VERSION").to_string(),
+    protocol: PROTOCOL_VERSION,
+    functions: PLUGIN_FUNCTIONS
+        .iter()
+        .map(|&(name, n_args, n_required)| FunctionInfo {
+            name: name.to_string(),
+            n_args,
+            n_required,
+        })
+        .collect(),
+});
+define_func!(has_function, |name: String| PLUGIN_FUNCTIONS
+    .iter()
+    .any(|&(func_name, _, _)| func_name == name));
+
+// the results of functions flagged `cached` are kept between calls; these functions empty the
+// cache, report its use and set its limits
+define_func!(cache_clear, || {
+    cache::clear();
+    cache::stats()
+});
+#[wasm_func]
+fn cache_stats() -> Vec<u8> {
+    cache::stats().into_wasm_output()
+}
+define_func!(cache_configure, |max_entries: u64, max_bytes: u64| {
+    // limits beyond the address space cannot be reached anyway
+    cache::configure(
+        usize::try_from(max_entries).unwrap_or(usize::MAX),
+        usize::try_from(max_bytes).unwrap_or(usize::MAX),
+    );
+    cache::stats()
+});
+
+// CBOR arguments are checked against these limits before they are decoded
+#[wasm_func]
+fn input_limits() -> Vec<u8> {
+    cbor::limits().into_wasm_output()
+}
+define_func!(
+    input_limits_configure,
+    |max_elements: u64, max_digits: u64| {
+        cbor::configure(cbor::InputLimits {
+            max_elements,
+            max_digits,
+        });
+        cbor::limits()
+    }
+);
+
 // Common Functions
 
 define_complex_method_func!(sin);
@@ -302,10 +619,122 @@ define_complex_method_func!(log10);
 define_complex_method_func!(sqrt);
 define_complex_method_func!(cbrt);
 
+/// The real `n`-th root, which is negative for negative `x` and odd `n` and `NaN` for negative
+/// `x` and even `n`, unlike `x.powf(1.0 / n)`.
+fn real_root(x: f64, n: u64) -> f64 {
+    match n {
+        0 => return f64::NAN,
+        1 => return x,
+        2 => return x.sqrt(),
+        3 => return x.cbrt(),
+        _ if x < 0.0 && n.is_multiple_of(2) => return f64::NAN,
+        _ => {}
+    }
+    let a = x.abs();
+    let mut r = a.powf(1.0 / n as f64);
+    // `1 / n` is inexact; a Newton step recovers most of the error
+    if r.is_normal() && n <= i32::MAX as u64 {
+        let k = n as i32;
+        r -= (r.powi(k) - a) / (k as f64 * r.powi(k - 1));
+    }
+    r.copysign(x)
+}
+
+define_float_method_func!(cbrt);
+define_func!(nth_root, |x: f64, n: u64| real_root(x, n));
+
+define_method_func_with_complex!(cot);
+define_method_func_with_complex!(sec);
+define_method_func_with_complex!(csc);
+define_method_func_with_complex!(coth);
+define_method_func_with_complex!(sech);
+define_method_func_with_complex!(csch);
+define_method_func_with_complex!(acot);
+define_method_func_with_complex!(asec);
+define_method_func_with_complex!(acsc);
+define_method_func_with_complex!(acoth);
+define_method_func_with_complex!(asech);
+define_method_func_with_complex!(acsch);
+
 define_method_func_with_complex!(asinh);
 define_method_func_with_complex!(acosh);
 define_method_func_with_complex!(atanh);
 
+define_func!(sinc, |x: f64| if x == 0.0 { 1.0 } else { x.sin() / x });
+define_func!(sinc_complex, |z: c64| if z == c64::ZERO {
+    c64::ONE
+} else {
+    z.sin() / z
+});
+define_func!(sinc_norm, |x: f64| if x == 0.0 {
+    1.0
+} else {
+    let x = std::f64::consts::PI * x;
+    x.sin() / x
+});
+define_func!(sinc_norm_complex, |z: c64| if z == c64::ZERO {
+    c64::ONE
+} else {
+    let z = std::f64::consts::PI * z;
+    z.sin() / z
+});
+define_func!(sinhc, |x: f64| if x == 0.0 { 1.0 } else { x.sinh() / x });
+define_func!(sinhc_complex, |z: c64| if z == c64::ZERO {
+    c64::ONE
+} else {
+    z.sinh() / z
+});
+
+/// The angle of the point `(x, y)` in `(-π, π]`, which is `0` at the origin whatever the signs
+/// of the zeros. Otherwise it agrees with `f64::atan2`, except that the `-π` it gives for
+/// `y = -0, x < 0` is folded to `π`.
+fn principal_angle(y: f64, x: f64) -> f64 {
+    if x == 0.0 && y == 0.0 {
+        return 0.0;
+    }
+    let angle = y.atan2(x);
+    if angle == -std::f64::consts::PI {
+        std::f64::consts::PI
+    } else {
+        angle
+    }
+}
+
+define_func!(atan2, |y: f64, x: f64| principal_angle(y, x));
+define_func!(complex_arg, |z: c64| principal_angle(z.im, z.re));
+// `√(x² + y²)` scaled so that the squares cannot overflow or underflow
+define_func!(hypot, |x: f64, y: f64| x.hypot(y));
+
+/// `ln(base)` for a logarithm base, which must be positive and not 1.
+fn ln_of_base(base: f64) -> f64 {
+    if base > 0.0 && base != 1.0 {
+        base.ln()
+    } else {
+        f64::NAN
+    }
+}
+
+fn real_log_base(x: f64, base: f64) -> f64 {
+    match base {
+        // the dedicated functions are exact at powers of their base
+        2.0 => x.log2(),
+        10.0 => x.log10(),
+        _ => x.ln() / ln_of_base(base),
+    }
+}
+
+define_func!(log_base, |x: f64, base: f64| real_log_base(x, base));
+define_func!(log_base_complex, |z: c64, base: f64| z.ln()
+    / ln_of_base(base));
+
+// these follow Rust: `signum(±0) = ±1`, `fract` and `trunc` keep the sign of `x`, and `fract(x)`
+// is `x - trunc(x)` rather than `x - floor(x)`
+define_float_method_func!(signum);
+define_func!(copysign, |x: f64, y: f64| x.copysign(y));
+define_float_method_func!(fract);
+define_float_method_func!(trunc);
+define_float_method_func!(round_ties_even);
+
 // Special Functions
 
 define_func!(gamma, |x: f64| scirs2_special::gamma(x));
@@ -319,8 +748,52 @@ define_func!(beta_complex, |z1: c64, z2: c64| {
     scirs2_special::beta_complex(z1, z2)
 });
 define_func!(lambert_w, |x: f64| x.lambert_w0());
-define_func!(zeta, |x: f64| scirs2_special::zeta(x), true);
+define_func!(lambert_wm1, |x: f64| x.lambert_wm1());
+define_func!(
+    lambert_w_complex,
+    |z: c64| scirs2_special::lambert_w(z, 0, f64::EPSILON),
+    true
+);
+define_func!(
+    lambert_wm1_complex,
+    |z: c64| scirs2_special::lambert_w(z, -1, f64::EPSILON),
+    true
+);
+// the trailing argument sets the number of series terms; passing empty bytes instead leaves
+// the choice to the library
+define_func!(
+    zeta,
+    |x: f64, terms: Option<u64>| anyhow::Ok(match terms {
+        Some(terms) => series::hurwitz_zeta(x, 1.0, terms)?,
+        None => scirs2_special::zeta(x)?,
+    }),
+    true
+);
 define_func!(zeta_complex, |z: c64| spfunc::zeta::zeta(z));
+define_func!(
+    hurwitz_zeta,
+    |s: f64, q: f64, terms: Option<u64>| anyhow::Ok(match terms {
+        Some(terms) => series::hurwitz_zeta(s, q, terms)?,
+        None => scirs2_special::hurwitz_zeta(s, q)?,
+    }),
+    true
+);
+define_func!(
+    hyp1f1,
+    |a: f64, b: f64, z: f64, max_terms: Option<u64>| anyhow::Ok(match max_terms {
+        Some(max_terms) => series::hyp1f1(a, b, z, max_terms)?,
+        None => scirs2_special::hyp1f1(a, b, z)?,
+    }),
+    true
+);
+define_func!(
+    hyp2f1,
+    |a: f64, b: f64, c: f64, z: f64, max_terms: Option<u64>| anyhow::Ok(match max_terms {
+        Some(max_terms) => series::hyp2f1(a, b, c, z, max_terms)?,
+        None => scirs2_special::hyp2f1(a, b, c, z)?,
+    }),
+    true
+);
 define_func!(airy_ai, |x: f64| scirs2_special::ai(x));
 define_func!(airy_ai_complex, |x: c64| scirs2_special::ai_complex(x));
 define_func!(airy_bi, |x: f64| scirs2_special::bi(x));
@@ -339,11 +812,132 @@ fn prime_factors(arg: &[u8]) -> Vec<u8> {
     out
 }
 
+/// Ω(n), the number of prime factors of `n` counted with multiplicity.
+fn count_prime_factors(n: u64) -> Result<u64, anyhow::Error> {
+    if n == 0 {
+        return Err(anyhow!("0 has no prime factorization"));
+    }
+    Ok(prime_factorization::Factorization::run(n).factors.len() as u64)
+}
+
+define_func!(big_omega, |n: u64| count_prime_factors(n), true);
+// λ(n) = (-1)^Ω(n)
+define_func!(
+    liouville,
+    |n: u64| anyhow::Ok(if count_prime_factors(n)?.is_multiple_of(2) {
+        1i8
+    } else {
+        -1
+    }),
+    true
+);
+// Λ(n) = ln p if n is a power of the prime p and 0 otherwise
+define_func!(
+    von_mangoldt,
+    |n: u64| {
+        if n == 0 {
+            return Err(anyhow!("0 has no prime factorization"));
+        }
+        let factors = prime_factorization::Factorization::run(n).factors;
+        anyhow::Ok(match (factors.first(), factors.last()) {
+            (Some(&p), Some(&q)) if p == q => (p as f64).ln(),
+            _ => 0.0,
+        })
+    },
+    true
+);
+// the factors come sorted, and 0 and 1 have neither by convention
+define_func!(smallest_prime_factor, |n: u64| match n {
+    0 | 1 => 1,
+    n => prime_factorization::Factorization::run(n).factors[0],
+});
+define_func!(largest_prime_factor, |n: u64| match n {
+    0 | 1 => 1,
+    n => *prime_factorization::Factorization::run(n)
+        .factors
+        .last()
+        .unwrap(),
+});
+
 define_func!(extended_gcd, |m: i64, n: i64| ExtendedGcd::extended_gcd(
     m, n
 ));
-define_func!(nth_prime, |n: u64| nt_funcs::nth_prime(n));
-define_func!(prime_pi, |n: u64| nt_funcs::prime_pi(n));
+define_func!(nth_prime, |n: u64| prime_count::nth_prime(n), true, cached);
+define_func!(prime_pi, |n: u64| primes::prime_pi(n), true, cached);
+// `(estimate, lower, upper)` for arguments beyond 64 bits
+define_func!(
+    prime_pi_approx,
+    |n: Mpz| prime_count::prime_pi_approx(&n),
+    true
+);
+define_func!(
+    nth_prime_approx,
+    |k: Mpz| prime_count::nth_prime_approx(&k),
+    true
+);
+define_func!(primes_below, |n: u64| primes::primes_below(n), true);
+define_func!(
+    primes_in_range,
+    |a: u64, b: u64| primes::primes_in_range(a, b),
+    true
+);
+define_func!(
+    prime_count_range,
+    |a: u64, b: u64| anyhow::Ok(primes::primes_in_range(a, b)?.len() as u64),
+    true
+);
+define_func!(
+    composite_smallest_factor_table,
+    |n: u64| primes::smallest_factor_table(n),
+    true
+);
+define_func!(
+    subfactorial,
+    |n: u64| {
+        // !n ≈ n!/e has about n log₂ n bits
+        if n > 1 {
+            check_power_size((n as f64).log2(), n)?;
+        }
+        // !n = (n - 1)(!(n - 1) + !(n - 2)), starting from !0 = 1 and !1 = 0
+        let (mut prev, mut curr) = (Mpn::ONE, Mpn::ZERO);
+        if n == 0 {
+            return Ok(prev);
+        }
+        for k in 2..=n {
+            let next = Mpn::from(k - 1) * (&prev + &curr);
+            (prev, curr) = (curr, next);
+        }
+        anyhow::Ok(curr)
+    },
+    true,
+    cached
+);
+define_func!(
+    sqrt_continued_fraction,
+    |d: u64| {
+        let (a0, period) = pell::sqrt_continued_fraction(&Mpn::from(d))?;
+        // every partial quotient is at most 2⌊√d⌋ and therefore fits in a u64
+        let to_u64 = |a: &Mpn| u64::try_from(a).unwrap();
+        anyhow::Ok(vec![vec![to_u64(&a0)], period.iter().map(to_u64).collect()])
+    },
+    true,
+);
+define_func!(
+    pell_fundamental_solution,
+    |d: Mpz| {
+        let d = Mpn::try_from(d).map_err(|_| anyhow!("d must be positive"))?;
+        pell::pell_fundamental_solution(&d)
+    },
+    true,
+);
+define_func!(
+    pell_solutions,
+    |d: Mpz, k: u64| {
+        let d = Mpn::try_from(d).map_err(|_| anyhow!("d must be positive"))?;
+        pell::pell_solutions(&d, k)
+    },
+    true,
+);
 
 // Rational / Fraction
 
@@ -369,42 +963,102 @@ define_func!(
     true,
 );
 define_func!(fraction_from_float, |num: f64| q64::from(num));
-define_func!(fraction_sub, |x: q64, y: q64| x - y);
-define_func!(fraction_div, |x: q64, y: q64| x / y);
 define_func!(fraction_cmp, |x: q64, y: q64| x.cmp(&y));
-define_func!(fraction_approx, |x: q64, max_den: u64| q64::from(
-    frac::Frac::<u64>::from(x).approx(&max_den)
-));
+// the same as `fraction_limit_den`, under its older name
+define_func!(
+    fraction_approx,
+    |x: q64, max_den: u64| frac::Frac::<u64>::from(x)
+        .limit_den(max_den)
+        .map(q64::from)
+        .map_err(anyhow::Error::msg),
+    true
+);
+define_func!(
+    fraction_limit_den,
+    |x: q64, max_den: u64| frac::Frac::<u64>::from(x)
+        .limit_den(max_den)
+        .map(q64::from)
+        .map_err(anyhow::Error::msg),
+    true
+);
 
-#[wasm_func]
-fn fraction_add(arg: &[u8]) -> Vec<u8> {
-    let fracs: Vec<frac::FracData<u64>> = ciborium::de::from_reader(arg).unwrap();
-    let result: q64 = fracs
-        .iter()
-        .map(|f| (*f).into())
-        .fold(q64::zero(), |acc, x: q64| acc + x);
-    let result = frac::FracData::from(result);
-    let mut out = Vec::new();
-    ciborium::ser::into_writer(&result, &mut out).unwrap();
-    out
+fn fraction_into_mpq(x: q64) -> MpqExt {
+    let frac::FracData { sign, num, den } = x.into();
+    MpqExt::from_sign_and_naturals(sign, Mpn::from(num), Mpn::from(den))
 }
 
-#[wasm_func]
-fn fraction_mul(arg: &[u8]) -> Vec<u8> {
-    let fracs: Vec<frac::FracData<u64>> = ciborium::de::from_reader(arg).unwrap();
-    let result: q64 = fracs
-        .iter()
-        .map(|f| (*f).into())
-        .fold(q64::one(), |acc, x: q64| acc * x);
-    let result = frac::FracData::from(result);
-    let mut out = Vec::new();
-    ciborium::ser::into_writer(&result, &mut out).unwrap();
-    out
+fn mpq_into_fraction(x: MpqExt) -> Result<FracData<u64>, anyhow::Error> {
+    let sign = x.is_sign_positive();
+    let (num, den) = x.into_numerator_and_denominator();
+    let to_u64 =
+        |n: Mpn| u64::try_from(&n).map_err(|_| anyhow!("{n} is too large for a 64-bit fraction"));
+    Ok(frac::FracData {
+        sign,
+        num: to_u64(num)?,
+        den: to_u64(den)?,
+    })
+}
+
+define_func!(fraction_to_mpq, |x: q64| fraction_into_mpq(x));
+define_func!(
+    mpq_to_fraction,
+    |x: MpqExt| mpq_into_fraction(x).map(q64::from),
+    true
+);
+
+/// The result of arithmetic on 64-bit fractions, which is computed exactly and promoted to a
+/// multi-precision rational if its numerator or denominator does not fit in `u64`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum FractionResult {
+    Fraction(FracData<u64>),
+    Promoted(MpqExt),
 }
+impl_wasm_conversion_serialize!(FractionResult);
 
-define_func!(fraction_pow, |frac: q64, exp: i64| q64::from(
-    frac::Frac::<u64>::from(frac).pow(exp)
-),);
+impl From<MpqExt> for FractionResult {
+    fn from(value: MpqExt) -> Self {
+        match mpq_into_fraction(value.clone()) {
+            Ok(frac) => Self::Fraction(frac),
+            Err(_) => Self::Promoted(value),
+        }
+    }
+}
+
+define_func!(fraction_add, |fracs: Vec<FracData<u64>>| {
+    FractionResult::from(
+        fracs
+            .into_iter()
+            .map(|f| fraction_into_mpq(f.into()))
+            .sum::<MpqExt>(),
+    )
+});
+define_func!(fraction_mul, |fracs: Vec<FracData<u64>>| {
+    FractionResult::from(
+        fracs
+            .into_iter()
+            .map(|f| fraction_into_mpq(f.into()))
+            .product::<MpqExt>(),
+    )
+});
+define_func!(fraction_sub, |x: q64, y: q64| FractionResult::from(
+    fraction_into_mpq(x) - fraction_into_mpq(y)
+));
+define_func!(fraction_div, |x: q64, y: q64| FractionResult::from(
+    fraction_into_mpq(x) / fraction_into_mpq(y)
+));
+define_func!(
+    fraction_pow,
+    |frac: q64, exp: i64| {
+        let x = fraction_into_mpq(frac);
+        if let MpqExt::Rational(q) = &x {
+            let log2_base = log2_natural(q.numerator_ref()).max(log2_natural(q.denominator_ref()));
+            check_power_size(log2_base, exp.unsigned_abs())?;
+        }
+        anyhow::Ok(FractionResult::from(MpqExt::pow(x, exp)))
+    },
+    true
+);
 
 // Complex
 
@@ -416,11 +1070,7 @@ fn decode_complex_seq(arg: &[u8]) -> impl Iterator<Item = c64> {
     })
 }
 
-define_func!(
-    parse_complex,
-    |src: String| Ok::<c64, anyhow::Error>(c64::from_str(&src.replace("\u{2212}", "-"))?),
-    true,
-);
+define_func!(parse_complex, |src: String| complex::parse(&src), true);
 
 #[wasm_func]
 fn complex_add(arg: &[u8]) -> Vec<u8> {
@@ -438,15 +1088,368 @@ define_func!(complex_div, |z1: c64, z2: c64| z1 / z2);
 define_func!(complex_pow_real, |z: c64, exp: f64| z.powf(exp));
 define_func!(complex_pow_complex, |z1: c64, z2: c64| z1.powc(z2));
 define_func!(complex_reci, |z: c64| z.recip());
+define_func!(complex_eq, |z1: c64, z2: c64| z1 == z2);
+define_func!(complex_ne, |z1: c64, z2: c64| z1 != z2);
+define_func!(
+    complex_cmp_abs,
+    |z1: c64, z2: c64| complex::cmp_abs(z1, z2),
+    true
+);
+define_func!(
+    complex_approx_eq,
+    |z1: c64, z2: c64, tol: f64| complex::approx_eq(z1, z2, tol),
+    true
+);
+define_func!(
+    complex_nth_roots,
+    |z: c64, n: u64| complex::roots(z, n)
+        .map(|roots| roots.into_iter().map(ComplexData::from).collect::<Vec<_>>()),
+    true
+);
+define_func!(complex_log_branch, |z: c64, k: i64| complex::log_branch(
+    z, k
+));
+define_func!(complex_powi, |z: c64, n: i64| complex::powi(z, n), true);
+define_func!(
+    complex_root,
+    |z: c64, n: u64, k: i64| complex::root(z, n, k),
+    true
+);
+
+// Automatic Differentiation
+
+// each function takes a dual number `a + bε` and returns its image `(value, derivative)`, which
+// for `b = 1` is `(f(a), f'(a))`
+define_dual_method_func!(sin);
+define_dual_method_func!(cos);
+define_dual_method_func!(tan);
+define_dual_method_func!(exp);
+define_dual_method_func!(ln);
+define_dual_method_func!(sqrt);
+define_dual_method_func!(recip);
+define_func!(dual_powf, |a: f64, b: f64, n: f64| {
+    let y = Dual::new(a, b).powf(n);
+    (y.re, y.du)
+});
+define_func!(dual_mul, |a: f64, b: f64, c: f64, d: f64| {
+    let y = Dual::new(a, b) * Dual::new(c, d);
+    (y.re, y.du)
+});
+define_func!(dual_div, |a: f64, b: f64, c: f64, d: f64| {
+    let y = Dual::new(a, b) / Dual::new(c, d);
+    (y.re, y.du)
+});
+
+// Polynomials
+
+define_func!(poly_eval, |coeffs: Vec<f64>, x: f64| poly::eval(&coeffs, x));
+define_func!(poly_eval_tensor, |coeffs: Vec<f64>, xs: Tensor<f64>| {
+    xs.map(|x| poly::eval(&coeffs, x))
+});
+define_func!(poly_eval_complex, |coeffs: Vec<f64>, z: c64| {
+    poly::eval_complex(&coeffs, z)
+});
+define_func!(poly_eval_deriv, |coeffs: Vec<f64>, x: f64| {
+    poly::eval_with_derivative(&coeffs, x)
+});
+define_func!(
+    poly_roots_real,
+    |coeffs: Vec<f64>| poly::real_roots(&coeffs),
+    true
+);
+
+fn into_complex_data(roots: Vec<c64>) -> Vec<ComplexData> {
+    roots.into_iter().map(ComplexData::from).collect()
+}
+
+define_func!(solve_quadratic, |a: f64, b: f64, c: f64| {
+    into_complex_data(poly::solve_quadratic(a, b, c))
+});
+define_func!(solve_cubic, |a: f64, b: f64, c: f64, d: f64| {
+    into_complex_data(poly::solve_cubic(a, b, c, d))
+});
+define_func!(solve_quartic, |a: f64, b: f64, c: f64, d: f64, e: f64| {
+    into_complex_data(poly::solve_quartic(a, b, c, d, e))
+});
+
+// Calendar
+
+define_func!(is_leap_year, |year: i64, julian: bool| {
+    calendar::is_leap_year(year, julian)
+});
+define_func!(
+    julian_day_number,
+    |year: i64, month: i64, day: i64, julian: bool| {
+        calendar::julian_day_number(year, month, day, julian)
+    },
+    true,
+);
+define_func!(date_from_julian_day_number, |jdn: i64, julian: bool| {
+    calendar::date_from_julian_day_number(jdn, julian)
+});
+define_func!(
+    day_of_week,
+    |year: i64, month: i64, day: i64, julian: bool| calendar::day_of_week(year, month, day, julian),
+    true,
+);
+define_func!(easter_date, |year: i64| calendar::easter_date(year));
+define_func!(
+    days_between,
+    |y1: i64, m1: i64, d1: i64, y2: i64, m2: i64, d2: i64, julian: bool| {
+        Ok::<i64, anyhow::Error>(
+            calendar::julian_day_number(y2, m2, d2, julian)?
+                - calendar::julian_day_number(y1, m1, d1, julian)?,
+        )
+    },
+    true,
+);
+
+// Statistics
+
+define_func!(sum_accurate, |data: Packed<f64>, exact: bool| if exact {
+    // rounding the exact sum once gives the correctly rounded result
+    stats::exact_sum(&data.0).to_f64_nearest()
+} else {
+    stats::neumaier_sum(&data.0)
+});
+define_func!(sum_exact_rational, |data: Packed<f64>| stats::exact_sum(
+    &data.0
+));
+define_func!(
+    dot_accurate,
+    |xs: Packed<f64>, ys: Packed<f64>| stats::dot_accurate(&xs.0, &ys.0),
+    true
+);
+define_func!(
+    histogram,
+    |data: Packed<f64>, n_bins: u64, lo: f64, hi: f64| stats::histogram(&data.0, n_bins, lo, hi),
+    true
+);
+define_func!(
+    histogram_auto,
+    |data: Packed<f64>| stats::histogram_auto(&data.0),
+    true
+);
+define_func!(
+    bin_indices,
+    |data: Packed<f64>, edges: Packed<f64>| stats::bin_indices(&data.0, &edges.0).map(Packed),
+    true
+);
+define_func!(stats_mean, |data: Vec<f64>| stats::mean(&data), true);
+define_func!(
+    stats_variance,
+    |data: Vec<f64>, sample: bool| stats::variance(&data, sample),
+    true
+);
+define_func!(
+    stats_stddev,
+    |data: Vec<f64>, sample: bool| stats::stddev(&data, sample),
+    true
+);
+define_func!(
+    logsumexp,
+    |data: Packed<f64>| stats::logsumexp(&data.0),
+    true
+);
+define_func!(
+    softmax,
+    |data: Packed<f64>| stats::softmax(&data.0).map(Packed),
+    true
+);
+define_func!(
+    log_softmax,
+    |data: Packed<f64>| stats::log_softmax(&data.0).map(Packed),
+    true
+);
+// the tensor forms work on each slice along the last axis, e.g. each row of a matrix
+define_func!(
+    stats_mean_tensor,
+    |data: Tensor<f64>| data.reduce_lanes(stats::mean),
+    true
+);
+define_func!(
+    logsumexp_tensor,
+    |data: Tensor<f64>| data.reduce_lanes(stats::logsumexp),
+    true
+);
+define_func!(
+    softmax_tensor,
+    |data: Tensor<f64>| data.map_lanes(stats::softmax),
+    true
+);
+define_func!(
+    log_softmax_tensor,
+    |data: Tensor<f64>| data.map_lanes(stats::log_softmax),
+    true
+);
+
+// Interpolation
+
+define_func!(
+    interp_linear,
+    |xs: Packed<f64>, ys: Packed<f64>, queries: Packed<f64>, outside: String| {
+        interp::linear(&xs.0, &ys.0, &queries.0, outside.parse()?).map(Packed)
+    },
+    true
+);
+define_func!(
+    interp_monotone,
+    |xs: Packed<f64>, ys: Packed<f64>, queries: Packed<f64>, outside: String| {
+        interp::monotone_cubic(&xs.0, &ys.0, &queries.0, outside.parse()?).map(Packed)
+    },
+    true
+);
+define_func!(
+    interp_crossings,
+    |xs: Packed<f64>, ys: Packed<f64>, level: f64| interp::crossings(&xs.0, &ys.0, level)
+        .map(Packed),
+    true
+);
+define_func!(
+    resample_uniform,
+    |xs: Packed<f64>, ys: Packed<f64>, n: u64| {
+        let (xs, ys) = interp::resample_uniform(&xs.0, &ys.0, n)?;
+        anyhow::Ok((Packed(xs), Packed(ys)))
+    },
+    true
+);
+
+// integrals of samples `ys` taken at equal spacing `dx`
+define_func!(
+    integrate_trapezoid,
+    |ys: Vec<f64>, dx: f64| interp::integrate_trapezoid(&ys, dx),
+    true
+);
+define_func!(
+    integrate_simpson,
+    |ys: Vec<f64>, dx: f64| interp::integrate_simpson(&ys, dx),
+    true
+);
+
+// Vectors
+
+define_func!(
+    vec_dot,
+    |a: Packed<f64>, b: Packed<f64>| vector::dot(&a.0, &b.0),
+    true
+);
+define_func!(
+    vec_cross,
+    |a: Packed<f64>, b: Packed<f64>| vector::cross::<f64>(&a.0, &b.0).map(Packed),
+    true
+);
+define_func!(
+    vec_norm,
+    |a: Packed<f64>, p: f64| vector::norm(&a.0, p),
+    true
+);
+define_func!(
+    vec_angle,
+    |a: Packed<f64>, b: Packed<f64>| vector::angle(&a.0, &b.0),
+    true
+);
+define_func!(
+    vec_project,
+    |a: Packed<f64>, onto: Packed<f64>| vector::project(&a.0, &onto.0).map(Packed),
+    true
+);
+define_func!(
+    mpq_vec_dot,
+    |a: Vec<MpqExt>, b: Vec<MpqExt>| vector::dot_exact(&a, &b),
+    true
+);
+define_func!(mpq_vec_norm_sq, |a: Vec<MpqExt>| vector::norm_sq_exact(&a));
+define_func!(
+    mpq_vec_cross,
+    |a: Vec<MpqExt>, b: Vec<MpqExt>| vector::cross::<MpqExt>(&a, &b),
+    true
+);
+
+// Matrices
+
+define_func!(
+    mpq_matrix_det,
+    |rows: Vec<Vec<MpqExt>>| matrix::det(rows),
+    true
+);
+define_func!(
+    mpq_matrix_inverse,
+    |rows: Vec<Vec<MpqExt>>| matrix::inverse(rows),
+    true
+);
+define_func!(
+    mpq_matrix_solve,
+    |rows: Vec<Vec<MpqExt>>, b: Vec<MpqExt>| matrix::solve(rows, b),
+    true
+);
+
+// Expressions
+
+// `eval_exact("1/3 + 2^-2")` is exact and gives `inf` and `nan` for divisions by zero, while
+// `eval_float` also knows constants such as `pi` and the real functions above, e.g. `gamma(1/2)`
+define_func!(eval_exact, |src: String| eval::eval_exact(&src), true);
+define_func!(eval_float, |src: String| eval::eval_float(&src), true);
+
+// Geometry
+
+define_func!(
+    polygon_area,
+    |points: Vec<MpqExt>| geometry::polygon_area(points),
+    true
+);
+define_func!(
+    polygon_centroid,
+    |points: Vec<MpqExt>| geometry::polygon_centroid(points),
+    true
+);
+define_func!(
+    point_in_polygon,
+    |points: Vec<MpqExt>, x: MpqExt, y: MpqExt| geometry::point_in_polygon(points, x, y),
+    true
+);
+define_func!(
+    convex_hull,
+    |points: Packed<f64>| geometry::convex_hull(&points.0),
+    true
+);
+
+// Floating Point
+
+define_func!(float_decompose, |x: f64| float::decompose(x));
+define_func!(float_next_after, |x: f64, toward: f64| float::next_after(
+    x, toward
+));
+define_func!(float_ulp, |x: f64| float::ulp(x));
+define_func!(
+    float_from_parts,
+    |sign: u64, exponent_bits: u64, mantissa_bits: u64| float::from_parts(
+        sign,
+        exponent_bits,
+        mantissa_bits
+    ),
+    true
+);
+
+// Interval Arithmetic
+
+// intervals are passed as pairs `(lo, hi)`, and every result is rounded outward
+define_func!(interval_add, |x: Interval, y: Interval| x + y);
+define_func!(interval_sub, |x: Interval, y: Interval| x - y);
+define_func!(interval_mul, |x: Interval, y: Interval| x * y);
+define_func!(interval_div, |x: Interval, y: Interval| x / y);
+define_func!(interval_neg, |x: Interval| -x);
+define_func!(interval_abs, |x: Interval| x.abs());
+define_func!(interval_sqrt, |x: Interval| x.sqrt(), true);
+define_func!(interval_width, |x: Interval| x.width());
+
+// Quaternions
+
+#[allow(non_camel_case_types)]
+type h64 = Quaternion<f64>;
+
+define_func!(quaternion_mul, |x: h64, y: h64| quaternion::mul(x, y));
+// define_func!(quaternion_inv, |x: h64| quaternion::inv(x));
 
-// Quaternions
-
-#[allow(non_camel_case_types)]
-type h64 = Quaternion<f64>;
-
-define_func!(quaternion_mul, |x: h64, y: h64| quaternion::mul(x, y));
-// define_func!(quaternion_inv, |x: h64| quaternion::inv(x));
-
 // Multi-precision Integers
 
 macro_rules! sanitize_numeric_src {
@@ -492,36 +1495,515 @@ define_func!(
     true,
 );
 define_func!(mpz_from_int, |src: i64| MpzExt::from(src));
+define_func!(mpz_from_float, |x: f64| MpzExt::try_from(x), true);
+define_func!(mpz_to_float, |x: MpzExt| TryInto::<f64>::try_into(x), true);
 define_func!(mpz_repr, |x: MpzExt| x.to_string());
 define_func!(
-    mpz_to_string,
-    |x: MpzExt, options: FlagSet<IntLayoutOptions>| x.to_layout_string(options)
+    mpz_to_string,
+    |x: MpzExt, options: FlagSet<IntLayoutOptions>| x.to_layout_string(options)
+);
+define_func!(
+    mpz_to_roman,
+    |x: Mpn| match u32::try_from(&x) {
+        Ok(n) => roman::to_roman(n),
+        Err(_) => Err(anyhow!(
+            "{x} is out of the range [1, {}] of Roman numerals",
+            roman::MAX_ROMAN
+        )),
+    },
+    true
+);
+define_func!(
+    roman_to_mpz,
+    |src: String| anyhow::Ok(Mpn::from(roman::from_roman(&src)?)),
+    true
+);
+
+#[wasm_func]
+fn verify_mpz(arg: &[u8]) -> Vec<u8> {
+    cbor::decode::<Mpz>(arg).is_ok().into_wasm_output()
+}
+
+define_func!(mpz_add, |nums: Vec<MpzExt>| nums.iter().sum::<MpzExt>());
+define_func!(mpz_sub, |x: MpzExt, y: MpzExt| x - y);
+define_func!(mpz_mul, |nums: Vec<MpzExt>| nums.iter().product::<MpzExt>());
+define_func!(mpz_div, |x: MpzExt, y: MpzExt| x / y);
+/// Unwraps the operands of the finite-only divisions below.
+fn finite_division_operands(x: MpzExt, y: MpzExt) -> Result<(Mpz, Mpz), anyhow::Error> {
+    let (x, y): (Mpz, Mpz) = (x.try_into()?, y.try_into()?);
+    if y == 0 {
+        return Err(anyhow!("division by zero"));
+    }
+    Ok((x, y))
+}
+
+// `mpz_div` truncates toward zero like C. The floor variants round the quotient toward −∞ like
+// Python's `//` and `%`, so the remainder takes the sign of the divisor. The Euclidean variants
+// always give a remainder in [0, |y|), and the quotient is whatever satisfies x = qy + r.
+define_func!(
+    mpz_div_floor,
+    |x: MpzExt, y: MpzExt| {
+        let (x, y) = finite_division_operands(x, y)?;
+        anyhow::Ok(MpzExt::from(x.div_round(y, RoundingMode::Floor).0))
+    },
+    true
+);
+define_func!(
+    mpz_mod_floor,
+    |x: MpzExt, y: MpzExt| {
+        let (x, y) = finite_division_operands(x, y)?;
+        anyhow::Ok(MpzExt::from(x.mod_op(y)))
+    },
+    true
+);
+define_func!(
+    mpz_div_euclid,
+    |x: MpzExt, y: MpzExt| {
+        let (x, y) = finite_division_operands(x, y)?;
+        let r = (&x).mod_op((&y).abs());
+        anyhow::Ok(MpzExt::from((x - r).div_exact(y)))
+    },
+    true
+);
+define_func!(
+    mpz_rem_euclid,
+    |x: MpzExt, y: MpzExt| {
+        let (x, y) = finite_division_operands(x, y)?;
+        anyhow::Ok(MpzExt::from(x.mod_op(y.abs())))
+    },
+    true
+);
+define_func!(mpz_neg, |x: MpzExt| -x);
+// bitwise operations treat negative integers as infinite two's-complement bit strings, so that
+// -1 is all ones and `!x = -x - 1`
+define_func!(mpz_and, |x: Mpz, y: Mpz| x & y);
+define_func!(mpz_or, |x: Mpz, y: Mpz| x | y);
+define_func!(mpz_xor, |x: Mpz, y: Mpz| x ^ y);
+define_func!(mpz_not, |x: Mpz| !x);
+define_func!(
+    mpz_shl,
+    |x: Mpz, n: u64| {
+        let bits = x.unsigned_abs_ref().significant_bits().saturating_add(n);
+        if x != 0 && bits as f64 > MAX_POWER_BITS {
+            bail!("the shift would take {bits} bits, more than the limit of {MAX_POWER_BITS:e}");
+        }
+        anyhow::Ok(x << n)
+    },
+    true
+);
+// rounds toward −∞ like an arithmetic shift, so -1 >> n stays -1
+define_func!(mpz_shr, |x: Mpz, n: u64| x >> n);
+define_func!(
+    mpz_popcount,
+    |x: Mpz| x
+        .checked_count_ones()
+        .ok_or_else(|| anyhow!("a negative integer has infinitely many one bits")),
+    true
+);
+
+/// The base of an integer logarithm, which must be at least 2.
+fn integer_log_base(base: u64) -> Result<Mpn, anyhow::Error> {
+    if base < 2 {
+        bail!("the base of a logarithm must be at least 2, but got {base}");
+    }
+    Ok(Mpn::from(base))
+}
+
+// integer logarithms are computed exactly, so exact powers such as 10^100 are never off by one
+define_func!(
+    mpz_ilog,
+    |x: Mpz, base: u64| {
+        let base = integer_log_base(base)?;
+        if x <= 0u32 {
+            bail!("the logarithm of {x} is undefined");
+        }
+        anyhow::Ok(x.unsigned_abs_ref().floor_log_base(&base))
+    },
+    true
+);
+define_func!(
+    mpz_is_power_of,
+    |x: Mpz, base: u64| {
+        let base = integer_log_base(base)?;
+        anyhow::Ok(if x > 0u32 {
+            x.unsigned_abs_ref().checked_log_base(&base)
+        } else {
+            None
+        })
+    },
+    true
+);
+define_func!(mpz_checked_ilog2, |x: Mpz| (x > 0u32)
+    .then(|| x.unsigned_abs_ref().floor_log_base_2()));
+define_func!(mpz_checked_ilog10, |x: Mpz| (x > 0u32)
+    .then(|| x.unsigned_abs_ref().floor_log_base(&Mpn::TEN)));
+define_func!(
+    mpz_sig_figs,
+    |x: Mpz, n: u64| {
+        // rounds half to even, and reports how many trailing digits were zeroed; a carry such
+        // as 999 → 1000 leaves that count unchanged
+        if n == 0 {
+            bail!("at least one significant digit must be kept");
+        }
+        if x == 0u32 {
+            return Ok((x, 0));
+        }
+        let digits = x.unsigned_abs_ref().floor_log_base(&Mpn::TEN) + 1;
+        if digits <= n {
+            return Ok((x, 0));
+        }
+        let dropped = digits - n;
+        let scale = Mpz::from(Mpn::TEN.pow(dropped));
+        let rounded = x.div_round(&scale, RoundingMode::Nearest).0 * scale;
+        anyhow::Ok((rounded, dropped))
+    },
+    true
+);
+
+/// Fails when an integer of `log₂` about `log2_base` raised to `exp` would take more than
+/// [`MAX_POWER_BITS`] bits.
+fn check_power_size(log2_base: f64, exp: u64) -> Result<(), anyhow::Error> {
+    let bits = log2_base * exp as f64;
+    if bits > MAX_POWER_BITS {
+        bail!(
+            "the power would take about {bits:.3e} bits, more than the limit of {MAX_POWER_BITS:e}"
+        );
+    }
+    Ok(())
+}
+
+define_func!(
+    mpz_pow,
+    |x: MpzExt, y: u64| {
+        if let MpzExt::Integer(n) = &x {
+            check_power_size(log2_natural(n.unsigned_abs_ref()), y)?;
+        }
+        anyhow::Ok(x.pow(y))
+    },
+    true
+);
+define_func!(
+    mpz_pow_mpn,
+    |base: Mpz, exp: Mpn| TryInto::<Mpz>::try_into(MpzExt::from(base).pow(exp)).map_err(|_| {
+        anyhow!("the power would take more than the limit of {MAX_POWER_BITS:e} bits")
+    }),
+    true,
+);
+define_func!(mpz_abs, |x: MpzExt| x.unsigned_abs());
+define_func!(mpz_sign, |x: MpzExt| x.sign());
+define_func!(mpz_cmp, |x: MpzExt, y: MpzExt| x.partial_cmp(&y));
+define_func!(mpz_eq, |x: MpzExt, y: MpzExt| x == y);
+define_func!(mpz_fact, |n: u64| Mpn::factorial(n), false, cached);
+define_func!(
+    mpz_tangent_number,
+    |n: u64| taylor::tangent_number(n),
+    true,
+    cached
+);
+define_func!(
+    mpz_secant_number,
+    |n: u64| taylor::secant_number(n),
+    true,
+    cached
+);
+define_func!(
+    mpq_taylor_coeff,
+    |function: String, n: u64| taylor::taylor_coeff(&function, n).map(MpqExt::from),
+    true
+);
+define_func!(
+    mpz_binom,
+    |n: Mpz, k: Mpz| Mpz::binomial_coefficient(n, k),
+    false,
+    cached
+);
+define_func!(
+    mpz_multinomial,
+    |ks: Vec<Mpn>| {
+        // (k₁ + … + kₘ; k₁, …, kₘ) = ∏ binom(k₁ + … + kᵢ, kᵢ)
+        let mut n = Mpn::ZERO;
+        let mut result = Mpn::ONE;
+        for k in ks {
+            n += &k;
+            result *= Mpn::binomial_coefficient(n.clone(), k);
+        }
+        result
+    },
+    false,
+    cached
+);
+define_func!(
+    combination_unrank,
+    |n: u64, k: u64, rank: Mpz| combinatorics::combination_unrank(n, k, &rank),
+    true
+);
+define_func!(
+    combination_rank,
+    |n: u64, indices: Vec<u64>| combinatorics::combination_rank(n, &indices),
+    true
+);
+define_func!(
+    permutation_unrank,
+    |n: u64, rank: Mpz| combinatorics::permutation_unrank(n, &rank),
+    true
+);
+define_func!(
+    permutation_rank,
+    |permutation: Vec<u64>| combinatorics::permutation_rank(&permutation),
+    true
+);
+define_func!(mpz_gcd, |m: Mpz, n: Mpz| Mpn::gcd(
+    m.unsigned_abs(),
+    n.unsigned_abs()
+));
+define_func!(mpz_egcd, |m: Mpz, n: Mpz| Mpz::extended_gcd(m, n));
+define_func!(
+    mpz_crt,
+    |residues: Vec<Mpz>, moduli: Vec<Mpz>| { modular::crt(&residues, &moduli) },
+    true,
+);
+define_func!(
+    mpz_rational_reconstruction,
+    |residue: Mpz, modulus: Mpz, max_num: Mpz, max_den: Mpz| {
+        modular::rational_reconstruction(&residue, &modulus, &max_num, &max_den)?
+            .ok_or_else(|| anyhow!("no fraction within the bounds has this residue"))
+    },
+    true,
+);
+define_func!(
+    mpz_multiplicative_order,
+    |a: Mpz, m: Mpz| modular::multiplicative_order(&a, &m),
+    true,
+);
+define_func!(
+    mpz_discrete_log,
+    |base: Mpz, target: Mpz, m: Mpz, max_steps: u64| {
+        modular::discrete_log(&base, &target, &m, max_steps)?
+            .ok_or_else(|| anyhow!("target is not a power of base"))
+    },
+    true,
+);
+fn check_positive(n: &Mpz) -> Result<Mpn, anyhow::Error> {
+    match Mpn::try_from(n) {
+        Ok(n) if n != 0u32 => Ok(n),
+        _ => Err(anyhow!("{n} is not a positive integer")),
+    }
+}
+
+/// Upper bound on the number of divisors returned by `mpz_divisors`.
+const MAX_DIVISORS: u64 = 1 << 16;
+
+define_func!(
+    mpz_euler_phi,
+    |n: Mpz| divisors::euler_phi(&check_positive(&n)?),
+    true
+);
+define_func!(
+    mpz_mobius,
+    |n: Mpz| divisors::mobius(&check_positive(&n)?),
+    true
+);
+define_func!(
+    mpz_sigma,
+    |n: Mpz, k: u64| {
+        let n = check_positive(&n)?;
+        // σₖ(n) is below 2nᵏ for k ≥ 2 and has about as many bits as n for k = 1
+        check_power_size(log2_natural(&n), k)?;
+        divisors::sigma(&n, k)
+    },
+    true
+);
+define_func!(
+    mpz_num_divisors,
+    |n: Mpz| divisors::sigma(&check_positive(&n)?, 0),
+    true
+);
+define_func!(
+    mpz_divisors,
+    |n: Mpz| divisors::divisors(&check_positive(&n)?, MAX_DIVISORS),
+    true
+);
+
+/// Upper bound on the number of steps taken by `mpz_aliquot_sequence`.
+const MAX_ALIQUOT_STEPS: u64 = 1000;
+
+/// The smallest and the largest prime factor of `n`, both 1 for `n ≤ 1` as for the 64-bit
+/// versions.
+fn extreme_prime_factors(n: &Mpz) -> Result<(Mpn, Mpn), anyhow::Error> {
+    if *n <= 1u32 {
+        return Ok((Mpn::ONE, Mpn::ONE));
+    }
+    let factors = primes::factorize_bounded(n.unsigned_abs_ref())?;
+    Ok((factors[0].0.clone(), factors.last().unwrap().0.clone()))
+}
+
+define_func!(
+    mpz_smallest_prime_factor,
+    |n: Mpz| anyhow::Ok(extreme_prime_factors(&n)?.0),
+    true
+);
+define_func!(
+    mpz_largest_prime_factor,
+    |n: Mpz| anyhow::Ok(extreme_prime_factors(&n)?.1),
+    true
+);
+define_func!(
+    mpz_aliquot_sum,
+    |n: Mpz| divisors::aliquot_sum(&check_positive(&n)?),
+    true
+);
+define_func!(
+    mpz_is_perfect,
+    |n: Mpz| anyhow::Ok(divisors::abundance(&check_positive(&n)?)?.is_eq()),
+    true
+);
+define_func!(
+    mpz_is_abundant,
+    |n: Mpz| anyhow::Ok(divisors::abundance(&check_positive(&n)?)?.is_gt()),
+    true
+);
+define_func!(
+    mpz_is_deficient,
+    |n: Mpz| anyhow::Ok(divisors::abundance(&check_positive(&n)?)?.is_lt()),
+    true
+);
+define_func!(
+    mpz_amicable_partner,
+    |n: Mpz| divisors::amicable_partner(&check_positive(&n)?),
+    true
+);
+define_func!(
+    mpz_aliquot_sequence,
+    |n: Mpz, max_steps: u64| {
+        if max_steps > MAX_ALIQUOT_STEPS {
+            return Err(anyhow!(
+                "at most {MAX_ALIQUOT_STEPS} steps are allowed, but got {max_steps}"
+            ));
+        }
+        divisors::aliquot_sequence(&check_positive(&n)?, max_steps)
+    },
+    true
+);
+
+define_func!(mpn_is_prime, |n: Mpn| primes::is_prime(&n));
+
+fn check_prime(p: &Mpz) -> Result<Mpn, anyhow::Error> {
+    match Mpn::try_from(p) {
+        Ok(p) if primes::is_probable_prime(&p) => Ok(p),
+        _ => Err(anyhow!("{p} is not a prime")),
+    }
+}
+
+/// Upper bound on the number of roots returned by `mpz_sqrt_mod_prime_power`; only moduli
+/// sharing a large prime power with `a` come close to it.
+const MAX_SQRT_MOD_ROOTS: u64 = 1 << 16;
+
+define_func!(
+    mpz_sqrt_mod,
+    |a: Mpz, p: Mpz| {
+        let p = check_prime(&p)?;
+        if p == 2u32 {
+            return Err(anyhow!("p must be an odd prime"));
+        }
+        let r = modular::sqrt_mod_prime(&a, &p)
+            .ok_or_else(|| anyhow!("{a} is a quadratic non-residue modulo {p}"))?;
+        let mut roots = vec![(&p - &r) % &p, r];
+        roots.sort();
+        roots.dedup();
+        Ok(roots)
+    },
+    true,
+);
+define_func!(
+    mpz_sqrt_mod_prime_power,
+    |a: Mpz, p: Mpz, k: u64| {
+        modular::sqrt_mod_prime_power(&a, &check_prime(&p)?, k, MAX_SQRT_MOD_ROOTS)
+    },
+    true,
+);
+define_func!(
+    mpz_sqrt_mod_composite,
+    |a: Mpz, n: Mpz, max_roots: u64| modular::sqrt_mod(&a, &n, max_roots),
+    true,
+);
+define_func!(
+    mpz_legendre_symbol,
+    |a: Mpz, p: Mpz| {
+        if p <= 2 || !primes::is_probable_prime(p.unsigned_abs_ref()) {
+            Err(anyhow!("p must be an odd prime"))
+        } else {
+            Ok(a.legendre_symbol(p))
+        }
+    },
+    true,
+);
+// malachite computes (a/n) by quadratic reciprocity, halving out the factors of 2 of the top
+// argument, without factoring n
+define_func!(
+    mpz_jacobi_symbol,
+    |a: Mpz, n: Mpz| {
+        if n <= 0 || n.even() {
+            Err(anyhow!(
+                "the Jacobi symbol needs a positive odd modulus, but got {n}"
+            ))
+        } else {
+            Ok(a.jacobi_symbol(n))
+        }
+    },
+    true,
+);
+define_func!(mpz_is_square, |n: Mpz| n >= 0
+    && n.unsigned_abs().checked_sqrt().is_some());
+define_func!(mpz_is_cube, |n: Mpz| n.checked_root(3u64).is_some());
+// `√n = c√r` with `r` square-free; a negative `n` keeps its sign in `r`
+define_func!(
+    mpz_simplify_sqrt,
+    |n: Mpz| {
+        let (c, r) = primes::square_free_decomposition(n.unsigned_abs_ref())?;
+        let r = Mpz::from(r);
+        anyhow::Ok((Mpz::from(c), if n < 0 { -r } else { r }))
+    },
+    true
+);
+
+// Fixed-width Integers
+
+define_func!(
+    fixed_add,
+    |x: Mpz, y: Mpz, bits: u64, signed: bool| fixed_width::add(&x, &y, bits, signed),
+    true
+);
+define_func!(
+    fixed_mul,
+    |x: Mpz, y: Mpz, bits: u64, signed: bool| fixed_width::mul(&x, &y, bits, signed),
+    true
+);
+define_func!(
+    fixed_neg,
+    |x: Mpz, bits: u64, signed: bool| fixed_width::neg(&x, bits, signed),
+    true
+);
+define_func!(
+    fixed_shl,
+    |x: Mpz, amount: u64, bits: u64, signed: bool| fixed_width::shl(&x, amount, bits, signed),
+    true
+);
+define_func!(
+    fixed_shr,
+    |x: Mpz, amount: u64, bits: u64, signed: bool| fixed_width::shr(&x, amount, bits, signed),
+    true
+);
+define_func!(
+    to_twos_complement,
+    |x: Mpz, bits: u64| fixed_width::to_twos_complement(&x, bits),
+    true
+);
+define_func!(
+    from_twos_complement,
+    |src: String| fixed_width::from_twos_complement(&src),
+    true
 );
 
-#[wasm_func]
-fn verify_mpz(arg: &[u8]) -> Vec<u8> {
-    ciborium::de::from_reader::<Mpz, &[u8]>(arg)
-        .is_ok()
-        .into_wasm_output()
-}
-
-define_func!(mpz_add, |nums: Vec<MpzExt>| nums.iter().sum::<MpzExt>());
-define_func!(mpz_sub, |x: MpzExt, y: MpzExt| x - y);
-define_func!(mpz_mul, |nums: Vec<MpzExt>| nums.iter().product::<MpzExt>());
-define_func!(mpz_div, |x: MpzExt, y: MpzExt| x / y);
-define_func!(mpz_neg, |x: MpzExt| -x);
-define_func!(mpz_pow, |x: MpzExt, y: u64| x.pow(y));
-define_func!(mpz_abs, |x: MpzExt| x.unsigned_abs());
-define_func!(mpz_sign, |x: MpzExt| x.sign());
-define_func!(mpz_cmp, |x: MpzExt, y: MpzExt| x.partial_cmp(&y));
-define_func!(mpz_fact, |n: u64| Mpn::factorial(n));
-define_func!(mpz_binom, |n: Mpz, k: Mpz| Mpz::binomial_coefficient(n, k));
-define_func!(mpz_gcd, |m: Mpz, n: Mpz| Mpn::gcd(
-    m.unsigned_abs(),
-    n.unsigned_abs()
-));
-define_func!(mpz_egcd, |m: Mpz, n: Mpz| Mpz::extended_gcd(m, n));
-
 // Multi-precision Rationals
 
 define_func!(
@@ -536,6 +2018,17 @@ define_func!(
     },
     true
 );
+// `parse_mpq` keeps the exact value written, so `0.1` is `1/10`; this variant instead returns the
+// exact value of the nearest `f64`, reproducing what IEEE 754 parsing does to the input
+define_func!(
+    parse_mpq_as_binary_float,
+    |src: String| {
+        let x = MpqExt::from_str(&sanitize_numeric_src!(src))
+            .map_err(|_| anyhow!("Invalid number format"))?;
+        MpqExt::try_from(x.to_f64_nearest())
+    },
+    true
+);
 define_func!(mpq_from_int, |n: i64| MpqExt::from(n));
 define_func!(mpq_from_float, |n: f64| MpqExt::try_from(n), true);
 define_func!(mpq_from_mpz, |n: MpzExt| MpqExt::from(n));
@@ -549,18 +2042,29 @@ define_func!(mpq_den_signed, |x: MpqExt| x.into_denominator_signed());
 
 #[wasm_func]
 fn verify_mpq(arg: &[u8]) -> Vec<u8> {
-    ciborium::de::from_reader::<MpqExt, &[u8]>(arg)
-        .is_ok()
-        .into_wasm_output()
+    cbor::decode::<MpqExt>(arg).is_ok().into_wasm_output()
 }
 
-define_func!(mpq_add, |nums: Vec<MpqExt>| nums.iter().sum::<MpqExt>());
+define_func!(mpq_add, |nums: Vec<MpqExt>| small_rational::sum(&nums));
 define_func!(mpq_sub, |x: MpqExt, y: MpqExt| x - y);
-define_func!(mpq_mul, |nums: Vec<MpqExt>| nums.iter().product::<MpqExt>());
+define_func!(mpq_mul, |nums: Vec<MpqExt>| small_rational::product(&nums));
 define_func!(mpq_div, |x: MpqExt, y: MpqExt| x / y);
 define_func!(mpq_neg, |x: MpqExt| -x);
-define_func!(mpq_pow, |x: MpqExt, y: i64| MpqExt::pow(x, y));
+define_func!(
+    mpq_pow,
+    |x: MpqExt, y: i64| {
+        // the numerator and the denominator are raised separately
+        if let MpqExt::Rational(q) = &x {
+            let log2_base = log2_natural(q.numerator_ref()).max(log2_natural(q.denominator_ref()));
+            check_power_size(log2_base, y.unsigned_abs())?;
+        }
+        anyhow::Ok(MpqExt::pow(x, y))
+    },
+    true
+);
 define_func!(mpq_abs, |x: MpqExt| x.abs());
+define_func!(mpq_gcd, |x: MpqExt, y: MpqExt| x.gcd(&y));
+define_func!(mpq_lcm, |x: MpqExt, y: MpqExt| x.lcm(&y));
 define_func!(mpq_sign, |x: MpqExt| x.sign());
 define_func!(mpq_sign_strict, |x: MpqExt| x.sign_strict());
 define_func!(mpq_repr, |x: MpqExt| x.to_string());
@@ -572,15 +2076,376 @@ define_func!(
     mpq_to_math,
     |x: MpqExt, options: FlagSet<FracLayoutOptions>| { x.to_math_strings(options) }
 );
-define_func!(mpq_cmp, |x: MpqExt, y: MpqExt| x.partial_cmp(&y));
+define_func!(mpq_to_unicode_fraction, |x: MpqExt,
+                                       options: FlagSet<
+    FracLayoutOptions,
+>| unicode_fraction_string(
+    &x, options
+));
+define_func!(mpq_cmp, |x: MpqExt, y: MpqExt| small_rational::partial_cmp(
+    &x, &y
+));
+define_func!(mpq_cmp_float, |x: MpqExt, y: f64| x.partial_cmp(&y));
 define_func!(mpq_cmp_strict, |x: MpqExt, y: MpqExt| x
     .partial_cmp_strict(&y));
+define_func!(mpq_eq, |x: MpqExt, y: MpqExt| x == y);
+// unlike `mpq_eq`, tells `-0` from `+0`
+define_func!(mpq_eq_strict, |x: MpqExt, y: MpqExt| x
+    .partial_cmp_strict(&y)
+    == Some(Ordering::Equal));
 define_func!(mpq_is_finite, |x: MpqExt| x.is_finite());
 define_func!(mpq_is_infinite, |x: MpqExt| x.is_infinite());
 define_func!(mpq_is_nan, |x: MpqExt| x.is_nan());
+define_func!(mpq_is_square, |x: MpqExt| match x {
+    MpqExt::Zero(_) => true,
+    // a reduced fraction is a square exactly when its numerator and denominator both are
+    MpqExt::Rational(q) => {
+        q > 0
+            && q.numerator_ref().checked_sqrt().is_some()
+            && q.denominator_ref().checked_sqrt().is_some()
+    }
+    MpqExt::Inf(_) | MpqExt::NaN => false,
+});
+define_func!(
+    mpq_simplify_sqrt,
+    |x: MpqExt| {
+        let x: Mpq = x.try_into()?;
+        // √(p/q) = √(pq) / q
+        let (p, q) = x.numerator_and_denominator_ref();
+        let (c, r) = primes::square_free_decomposition(&(p * q))?;
+        let r = Mpz::from(r);
+        let r = if x < 0 { -r } else { r };
+        anyhow::Ok((MpqExt::from(Mpq::from_naturals_ref(&c, q)), r))
+    },
+    true
+);
 define_func!(mpq_approx, |x: MpqExt, max_den: Mpn| x.approx(&max_den));
+define_func!(
+    mpq_sqrt_approx,
+    |x: MpqExt, max_den: Mpn| x.sqrt_approx(&max_den),
+    true
+);
+define_func!(
+    mpq_approx_best,
+    |x: MpqExt, eps: MpqExt| anyhow::Ok(MpqExt::from(farey::simplest_within(
+        &x.try_into()?,
+        &eps.try_into()?
+    )?)),
+    true
+);
+define_func!(
+    mpq_mediant,
+    |x: MpqExt, y: MpqExt| {
+        let (x, y): (Mpq, Mpq) = (x.try_into()?, y.try_into()?);
+        anyhow::Ok(MpqExt::from(farey::mediant(&x, &y)))
+    },
+    true
+);
+define_func!(
+    farey_sequence,
+    |n: u64| anyhow::Ok(
+        farey::sequence(n)?
+            .into_iter()
+            .map(MpqExt::from)
+            .collect::<Vec<_>>()
+    ),
+    true
+);
+define_func!(
+    mpq_farey_neighbors,
+    |x: MpqExt, n: Mpn| {
+        let (left, right) = farey::neighbors(&x.try_into()?, &n)?;
+        anyhow::Ok((left.map(MpqExt::from), right.map(MpqExt::from)))
+    },
+    true
+);
+/// Upper bound on the length of the paths and move lists taken and returned by the Stern–Brocot
+/// functions.
+const MAX_STERN_BROCOT_MOVES: usize = 1000;
+
+define_func!(
+    mpq_stern_brocot_path,
+    |x: MpqExt, max_len: u64| {
+        if max_len > MAX_STERN_BROCOT_MOVES as u64 {
+            return Err(anyhow!(
+                "paths longer than {MAX_STERN_BROCOT_MOVES} moves are not supported"
+            ));
+        }
+        farey::stern_brocot_path(&x.try_into()?, max_len as usize)
+    },
+    true
+);
+define_func!(
+    mpq_from_stern_brocot_path,
+    |path: String| {
+        if path.len() > MAX_STERN_BROCOT_MOVES {
+            return Err(anyhow!(
+                "a path of {} moves is longer than {MAX_STERN_BROCOT_MOVES}",
+                path.len()
+            ));
+        }
+        anyhow::Ok(MpqExt::from(farey::from_stern_brocot_path(&path)?))
+    },
+    true
+);
+
+// moves as booleans with `true` for a step to the right, starting from the root `1/1`
+define_func!(
+    mpq_stern_brocot_encode,
+    |x: MpqExt| {
+        let (path, truncated) = farey::stern_brocot_path(&x.try_into()?, MAX_STERN_BROCOT_MOVES)?;
+        if truncated {
+            return Err(anyhow!(
+                "the path is longer than {MAX_STERN_BROCOT_MOVES} moves"
+            ));
+        }
+        anyhow::Ok(path.chars().map(|c| c == 'R').collect::<Vec<_>>())
+    },
+    true
+);
+define_func!(
+    mpq_stern_brocot_decode,
+    |moves: Vec<bool>| {
+        if moves.len() > MAX_STERN_BROCOT_MOVES {
+            return Err(anyhow!(
+                "a path of {} moves is longer than {MAX_STERN_BROCOT_MOVES}",
+                moves.len()
+            ));
+        }
+        anyhow::Ok(MpqExt::from(farey::from_stern_brocot_moves(moves)))
+    },
+    true
+);
+define_func!(
+    mpq_continued_fraction,
+    |x: MpqExt| {
+        let (a0, rest) = x
+            .continued_fraction()
+            .ok_or_else(|| anyhow!("only finite numbers have a continued fraction"))?;
+        anyhow::Ok(
+            std::iter::once(a0)
+                .chain(rest.into_iter().map(Mpz::from))
+                .collect::<Vec<_>>(),
+        )
+    },
+    true
+);
+/// Upper bound on the number of fractional digits produced by `mpq_to_radix_string` and
+/// `mpq_to_decimal`.
+const MAX_RADIX_DIGITS: u64 = 1 << 20;
+
+fn check_radix_digits(max_digits: u64) -> Result<usize, anyhow::Error> {
+    if max_digits > MAX_RADIX_DIGITS {
+        bail!("at most {MAX_RADIX_DIGITS} digits are allowed, but got {max_digits}");
+    }
+    Ok(max_digits as usize)
+}
+
+define_func!(
+    mpq_to_radix_string,
+    |x: MpqExt, base: u32, max_digits: u64| {
+        x.to_radix_expansion(base, check_radix_digits(max_digits)?)
+    },
+    true
+);
+define_func!(
+    mpq_decimal_preperiod,
+    |x: MpqExt| x.decimal_preperiod(),
+    true
+);
+define_func!(mpq_decimal_period, |x: MpqExt| x.decimal_period(), true);
+define_func!(
+    mpq_to_decimal,
+    |x: MpqExt, digits: u64| {
+        // `3.25`, `0.[142857]` for a period found within `digits` fractional digits, and
+        // `3.14159…` when the expansion was cut off; `digits` counts the places after the point
+        // rather than significant figures, so `1/7000` to 5 digits is `0.00014…`
+        let expansion = x.to_radix_expansion(10, check_radix_digits(digits)?)?;
+        let mut out = String::new();
+        if expansion.negative {
+            out.push('\u{2212}');
+        }
+        out += &expansion.int_digits;
+        if !(expansion.frac_digits.is_empty() && expansion.repeating_digits.is_empty()) {
+            out.push('.');
+            out += &expansion.frac_digits;
+        }
+        if !expansion.repeating_digits.is_empty() {
+            out += &format!("[{}]", expansion.repeating_digits);
+        }
+        if expansion.truncated {
+            out.push('\u{2026}');
+        }
+        anyhow::Ok(out)
+    },
+    true
+);
+define_func!(
+    mpq_to_continued_fraction_string,
+    |x: MpqExt| {
+        let (a0, rest) = x
+            .continued_fraction()
+            .ok_or_else(|| anyhow!("only finite numbers have a continued fraction"))?;
+        let mut out = format!("[{}", a0.to_string().replace('-', "\u{2212}"));
+        if !rest.is_empty() {
+            let rest: Vec<String> = rest.iter().map(Mpn::to_string).collect();
+            out += "; ";
+            out += &rest.join(", ");
+        }
+        out.push(']');
+        anyhow::Ok(out)
+    },
+    true
+);
 define_func!(mpq_floor, |x: MpqExt| x.floor());
 define_func!(mpq_ceil, |x: MpqExt| x.ceiling());
+// `⌊x / y⌋` and its analogues, with the special values of `x / y` carried over
+define_func!(mpq_floor_div, |x: MpqExt, y: MpqExt| (x / y).floor_to_mpz());
+define_func!(mpq_ceil_div, |x: MpqExt, y: MpqExt| (x / y).ceil_to_mpz());
+define_func!(mpq_round_div, |x: MpqExt, y: MpqExt| (x / y).round_to_mpz());
+define_func!(mpq_eval_poly, |coeffs: Vec<MpqExt>, x: MpqExt| {
+    // Horner's rule, coefficients from the highest degree down; starting from the leading
+    // coefficient rather than zero keeps `x = ±inf` from producing `0 · inf = NaN`
+    let mut coeffs = coeffs.into_iter();
+    match coeffs.next() {
+        Some(leading) => coeffs.fold(leading, |acc, c| acc * x.clone() + c),
+        None => MpqExt::ZERO,
+    }
+});
+define_func!(
+    mpq_binomial_pmf,
+    |n: u64, k: u64, p: MpqExt| {
+        let p: Mpq = p.try_into()?;
+        anyhow::Ok(MpqExt::from(probability::binomial_pmf(n, k, &p)?))
+    },
+    true
+);
+define_func!(
+    mpq_binomial_cdf,
+    |n: u64, k: u64, p: MpqExt| {
+        let p: Mpq = p.try_into()?;
+        anyhow::Ok(MpqExt::from(probability::binomial_cdf(n, k, &p)?))
+    },
+    true
+);
+define_func!(
+    mpq_hypergeom_pmf,
+    |big_n: u64, big_k: u64, n: u64, k: u64| {
+        probability::hypergeometric_pmf(big_n, big_k, n, k).map(MpqExt::from)
+    },
+    true
+);
+define_func!(
+    mpq_hypergeom_cdf,
+    |big_n: u64, big_k: u64, n: u64, k: u64| {
+        probability::hypergeometric_cdf(big_n, big_k, n, k).map(MpqExt::from)
+    },
+    true
+);
+
+// Gaussian Rationals
+
+define_func!(
+    parse_gaussian_rational,
+    |src: String| GaussianRational::from_str(&sanitize_numeric_src!(src)),
+    true,
+);
+define_func!(gaussian_rational_from_parts, |re: MpqExt, im: MpqExt| {
+    GaussianRational::new(re, im)
+});
+define_func!(
+    gaussian_rational_add,
+    |x: GaussianRational, y: GaussianRational| x + y
+);
+define_func!(
+    gaussian_rational_sub,
+    |x: GaussianRational, y: GaussianRational| x - y
+);
+define_func!(
+    gaussian_rational_mul,
+    |x: GaussianRational, y: GaussianRational| x * y
+);
+define_func!(gaussian_rational_neg, |x: GaussianRational| -x);
+define_func!(gaussian_rational_conj, |x: GaussianRational| x.conj());
+define_func!(gaussian_rational_repr, |x: GaussianRational| x.to_string());
+define_func!(gaussian_rational_to_str, |x: GaussianRational,
+                                        options: FlagSet<
+    FracLayoutOptions,
+>| x.to_layout_string(options));
+
+// Gaussian Integers
+
+define_func!(gaussint_add, |x: GaussianInteger, y: GaussianInteger| x + y);
+define_func!(gaussint_sub, |x: GaussianInteger, y: GaussianInteger| x - y);
+define_func!(gaussint_mul, |x: GaussianInteger, y: GaussianInteger| x * y);
+define_func!(
+    gaussint_div,
+    |x: GaussianInteger, y: GaussianInteger| {
+        if y.is_zero() {
+            Err(anyhow!("division by zero"))
+        } else {
+            Ok(x / y)
+        }
+    },
+    true,
+);
+define_func!(
+    gaussint_divmod,
+    |x: GaussianInteger, y: GaussianInteger| {
+        if y.is_zero() {
+            Err(anyhow!("division by zero"))
+        } else {
+            Ok(x.div_rem(&y))
+        }
+    },
+    true,
+);
+define_func!(gaussint_gcd, |x: GaussianInteger, y: GaussianInteger| x
+    .gcd(y));
+define_func!(gaussint_norm, |x: GaussianInteger| x.norm());
+define_func!(gaussint_conj, |x: GaussianInteger| x.conj());
+define_func!(gaussint_is_unit, |x: GaussianInteger| x.is_unit());
+define_func!(gaussint_is_prime, |x: GaussianInteger| x
+    .is_gaussian_prime());
+define_func!(gaussint_factor, |x: GaussianInteger| x.factor(), true,);
+
+// Plus-minus Values
+
+define_func!(pm_add, |x: PmValue, y: PmValue| x + y);
+define_func!(pm_sub, |x: PmValue, y: PmValue| x - y);
+define_func!(pm_neg, |x: PmValue| -x);
+define_func!(pm_scale, |x: PmValue, s: MpqExt| x * s);
+define_func!(pm_split, |x: PmValue| x.split());
+define_func!(
+    pm_to_math_strings,
+    |x: PmValue, options: FlagSet<FracLayoutOptions>| PmMathStrings {
+        delta: (!x.is_exact()).then(|| x
+            .delta
+            .to_math_strings(options - FracLayoutOptions::PlusSign)),
+        center: x.center.to_math_strings(options),
+    }
+);
+
+// Quadratic Extensions
+
+define_func!(
+    quadext_from_parts,
+    |a: MpqExt, b: MpqExt, d: Mpn| QuadExt::new(a, b, d),
+    true
+);
+define_func!(quadext_add, |x: QuadExt, y: QuadExt| x.try_add(&y), true);
+define_func!(quadext_sub, |x: QuadExt, y: QuadExt| x.try_sub(&y), true);
+define_func!(quadext_mul, |x: QuadExt, y: QuadExt| x.try_mul(&y), true);
+define_func!(quadext_div, |x: QuadExt, y: QuadExt| x.try_div(&y), true);
+define_func!(quadext_neg, |x: QuadExt| -x);
+define_func!(quadext_conj, |x: QuadExt| x.conj());
+define_func!(quadext_norm, |x: QuadExt| MpqExt::from(x.norm()));
+define_func!(quadext_cmp, |x: QuadExt, y: QuadExt| x.try_cmp(&y), true);
+define_func!(quadext_to_float, |x: QuadExt| x.to_f64());
+define_func!(
+    quadext_to_math,
+    |x: QuadExt, options: FlagSet<FracLayoutOptions>| quad_ext_math_strings(&x, options)
+);
 
 flags! {
     pub enum IntLayoutOptions: u8 {
@@ -743,6 +2608,112 @@ impl ToLayoutString for MpqExt {
     }
 }
 
+impl ToLayoutString for GaussianRational {
+    type Options = FlagSet<FracLayoutOptions>;
+
+    fn to_layout_string(&self, options: Self::Options) -> String {
+        use FracLayoutOptions::*;
+
+        let hyphen_minus = options.contains(HyphenMinus);
+
+        if self.im.is_zero() {
+            return self.re.to_layout_string(options);
+        }
+        let mut out = String::with_capacity(20);
+        if !self.re.is_zero() {
+            out += &self.re.to_layout_string(options);
+            out.push(if self.im.sign().is_lt() {
+                minus_sign!(hyphen_minus)
+            } else {
+                '+'
+            });
+        } else if self.im.sign().is_lt() {
+            out.push(minus_sign!(hyphen_minus));
+        } else if options.contains(PlusSign) {
+            out.push('+');
+        }
+        // the sign has been written already, so only the magnitude is needed
+        let coef = self.im.clone().abs();
+        if coef != MpqExt::ONE {
+            out += &coef.to_layout_string(options - PlusSign);
+        }
+        out.push('i');
+        out
+    }
+}
+
+/// The precomposed vulgar fractions of Unicode. U+2189 `↉` (0/3) is left out, as zero never
+/// has a denominator other than 1.
+const VULGAR_FRACTIONS: [(u32, u32, char); 18] = [
+    (1, 2, '\u{BD}'),
+    (1, 3, '\u{2153}'),
+    (2, 3, '\u{2154}'),
+    (1, 4, '\u{BC}'),
+    (3, 4, '\u{BE}'),
+    (1, 5, '\u{2155}'),
+    (2, 5, '\u{2156}'),
+    (3, 5, '\u{2157}'),
+    (4, 5, '\u{2158}'),
+    (1, 6, '\u{2159}'),
+    (5, 6, '\u{215A}'),
+    (1, 7, '\u{2150}'),
+    (1, 8, '\u{215B}'),
+    (3, 8, '\u{215C}'),
+    (5, 8, '\u{215D}'),
+    (7, 8, '\u{215E}'),
+    (1, 9, '\u{2151}'),
+    (1, 10, '\u{2152}'),
+];
+
+const SUPERSCRIPT_DIGITS: [char; 10] = [
+    '\u{2070}', '\u{B9}', '\u{B2}', '\u{B3}', '\u{2074}', '\u{2075}', '\u{2076}', '\u{2077}',
+    '\u{2078}', '\u{2079}',
+];
+const SUBSCRIPT_DIGITS: [char; 10] = [
+    '\u{2080}', '\u{2081}', '\u{2082}', '\u{2083}', '\u{2084}', '\u{2085}', '\u{2086}', '\u{2087}',
+    '\u{2088}', '\u{2089}',
+];
+
+fn to_script_digits(n: &Mpn, script: &[char; 10]) -> String {
+    n.to_string()
+        .bytes()
+        .map(|d| script[(d - b'0') as usize])
+        .collect()
+}
+
+/// `x` as a single vulgar fraction character such as `½` if Unicode has one, and otherwise
+/// composed as `⁷⁄₈` from superscript digits, U+2044 FRACTION SLASH and subscript digits.
+/// Integers are left as they are unless `DenomOne` is set, and zero, the infinities and `NaN`
+/// fall back to [`ToLayoutString`]. The sign follows the same options as there.
+fn unicode_fraction_string(x: &MpqExt, options: FlagSet<FracLayoutOptions>) -> String {
+    use FracLayoutOptions::*;
+
+    let MpqExt::Rational(q) = x else {
+        return x.to_layout_string(options);
+    };
+    let (num, den) = q.numerator_and_denominator_ref();
+    if *den == 1u32 && !options.contains(DenomOne) {
+        return x.to_layout_string(options);
+    }
+    let mut out = String::new();
+    if *q < 0u32 {
+        out.push(minus_sign!(options.contains(HyphenMinus)));
+    } else if options.contains(PlusSign) {
+        out.push('+');
+    }
+    let vulgar = VULGAR_FRACTIONS
+        .iter()
+        .find(|&&(n, d, _)| *num == n && *den == d);
+    if let Some(&(_, _, c)) = vulgar {
+        out.push(c);
+    } else {
+        out += &to_script_digits(num, &SUPERSCRIPT_DIGITS);
+        out.push('\u{2044}');
+        out += &to_script_digits(den, &SUBSCRIPT_DIGITS);
+    }
+    out
+}
+
 trait ToMathStrings {
     type Options;
     fn to_math_strings(&self, options: Self::Options) -> ToMathStringResult;
@@ -756,6 +2727,61 @@ struct ToMathStringResult {
 }
 impl_wasm_conversion_serialize!(ToMathStringResult);
 
+/// The parts of `center ± delta`, without a delta if both branches coincide.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PmMathStrings {
+    center: ToMathStringResult,
+    delta: Option<ToMathStringResult>,
+}
+impl_wasm_conversion_serialize!(PmMathStrings);
+
+/// The `±c√d` term of [`QuadMathStrings`], without a coefficient if it is 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RadicalStrings {
+    sign: Option<char>,
+    coef: Option<String>,
+    radicand: String,
+}
+
+/// The parts of `(p + q√d) / r` over the least common denominator, leaving out `p` if it is zero
+/// while `q` is not, the radical if `q` is zero and the denominator if it is 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QuadMathStrings {
+    rational: Option<ToMathStringResult>,
+    radical: Option<RadicalStrings>,
+    den: Option<String>,
+}
+impl_wasm_conversion_serialize!(QuadMathStrings);
+
+fn quad_ext_math_strings(x: &QuadExt, options: FlagSet<FracLayoutOptions>) -> QuadMathStrings {
+    let (p, q, r) = x.over_common_denominator();
+    let rational = (p != 0u32 || q == 0u32)
+        .then(|| MpqExt::from(p).to_math_strings(options - FracLayoutOptions::DenomOne));
+    let radical = (q != 0u32).then(|| {
+        let sign = if q < 0u32 {
+            Some(minus_sign!(
+                options.contains(FracLayoutOptions::HyphenMinus)
+            ))
+        } else if rational.is_some() || options.contains(FracLayoutOptions::PlusSign) {
+            Some('+')
+        } else {
+            None
+        };
+        let coef = q.unsigned_abs();
+        RadicalStrings {
+            sign,
+            coef: (coef != 1u32).then(|| coef.to_string()),
+            radicand: x.d().to_string(),
+        }
+    });
+    let den = (r != 1u32 || options.contains(FracLayoutOptions::DenomOne)).then(|| r.to_string());
+    QuadMathStrings {
+        rational,
+        radical,
+        den,
+    }
+}
+
 impl ToMathStrings for MpqExt {
     type Options = FlagSet<FracLayoutOptions>;
 
@@ -767,6 +2793,7 @@ impl ToMathStrings for MpqExt {
         let signed_zero = options.contains(SignedZero);
         let signed_inf = options.contains(SignedInf);
         let denom_one = options.contains(DenomOne);
+        let hyphen_minus = options.contains(HyphenMinus);
 
         match self {
             NaN => ToMathStringResult {
@@ -779,7 +2806,7 @@ impl ToMathStrings for MpqExt {
                     if s {
                         if plus_sign { Some('+') } else { None }
                     } else {
-                        Some('\u{2212}')
+                        Some(minus_sign!(hyphen_minus))
                     }
                 } else {
                     None
@@ -803,7 +2830,7 @@ impl ToMathStrings for MpqExt {
                         None
                     }
                 } else {
-                    Some('\u{2212}')
+                    Some(minus_sign!(hyphen_minus))
                 };
                 ToMathStringResult {
                     sign,
@@ -814,7 +2841,7 @@ impl ToMathStrings for MpqExt {
             Rational(q) => {
                 use Ordering::*;
                 let sign = match q.sign() {
-                    Less => Some('\u{2212}'),
+                    Less => Some(minus_sign!(hyphen_minus)),
                     Greater => {
                         if plus_sign {
                             Some('+')
@@ -824,11 +2851,11 @@ impl ToMathStrings for MpqExt {
                     }
                     Equal => unreachable!(),
                 };
-                let numerator = q.numerator_ref().to_string();
+                let numerator = small_rational::to_decimal(q.numerator_ref());
                 let denominator = if !denom_one & (q.denominator_ref() == &1) {
                     None
                 } else {
-                    Some(q.denominator_ref().to_string())
+                    Some(small_rational::to_decimal(q.denominator_ref()))
                 };
                 ToMathStringResult {
                     sign,
@@ -839,3 +2866,503 @@ impl ToMathStrings for MpqExt {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(name: &str) -> Option<(usize, usize)> {
+        PLUGIN_FUNCTIONS
+            .iter()
+            .find(|&&(listed, _, _)| listed == name)
+            .map(|&(_, n_args, n_required)| (n_args, n_required))
+    }
+
+    #[test]
+    fn plugin_functions_are_unique() {
+        let mut names: Vec<_> = PLUGIN_FUNCTIONS.iter().map(|&(name, _, _)| name).collect();
+        names.sort_unstable();
+        let len = names.len();
+        names.dedup();
+        assert_eq!(names.len(), len);
+        for &(name, n_args, n_required) in PLUGIN_FUNCTIONS {
+            assert!(n_required <= n_args, "{name}");
+        }
+    }
+
+    #[test]
+    fn plugin_functions_cover_helper_macros() {
+        assert_eq!(listed("plugin_info"), Some((0, 0)));
+        assert_eq!(listed("cbrt"), Some((1, 1)));
+        assert_eq!(listed("sin_complex"), Some((1, 1)));
+        assert_eq!(listed("cot"), Some((1, 1)));
+        assert_eq!(listed("cot_complex"), Some((1, 1)));
+        assert_eq!(listed("dual_sin"), Some((2, 2)));
+        assert_eq!(listed("hurwitz_zeta"), Some((3, 2)));
+        assert_eq!(listed("zeta"), Some((2, 1)));
+        assert_eq!(listed("sin"), None);
+    }
+
+    #[test]
+    fn is_plugin_function_checks_arguments() {
+        assert!(is_plugin_function("mpz_pow", 2));
+        assert!(!is_plugin_function("mpz_pow", 1));
+        assert!(!is_plugin_function("mpz_po", 2));
+        assert!(!is_plugin_function("mpz_pow_", 2));
+    }
+
+    #[test]
+    fn packed_floats() {
+        let values = [1.5, -0.0, f64::INFINITY, f64::NAN];
+        let bytes: Vec<u8> = values.iter().flat_map(|x| x.to_le_bytes()).collect();
+        let packed = Packed::<f64>::from_wasm_input(&bytes).unwrap();
+        assert_eq!(packed.0[..2], values[..2]);
+        assert!(packed.0[1].is_sign_negative() && packed.0[3].is_nan());
+        assert_eq!(packed.into_wasm_output(), bytes);
+        assert!(Packed::<f64>::from_wasm_input(&[]).unwrap().0.is_empty());
+        assert!(Packed::<f64>::from_wasm_input(&bytes[..12]).is_err());
+        // nested in CBOR as a byte string
+        let nested = (Packed(vec![1.5]), Packed::<f64>(vec![])).into_wasm_output();
+        let decoded: Vec<ciborium::Value> = cbor::decode(&nested).unwrap();
+        assert_eq!(decoded[0].as_bytes().unwrap()[..], 1.5f64.to_le_bytes());
+        assert!(decoded[1].as_bytes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn complex_equality_returns_bool() {
+        let z = |re: f64, im: f64| c64::new(re, im).into_wasm_output();
+        assert_eq!(complex_eq(&z(1.0, -2.0), &z(1.0, -2.0)).unwrap(), [1]);
+        assert_eq!(complex_eq(&z(1.0, -2.0), &z(1.0, 2.0)).unwrap(), [0]);
+        assert_eq!(complex_ne(&z(1.0, -2.0), &z(1.0, 2.0)).unwrap(), [1]);
+        // IEEE equality: -0 equals 0, NaN equals nothing
+        assert_eq!(complex_eq(&z(0.0, 0.0), &z(-0.0, 0.0)).unwrap(), [1]);
+        assert_eq!(
+            complex_eq(&z(f64::NAN, 0.0), &z(f64::NAN, 0.0)).unwrap(),
+            [0]
+        );
+        assert_eq!(
+            complex_ne(&z(f64::NAN, 0.0), &z(f64::NAN, 0.0)).unwrap(),
+            [1]
+        );
+    }
+
+    #[test]
+    fn integer_division_sign_conventions() {
+        type Division = fn(&[u8], &[u8]) -> Result<Vec<u8>, anyhow::Error>;
+        let call = |f: Division, x: i64, y: i64| {
+            let (x, y) = (MpzExt::from(Mpz::from(x)), MpzExt::from(Mpz::from(y)));
+            f(&x.into_wasm_output(), &y.into_wasm_output())
+                .map(|output| MpzExt::from_wasm_input(&output).unwrap())
+        };
+        // the results of each function for 7 / 2, -7 / 2, 7 / -2, -7 / -2 and 6 / -3
+        let table: [(Division, [i64; 5]); 5] = [
+            (mpz_div, [3, -3, -3, 3, -2]),
+            (mpz_div_floor, [3, -4, -4, 3, -2]),
+            (mpz_mod_floor, [1, 1, -1, -1, 0]),
+            (mpz_div_euclid, [3, -4, -3, 4, -2]),
+            (mpz_rem_euclid, [1, 1, 1, 1, 0]),
+        ];
+        let operands = [(7, 2), (-7, 2), (7, -2), (-7, -2), (6, -3)];
+        for (i, (f, expected)) in table.into_iter().enumerate() {
+            for ((x, y), expected) in operands.into_iter().zip(expected) {
+                let expected = MpzExt::from(Mpz::from(expected));
+                assert_eq!(call(f, x, y).unwrap(), expected, "function {i}: {x} / {y}");
+            }
+        }
+        for f in [mpz_div_floor, mpz_mod_floor, mpz_div_euclid, mpz_rem_euclid] {
+            assert_eq!(call(f, 1, 0).unwrap_err().to_string(), "division by zero");
+            let inf = MpzExt::Inf(true).into_wasm_output();
+            assert!(f(&inf, &MpzExt::from(Mpz::ONE).into_wasm_output()).is_err());
+        }
+    }
+
+    #[test]
+    fn shift_left_is_bounded() {
+        let shl = |x: i64, n: u64| {
+            mpz_shl(&Mpz::from(x).into_wasm_output(), &n.into_wasm_output())
+                .map(|output| Mpz::from_wasm_input(&output).unwrap())
+        };
+        assert_eq!(shl(-3, 4).unwrap(), -48);
+        assert_eq!(shl(0, u64::MAX).unwrap(), 0);
+        assert_eq!(shl(1, 1_000_000).unwrap(), Mpz::ONE << 1_000_000u64);
+        assert!(shl(1, 1 << 40).is_err());
+        assert!(shl(1, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn divisor_sums_are_bounded() {
+        let sigma = |n: u64, k: u64| {
+            mpz_sigma(&Mpz::from(n).into_wasm_output(), &k.into_wasm_output())
+                .map(|output| Mpn::from_wasm_input(&output).unwrap())
+        };
+        assert_eq!(sigma(6, 1).unwrap(), 12u32);
+        assert_eq!(sigma(1, u64::MAX).unwrap(), 1u32);
+        assert!(sigma(2, 20_000_000).is_err());
+        assert!(sigma(0, 1).is_err());
+    }
+
+    #[test]
+    fn fraction_approx_limits_the_denominator() {
+        let approx = |x: q64, max_den: u64| {
+            fraction_approx(&x.into_wasm_output(), &max_den.into_wasm_output())
+                .map(|output| q64::from_wasm_input(&output).unwrap())
+        };
+        let pi = q64::from(std::f64::consts::PI);
+        assert_eq!(approx(pi, 7).unwrap(), q64::new(22u64, 7u64));
+        assert_eq!(approx(pi, 200).unwrap(), q64::new(355u64, 113u64));
+        assert_eq!(approx(q64::infinity(), 5).unwrap(), q64::infinity());
+        let error = approx(pi, 0).unwrap_err();
+        assert_eq!(error.to_string(), "max_den must be positive");
+    }
+
+    #[test]
+    fn fraction_powers_promote_and_are_bounded() {
+        let pow = |x: q64, exp: i64| {
+            fraction_pow(&x.into_wasm_output(), &exp.into_wasm_output())
+                .map(|output| cbor::decode::<ciborium::Value>(&output).unwrap())
+        };
+        let half = q64::new(1u64, 2u64);
+        let small = pow(half, -3).unwrap();
+        assert_eq!(
+            small,
+            cbor::decode(&FractionResult::from(MpqExt::from(8u32)).into_wasm_output()).unwrap()
+        );
+        // 2¹⁰⁰ does not fit in 64 bits, so the result is promoted
+        let expected = FractionResult::Promoted(MpqExt::pow(fraction_into_mpq(half), 100i64));
+        assert_eq!(
+            pow(half, 100).unwrap(),
+            cbor::decode(&expected.into_wasm_output()).unwrap()
+        );
+        assert!(pow(half, 20_000_000).is_err());
+        assert!(pow(half, i64::MIN).is_err());
+        assert!(pow(q64::new(1u64, 1u64), i64::MAX).is_ok());
+    }
+
+    #[test]
+    fn huge_powers_fail_gracefully() {
+        let exp = 1_000_000_000u64;
+        let error = mpz_pow(
+            &MpzExt::from(Mpz::from(3)).into_wasm_output(),
+            &exp.into_wasm_output(),
+        )
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("more than the limit of 1e7"),
+            "{error}"
+        );
+        let x = MpqExt::from(Mpq::from_signeds(2, 3)).into_wasm_output();
+        assert!(mpq_pow(&x, &(exp as i64).into_wasm_output()).is_err());
+        assert!(mpq_pow(&x, &(-(exp as i64)).into_wasm_output()).is_err());
+        // bases whose powers stay small are not limited
+        let one = MpzExt::from(Mpz::from(-1)).into_wasm_output();
+        let output = mpz_pow(&one, &exp.into_wasm_output()).unwrap();
+        assert_eq!(
+            MpzExt::from_wasm_input(&output).unwrap(),
+            MpzExt::from(Mpz::ONE)
+        );
+    }
+
+    #[test]
+    fn powers_with_big_exponents() {
+        let pow = |base: i64, exp: Mpn| {
+            mpz_pow_mpn(&Mpz::from(base).into_wasm_output(), &exp.into_wasm_output())
+                .map(|output| Mpz::from_wasm_input(&output).unwrap())
+        };
+        let beyond_u64 = Mpn::from(u64::MAX) + Mpn::ONE;
+        assert_eq!(pow(3, Mpn::from(5u32)).unwrap(), 243);
+        assert_eq!(pow(-1, beyond_u64.clone()).unwrap(), Mpz::ONE);
+        assert_eq!(pow(-1, beyond_u64.clone() + Mpn::ONE).unwrap(), -1);
+        assert_eq!(pow(1, beyond_u64.clone()).unwrap(), Mpz::ONE);
+        assert_eq!(pow(0, beyond_u64.clone()).unwrap(), 0);
+        assert_eq!(pow(7, Mpn::ZERO).unwrap(), Mpz::ONE);
+        // the same limit applies whether the exponent fits in a u64 or not
+        let error = pow(3, Mpn::from(1_000_000_000u64)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the power would take more than the limit of 1e7 bits"
+        );
+        assert!(pow(-2, beyond_u64).is_err());
+        assert_eq!(
+            MpzExt::from(Mpz::from(2)).pow(MpnExt::from(Mpn::from(20_000_000u64))),
+            MpzExt::Inf(true)
+        );
+        assert_eq!(
+            MpzExt::from(Mpz::from(-2)).pow(MpnExt::from(Mpn::from(20_000_001u64))),
+            MpzExt::Inf(false)
+        );
+    }
+
+    #[test]
+    fn subfactorials_are_bounded() {
+        let subfactorial = |n: u64| {
+            super::subfactorial(&n.into_wasm_output())
+                .map(|output| Mpn::from_wasm_input(&output).unwrap())
+        };
+        let first: Vec<Mpn> = (0..8).map(|n| subfactorial(n).unwrap()).collect();
+        assert_eq!(first, [1u32, 0, 1, 2, 9, 44, 265, 1854].map(Mpn::from));
+        assert!(subfactorial(1000).is_ok());
+        let error = subfactorial(u64::MAX).unwrap_err();
+        assert!(
+            error.to_string().contains("more than the limit of 1e7"),
+            "{error}"
+        );
+        assert!(subfactorial(1_000_000).is_err());
+    }
+
+    #[test]
+    fn oversized_integers_are_rejected() {
+        // big integers travel as hex strings, here one of more digits than the limit
+        let digits = "f".repeat(cbor::DEFAULT_MAX_DIGITS as usize + 1);
+        let mut input = Vec::new();
+        ciborium::ser::into_writer(&format!("0x{digits}"), &mut input).unwrap();
+        let error = Mpz::from_wasm_input(&input).unwrap_err();
+        assert!(error.to_string().contains("exceeds the limit"), "{error}");
+        let mut input = Vec::new();
+        ciborium::ser::into_writer(&"-0xff", &mut input).unwrap();
+        assert_eq!(Mpz::from_wasm_input(&input).unwrap(), -255);
+    }
+
+    #[test]
+    fn stern_brocot_paths_are_bounded() {
+        let x = MpqExt::from(Mpq::from(u64::MAX)).into_wasm_output();
+        assert!(mpq_stern_brocot_path(&x, &1000u64.into_wasm_output()).is_ok());
+        assert!(mpq_stern_brocot_path(&x, &1001u64.into_wasm_output()).is_err());
+        assert!(mpq_stern_brocot_path(&x, &u64::MAX.into_wasm_output()).is_err());
+        let from_path = |path: String| {
+            mpq_from_stern_brocot_path(&path.into_wasm_output())
+                .map(|output| MpqExt::from_wasm_input(&output).unwrap())
+        };
+        assert_eq!(
+            from_path("LLRR".to_string()).unwrap(),
+            MpqExt::from(Mpq::from_signeds(3, 7))
+        );
+        assert!(from_path("R".repeat(1001)).is_err());
+    }
+
+    #[test]
+    fn pm_math_strings_collapse_exact_values() {
+        let strings = |center: MpqExt, delta: MpqExt, options: FlagSet<FracLayoutOptions>| {
+            let x = PmValue::new(center, delta).into_wasm_output();
+            let output = pm_to_math_strings(&x, &options.into_wasm_output()).unwrap();
+            PmMathStrings::from_wasm_input(&output).unwrap()
+        };
+        let math = |sign: Option<char>, num: &str, den: Option<&str>| ToMathStringResult {
+            sign,
+            num: num.to_string(),
+            den: den.map(str::to_string),
+        };
+        let q = |n: i64, d: i64| MpqExt::from(Mpq::from_signeds(n, d));
+        let x = strings(q(-1, 2), q(-3, 4), FlagSet::default());
+        assert_eq!(x.center, math(Some('\u{2212}'), "1", Some("2")));
+        assert_eq!(x.delta, Some(math(None, "3", Some("4"))));
+        // the ± takes the place of a plus sign on the delta
+        let x = strings(q(1, 1), q(2, 1), FracLayoutOptions::PlusSign.into());
+        assert_eq!(x.center, math(Some('+'), "1", None));
+        assert_eq!(x.delta, Some(math(None, "2", None)));
+        let x = strings(q(5, 3), MpqExt::ZERO, FlagSet::default());
+        assert_eq!(x.center, math(None, "5", Some("3")));
+        assert_eq!(x.delta, None);
+        let x = strings(q(5, 3), MpqExt::Zero(false), FlagSet::default());
+        assert_eq!(x.delta, None);
+    }
+
+    #[test]
+    fn golden_ratio_squared_is_typeset_exactly() {
+        let q = |n: i64, d: i64| MpqExt::from(Mpq::from_signeds(n, d));
+        let phi = QuadExt::new(q(1, 2), q(1, 2), Mpn::from(5u32))
+            .unwrap()
+            .into_wasm_output();
+        let square = quadext_mul(&phi, &phi).unwrap();
+        let options = FlagSet::<FracLayoutOptions>::default().into_wasm_output();
+        let output = quadext_to_math(&square, &options).unwrap();
+        let strings = QuadMathStrings::from_wasm_input(&output).unwrap();
+        let rational = strings.rational.unwrap();
+        assert_eq!((rational.sign, rational.num.as_str()), (None, "3"));
+        let radical = strings.radical.unwrap();
+        assert_eq!(radical.sign, Some('+'));
+        assert_eq!(radical.coef, None);
+        assert_eq!(radical.radicand, "5");
+        assert_eq!(strings.den.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn unicode_fractions() {
+        let unicode = |x: MpqExt, options: FlagSet<FracLayoutOptions>| {
+            let output =
+                mpq_to_unicode_fraction(&x.into_wasm_output(), &options.into_wasm_output())
+                    .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let q = |n: i64, d: i64| MpqExt::from(Mpq::from_signeds(n, d));
+        let none = FlagSet::<FracLayoutOptions>::default();
+        let precomposed = [
+            (1, 2, "½"),
+            (1, 3, "⅓"),
+            (2, 3, "⅔"),
+            (1, 4, "¼"),
+            (3, 4, "¾"),
+            (1, 5, "⅕"),
+            (2, 5, "⅖"),
+            (3, 5, "⅗"),
+            (4, 5, "⅘"),
+            (1, 6, "⅙"),
+            (5, 6, "⅚"),
+            (1, 7, "⅐"),
+            (1, 8, "⅛"),
+            (3, 8, "⅜"),
+            (5, 8, "⅝"),
+            (7, 8, "⅞"),
+            (1, 9, "⅑"),
+            (1, 10, "⅒"),
+        ];
+        assert_eq!(precomposed.len(), VULGAR_FRACTIONS.len());
+        for (n, d, expected) in precomposed {
+            assert_eq!(unicode(q(n, d), none), expected);
+            assert_eq!(unicode(q(-n, d), none), format!("\u{2212}{expected}"));
+        }
+        // 2/4 is reduced to ½ first
+        assert_eq!(unicode(q(2, 4), none), "½");
+        assert_eq!(unicode(q(22, 7), none), "²²⁄₇");
+        assert_eq!(unicode(q(-355, 113), none), "\u{2212}³⁵⁵⁄₁₁₃");
+        assert_eq!(
+            unicode(q(-355, 113), FracLayoutOptions::HyphenMinus.into()),
+            "-³⁵⁵⁄₁₁₃"
+        );
+        assert_eq!(unicode(q(1234567890, 7), none), "¹²³⁴⁵⁶⁷⁸⁹⁰⁄₇");
+        assert_eq!(unicode(q(1, 1234567890), none), "¹⁄₁₂₃₄₅₆₇₈₉₀");
+        assert_eq!(unicode(q(7, 2), FracLayoutOptions::PlusSign.into()), "+⁷⁄₂");
+        // integers pass through unless a denominator of 1 is asked for
+        assert_eq!(unicode(q(-3, 1), none), "\u{2212}3");
+        assert_eq!(unicode(q(3, 1), FracLayoutOptions::DenomOne.into()), "³⁄₁");
+        for x in [MpqExt::ZERO, MpqExt::Inf(false), MpqExt::NaN] {
+            assert_eq!(unicode(x.clone(), none), x.to_layout_string(none));
+        }
+    }
+
+    #[test]
+    fn decimal_strings() {
+        let decimal = |x: MpqExt, digits: u64| {
+            mpq_to_decimal(&x.into_wasm_output(), &digits.into_wasm_output())
+                .map(|output| String::from_utf8(output).unwrap())
+        };
+        let q = |n: i64, d: i64| MpqExt::from(Mpq::from_signeds(n, d));
+        assert_eq!(decimal(q(13, 4), 5).unwrap(), "3.25");
+        assert_eq!(decimal(q(1, 7), 10).unwrap(), "0.[142857]");
+        assert_eq!(decimal(q(-1, 7), 3).unwrap(), "\u{2212}0.142\u{2026}");
+        // the digits are counted after the point, not from the first nonzero one
+        assert_eq!(decimal(q(1, 7000), 5).unwrap(), "0.00014\u{2026}");
+        assert_eq!(decimal(q(7, 1), 0).unwrap(), "7");
+        assert!(decimal(q(1, 3), MAX_RADIX_DIGITS).is_ok());
+        let error = decimal(q(1, 3), MAX_RADIX_DIGITS + 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "at most {MAX_RADIX_DIGITS} digits are allowed, but got {}",
+                MAX_RADIX_DIGITS + 1
+            )
+        );
+        assert!(decimal(q(1, 3), u64::MAX).is_err());
+    }
+
+    #[test]
+    fn integer_logs_are_exact_at_powers() {
+        let ten = |k: u64| Mpz::from(Mpn::TEN.pow(k));
+        let ilog = |x: &Mpz, base: u64| {
+            mpz_ilog(&x.clone().into_wasm_output(), &base.into_wasm_output())
+                .map(|output| u64::from_wasm_input(&output).unwrap())
+        };
+        let power_of = |x: &Mpz, base: u64| {
+            let output =
+                mpz_is_power_of(&x.clone().into_wasm_output(), &base.into_wasm_output()).unwrap();
+            Option::<u64>::from_wasm_input(&output).unwrap()
+        };
+        for k in [1, 3, 15, 16, 17, 100, 308, 309, 1000] {
+            let x = ten(k);
+            assert_eq!(ilog(&x, 10).unwrap(), k);
+            assert_eq!(ilog(&(&x - Mpz::ONE), 10).unwrap(), k - 1);
+            assert_eq!(ilog(&(&x + Mpz::ONE), 10).unwrap(), k);
+            assert_eq!(power_of(&x, 10), Some(k));
+            assert_eq!(power_of(&(&x + Mpz::ONE), 10), None);
+            let output = mpz_checked_ilog10(&x.into_wasm_output()).unwrap();
+            assert_eq!(Option::<u64>::from_wasm_input(&output).unwrap(), Some(k));
+        }
+        let two = Mpz::ONE << 200u64;
+        assert_eq!(ilog(&two, 2).unwrap(), 200);
+        assert_eq!(ilog(&(&two - Mpz::ONE), 2).unwrap(), 199);
+        assert_eq!(ilog(&two, 8).unwrap(), 66);
+        assert_eq!(power_of(&two, 4), Some(100));
+        assert_eq!(power_of(&two, 8), None);
+        assert_eq!(power_of(&Mpz::ONE, 7), Some(0));
+        assert_eq!(power_of(&Mpz::from(-8), 2), None);
+        let output = mpz_checked_ilog2(&Mpz::ZERO.into_wasm_output()).unwrap();
+        assert_eq!(Option::<u64>::from_wasm_input(&output).unwrap(), None);
+        assert!(ilog(&Mpz::ZERO, 10).is_err());
+        assert!(ilog(&Mpz::from(-5), 10).is_err());
+        assert!(ilog(&Mpz::from(5), 1).is_err());
+    }
+
+    #[test]
+    fn significant_figures() {
+        let sig_figs = |x: Mpz, n: u64| {
+            mpz_sig_figs(&x.into_wasm_output(), &n.into_wasm_output())
+                .map(|output| <(Mpz, u64)>::from_wasm_input(&output).unwrap())
+        };
+        assert_eq!(
+            sig_figs(Mpz::from(123456), 3).unwrap(),
+            (Mpz::from(123000), 3)
+        );
+        assert_eq!(
+            sig_figs(Mpz::from(-987654), 2).unwrap(),
+            (Mpz::from(-990000), 4)
+        );
+        // half to even, and a carry into a new digit keeps the count of dropped digits
+        assert_eq!(sig_figs(Mpz::from(125), 2).unwrap(), (Mpz::from(120), 1));
+        assert_eq!(sig_figs(Mpz::from(135), 2).unwrap(), (Mpz::from(140), 1));
+        assert_eq!(sig_figs(Mpz::from(999), 2).unwrap(), (Mpz::from(1000), 1));
+        assert_eq!(sig_figs(Mpz::from(42), 5).unwrap(), (Mpz::from(42), 0));
+        assert_eq!(sig_figs(Mpz::ZERO, 1).unwrap(), (Mpz::ZERO, 0));
+        let googol = Mpz::from(Mpn::TEN.pow(100u64));
+        assert_eq!(
+            sig_figs(&googol * Mpz::from(3) - Mpz::ONE, 1).unwrap(),
+            (&googol * Mpz::from(3), 100)
+        );
+        assert!(sig_figs(Mpz::from(5), 0).is_err());
+    }
+
+    #[test]
+    fn taylor_coefficients_are_exported() {
+        assert_eq!(listed("mpq_taylor_coeff"), Some((2, 2)));
+        let coeff = |function: &str, n: u64| {
+            mpq_taylor_coeff(
+                &function.to_string().into_wasm_output(),
+                &n.into_wasm_output(),
+            )
+            .map(|output| MpqExt::from_wasm_input(&output).unwrap())
+        };
+        assert_eq!(
+            coeff("tan", 5).unwrap(),
+            MpqExt::from(Mpq::from_signeds(2, 15))
+        );
+        assert_eq!(coeff("cos", 3).unwrap(), MpqExt::ZERO);
+        assert!(coeff("log", 1).is_err());
+    }
+
+    #[test]
+    fn cached_calls_return_identical_bytes() {
+        cache::clear();
+        let input = 1000u64.into_wasm_output();
+        let first = mpz_fact(&input).unwrap();
+        assert_eq!(cache::stats().misses, 1);
+        assert_eq!(mpz_fact(&input).unwrap(), first);
+        assert_eq!(Mpn::from_wasm_input(&first).unwrap(), Mpn::factorial(1000));
+        let stats = cache::stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 1));
+        // a cap of one entry evicts the older result
+        cache::configure(1, cache::DEFAULT_MAX_BYTES);
+        mpz_fact(&2000u64.into_wasm_output()).unwrap();
+        assert_eq!(mpz_fact(&input).unwrap(), first);
+        let stats = cache::stats();
+        assert_eq!((stats.entries, stats.misses, stats.evictions), (1, 3, 2));
+        cache::configure(cache::DEFAULT_MAX_ENTRIES, cache::DEFAULT_MAX_BYTES);
+    }
+}
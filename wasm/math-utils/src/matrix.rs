@@ -0,0 +1,216 @@
+//! Square matrices given as lists of rows.
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz, Natural as Mpn, Rational as Mpq,
+    base::num::{
+        arithmetic::traits::{DivExact, Lcm},
+        basic::traits::{One, Zero},
+    },
+};
+use math_utils_base::MpqExt;
+
+/// The largest dimension [`det`], [`inverse`] and [`solve`] accept. The elimination takes `O(n³)`
+/// operations on entries that can grow to `n` times the size of the input's, so larger systems
+/// would take far longer than is reasonable during a compilation.
+pub const MAX_DIMENSION: usize = 64;
+
+fn exact_row(row: Vec<MpqExt>) -> Result<Vec<Mpq>, anyhow::Error> {
+    row.into_iter().map(TryInto::try_into).collect()
+}
+
+/// The entries of a square matrix, which must all be finite.
+fn exact_square(rows: Vec<Vec<MpqExt>>) -> Result<Vec<Vec<Mpq>>, anyhow::Error> {
+    let n = rows.len();
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            if row.len() != n {
+                bail!(
+                    "a {n}-row matrix must have {n} columns, but row {i} has {}",
+                    row.len()
+                );
+            }
+            exact_row(row)
+        })
+        .collect()
+}
+
+fn check_dimension(n: usize) -> Result<(), anyhow::Error> {
+    if n > MAX_DIMENSION {
+        bail!("matrices are limited to {MAX_DIMENSION} rows, but got {n}");
+    }
+    Ok(())
+}
+
+/// Reduces `a` to the identity by Gauss–Jordan elimination, applying the same row operations to
+/// `rhs`, which then holds `a⁻¹ rhs`. Any nonzero pivot will do, as the arithmetic is exact.
+fn gauss_jordan(
+    mut a: Vec<Vec<Mpq>>,
+    mut rhs: Vec<Vec<Mpq>>,
+) -> Result<Vec<Vec<Mpq>>, anyhow::Error> {
+    let n = a.len();
+    for k in 0..n {
+        let Some(pivot) = (k..n).find(|&i| a[i][k] != 0u32) else {
+            bail!("the matrix is singular");
+        };
+        a.swap(k, pivot);
+        rhs.swap(k, pivot);
+        let inv = Mpq::ONE / &a[k][k];
+        for x in a[k].iter_mut().chain(rhs[k].iter_mut()) {
+            *x *= &inv;
+        }
+        let (pivot_row, pivot_rhs) = (a[k].clone(), rhs[k].clone());
+        for i in (0..n).filter(|&i| i != k) {
+            let factor = a[i][k].clone();
+            if factor == 0u32 {
+                continue;
+            }
+            let row = a[i].iter_mut().zip(&pivot_row).skip(k);
+            for (x, p) in row.chain(rhs[i].iter_mut().zip(&pivot_rhs)) {
+                *x -= &factor * p;
+            }
+        }
+    }
+    Ok(rhs)
+}
+
+fn to_ext(rows: Vec<Vec<Mpq>>) -> Vec<Vec<MpqExt>> {
+    rows.into_iter()
+        .map(|row| row.into_iter().map(MpqExt::from).collect())
+        .collect()
+}
+
+/// The exact determinant of a square matrix with finite entries. Each row is scaled to integers
+/// by the least common multiple of its denominators, and the integer matrix is reduced by
+/// Bareiss' fraction-free elimination, whose divisions are all exact, so that no entry grows
+/// beyond the size of a minor. The empty matrix has determinant 1.
+pub fn det(rows: Vec<Vec<MpqExt>>) -> Result<MpqExt, anyhow::Error> {
+    check_dimension(rows.len())?;
+    let rows = exact_square(rows)?;
+    let n = rows.len();
+    let mut scale = Mpn::ONE;
+    let mut m = Vec::with_capacity(n);
+    for row in rows {
+        let den = row
+            .iter()
+            .fold(Mpn::ONE, |lcm, x| lcm.lcm(x.denominator_ref()));
+        let row_scale = Mpq::from(&den);
+        m.push(
+            row.into_iter()
+                .map(|x| Mpz::try_from(x * &row_scale).unwrap())
+                .collect::<Vec<_>>(),
+        );
+        scale *= den;
+    }
+    if n == 0 {
+        return Ok(MpqExt::ONE);
+    }
+    let mut negate = false;
+    let mut prev = Mpz::ONE;
+    for k in 0..n - 1 {
+        if m[k][k] == 0u32 {
+            let Some(pivot) = (k + 1..n).find(|&i| m[i][k] != 0u32) else {
+                return Ok(MpqExt::ZERO);
+            };
+            m.swap(k, pivot);
+            negate = !negate;
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                let value = &m[i][j] * &m[k][k] - &m[i][k] * &m[k][j];
+                m[i][j] = value.div_exact(&prev);
+            }
+        }
+        prev = m[k][k].clone();
+    }
+    let det = Mpq::from_integers(m[n - 1][n - 1].clone(), Mpz::from(scale));
+    Ok(MpqExt::from(if negate { -det } else { det }))
+}
+
+/// The exact inverse of a nonsingular square matrix with finite entries.
+pub fn inverse(rows: Vec<Vec<MpqExt>>) -> Result<Vec<Vec<MpqExt>>, anyhow::Error> {
+    let a = exact_square(rows)?;
+    let n = a.len();
+    check_dimension(n)?;
+    let identity = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == j { Mpq::ONE } else { Mpq::ZERO })
+                .collect()
+        })
+        .collect();
+    Ok(to_ext(gauss_jordan(a, identity)?))
+}
+
+/// The exact solution `x` of `a x = b` for a nonsingular square `a`.
+pub fn solve(rows: Vec<Vec<MpqExt>>, b: Vec<MpqExt>) -> Result<Vec<MpqExt>, anyhow::Error> {
+    let a = exact_square(rows)?;
+    check_dimension(a.len())?;
+    if b.len() != a.len() {
+        bail!(
+            "the right-hand side of a {}-row system must have {} entries, but got {}",
+            a.len(),
+            a.len(),
+            b.len()
+        );
+    }
+    let rhs = exact_row(b)?.into_iter().map(|x| vec![x]).collect();
+    Ok(to_ext(gauss_jordan(a, rhs)?)
+        .into_iter()
+        .map(|mut row| row.pop().unwrap())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&[(i64, i64)]]) -> Vec<Vec<MpqExt>> {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&(n, d)| MpqExt::from(Mpq::from_signeds(n, d)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rational_determinants() {
+        // 1/2 · (1/3 · 1 - 0) - 1 · (0 - 2 · 1/4) + 0 = 1/6 + 1/2
+        let a = matrix(&[
+            &[(1, 2), (1, 1), (0, 1)],
+            &[(0, 1), (1, 3), (2, 1)],
+            &[(1, 4), (0, 1), (1, 1)],
+        ]);
+        assert_eq!(det(a).unwrap(), MpqExt::from(Mpq::from_signeds(2, 3)));
+        // a zero leading pivot forces a row swap
+        let b = matrix(&[
+            &[(0, 1), (1, 1), (0, 1), (0, 1)],
+            &[(1, 1), (0, 1), (0, 1), (0, 1)],
+            &[(0, 1), (0, 1), (3, 2), (0, 1)],
+            &[(0, 1), (0, 1), (0, 1), (-2, 5)],
+        ]);
+        assert_eq!(det(b).unwrap(), MpqExt::from(Mpq::from_signeds(3, 5)));
+        let singular = matrix(&[&[(1, 2), (1, 3)], &[(3, 2), (1, 1)]]);
+        assert_eq!(det(singular).unwrap(), MpqExt::ZERO);
+        assert_eq!(det(vec![]).unwrap(), MpqExt::ONE);
+    }
+
+    #[test]
+    fn invalid_matrices() {
+        let error = det(matrix(&[&[(1, 1), (2, 1)]])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "a 1-row matrix must have 1 columns, but row 0 has 2"
+        );
+        assert!(det(vec![vec![MpqExt::NaN]]).is_err());
+        let n = MAX_DIMENSION + 1;
+        let error = det(vec![vec![]; n]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("matrices are limited to {MAX_DIMENSION} rows, but got {n}")
+        );
+    }
+}
@@ -0,0 +1,445 @@
+//! Polynomials over `f64`. Every function here takes its coefficients from the highest degree
+//! down, so `[1, 0, -2]` is `x² - 2`.
+
+use anyhow::bail;
+use num::complex::Complex64;
+
+/// The highest degree [`real_roots`] accepts. Its work grows with the cube of the degree.
+pub const MAX_REAL_ROOTS_DEGREE: usize = 100;
+
+/// Drops leading zero coefficients so that the first coefficient is the leading one.
+pub fn trim_leading_zeros(coeffs: &[f64]) -> &[f64] {
+    let start = coeffs
+        .iter()
+        .position(|&c| c != 0.0)
+        .unwrap_or(coeffs.len());
+    &coeffs[start..]
+}
+
+/// Evaluates the polynomial by Horner's rule.
+pub fn eval(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().fold(0.0, |acc, &c| acc * x + c)
+}
+
+/// Evaluates the polynomial at a complex point by Horner's rule.
+pub fn eval_complex(coeffs: &[f64], z: Complex64) -> Complex64 {
+    coeffs
+        .iter()
+        .fold(Complex64::new(0.0, 0.0), |acc, &c| acc * z + c)
+}
+
+/// Evaluates the polynomial and its derivative in a single Horner pass.
+pub fn eval_with_derivative(coeffs: &[f64], x: f64) -> (f64, f64) {
+    coeffs.iter().fold((0.0, 0.0), |(value, deriv), &c| {
+        // (p·x + c)' = p'·x + p
+        (value * x + c, deriv * x + value)
+    })
+}
+
+/// Coefficients of the derivative, highest degree first.
+pub fn derivative(coeffs: &[f64]) -> Vec<f64> {
+    let n = coeffs.len().saturating_sub(1);
+    coeffs[..n]
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| c * (n - i) as f64)
+        .collect()
+}
+
+/// A bound on the rounding error of `eval(coeffs, x)`.
+fn eval_error_bound(coeffs: &[f64], x: f64) -> f64 {
+    let magnitude = coeffs.iter().fold(0.0, |acc, &c| acc * x.abs() + c.abs());
+    4.0 * coeffs.len() as f64 * f64::EPSILON * magnitude
+}
+
+/// Bisects the sign change of `coeffs` on `[lo, hi]` until the interval cannot shrink any more.
+fn bisect(coeffs: &[f64], mut lo: f64, mut hi: f64) -> f64 {
+    let lo_positive = eval(coeffs, lo) > 0.0;
+    loop {
+        let mid = lo + (hi - lo) / 2.0;
+        if mid <= lo || mid >= hi {
+            return mid;
+        }
+        let y = eval(coeffs, mid);
+        if y == 0.0 {
+            return mid;
+        }
+        if (y > 0.0) == lo_positive {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+}
+
+/// Real roots of a polynomial without leading zeros, each listed once, inside `[-bound, bound]`.
+///
+/// Between two consecutive critical points the polynomial is monotone, so each such interval
+/// holds at most one root; it is found by bisection if the signs at the ends differ. A critical
+/// point at which the polynomial vanishes up to rounding is a multiple root.
+fn real_roots_in(coeffs: &[f64], bound: f64) -> Vec<f64> {
+    match coeffs.len() {
+        0 | 1 => return Vec::new(),
+        2 => return vec![-coeffs[1] / coeffs[0]],
+        _ => {}
+    }
+    let mut points = vec![-bound];
+    points.extend(real_roots_in(&derivative(coeffs), bound));
+    points.push(bound);
+
+    let is_root = |x: f64| eval(coeffs, x).abs() <= eval_error_bound(coeffs, x);
+    let mut roots = Vec::new();
+    for (i, window) in points.windows(2).enumerate() {
+        let (lo, hi) = (window[0], window[1]);
+        // the interior points are critical points of the polynomial
+        if i > 0 && is_root(lo) {
+            if roots.last() != Some(&lo) {
+                roots.push(lo);
+            }
+            continue;
+        }
+        if i + 2 < points.len() && is_root(hi) {
+            continue;
+        }
+        let (y_lo, y_hi) = (eval(coeffs, lo), eval(coeffs, hi));
+        if (y_lo > 0.0) != (y_hi > 0.0) {
+            roots.push(bisect(coeffs, lo, hi));
+        }
+    }
+    roots
+}
+
+/// Real roots of the polynomial with coefficients from the highest degree down, sorted
+/// ascending and listed once regardless of multiplicity. The constant polynomials, including
+/// the zero polynomial, are reported as having no roots.
+pub fn real_roots(coeffs: &[f64]) -> Result<Vec<f64>, anyhow::Error> {
+    let coeffs = trim_leading_zeros(coeffs);
+    if coeffs.len() > MAX_REAL_ROOTS_DEGREE + 1 {
+        bail!(
+            "polynomials of degree {} are above the limit of {MAX_REAL_ROOTS_DEGREE}",
+            coeffs.len() - 1
+        );
+    }
+    // factor out xᵏ so that the remaining polynomial has a nonzero constant term
+    let n_zero_roots = coeffs.iter().rev().take_while(|&&c| c == 0.0).count();
+    let coeffs = &coeffs[..coeffs.len() - n_zero_roots];
+    let mut roots = if coeffs.is_empty() {
+        Vec::new()
+    } else {
+        // Cauchy's bound: every root satisfies |x| < 1 + max |aᵢ / a₀|
+        let monic: Vec<f64> = coeffs.iter().map(|c| c / coeffs[0]).collect();
+        let bound = 1.0 + monic[1..].iter().fold(0.0, |acc: f64, c| acc.max(c.abs()));
+        real_roots_in(&monic, bound)
+    };
+    if n_zero_roots > 0 && !coeffs.is_empty() {
+        roots.push(0.0);
+    }
+    roots.sort_by(f64::total_cmp);
+    roots.dedup();
+    Ok(roots)
+}
+
+/// Roots of `bx + c` for the degenerate cases of the closed-form solvers.
+fn solve_linear(b: f64, c: f64) -> Vec<Complex64> {
+    if b == 0.0 {
+        Vec::new()
+    } else {
+        vec![Complex64::new(-c / b, 0.0)]
+    }
+}
+
+/// Both roots of `ax² + bx + c`. The real case uses `q = -(b + sgn(b)√Δ) / 2` and the roots
+/// `q / a`, `c / q`, which avoids the cancellation in the textbook formula.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<Complex64> {
+    if a == 0.0 {
+        return solve_linear(b, c);
+    }
+    let disc = b * b - 4.0 * a * c;
+    if disc >= 0.0 {
+        let q = -(b + b.signum() * disc.sqrt()) / 2.0;
+        if q == 0.0 {
+            // b = c = 0
+            return vec![Complex64::new(0.0, 0.0); 2];
+        }
+        vec![Complex64::new(q / a, 0.0), Complex64::new(c / q, 0.0)]
+    } else {
+        let re = -b / (2.0 * a);
+        let im = (-disc).sqrt() / (2.0 * a).abs();
+        vec![Complex64::new(re, -im), Complex64::new(re, im)]
+    }
+}
+
+/// All three roots of `ax³ + bx² + cx + d`, with the trigonometric form when they are all
+/// real and Cardano's formula otherwise.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<Complex64> {
+    if a == 0.0 {
+        return solve_quadratic(b, c, d);
+    }
+    // x = t - b / 3a turns the equation into t³ + pt + q = 0
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = -b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let disc = -(4.0 * p * p * p + 27.0 * q * q);
+    let mut roots = if disc > 0.0 {
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = (3.0 * q / (p * r)).clamp(-1.0, 1.0).acos() / 3.0;
+        (0..3)
+            .map(|k| {
+                let t = r * (phi - std::f64::consts::TAU * k as f64 / 3.0).cos();
+                Complex64::new(t, 0.0)
+            })
+            .collect()
+    } else {
+        // one real root t₁ = u - p / 3u, the other two solve t² + t₁t + (t₁² + p) = 0
+        let s = (q * q / 4.0 + p * p * p / 27.0).sqrt();
+        let u = (-q / 2.0 - q.signum() * s).cbrt();
+        let t1 = if u == 0.0 { 0.0 } else { u - p / (3.0 * u) };
+        let mut roots = vec![Complex64::new(t1, 0.0)];
+        roots.extend(solve_quadratic(1.0, t1, t1 * t1 + p));
+        roots
+    };
+    for root in roots.iter_mut() {
+        root.re += shift;
+    }
+    roots
+}
+
+/// All four roots of `ax⁴ + bx³ + cx² + dx + e` by Ferrari's method.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<Complex64> {
+    if a == 0.0 {
+        return solve_cubic(b, c, d, e);
+    }
+    // x = y - b / 4a turns the equation into y⁴ + py² + qy + r = 0
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    let shift = -b / 4.0;
+    let b2 = b * b;
+    let p = c - 3.0 * b2 / 8.0;
+    let q = d - b * c / 2.0 + b2 * b / 8.0;
+    let r = e - b * d / 4.0 + b2 * c / 16.0 - 3.0 * b2 * b2 / 256.0;
+    let mut roots = if q == 0.0 {
+        // biquadratic: y² is a root of z² + pz + r = 0
+        solve_quadratic(1.0, p, r)
+            .into_iter()
+            .flat_map(|z| {
+                let y = z.sqrt();
+                [-y, y]
+            })
+            .collect()
+    } else {
+        // the resolvent m³ + pm² + (p²/4 - r)m - q²/8 = 0 always has a positive real root
+        let m = solve_cubic(1.0, p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .filter(|m| m.im == 0.0)
+            .map(|m| m.re)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let s = (2.0 * m).sqrt();
+        let mut roots = solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s));
+        roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+        roots
+    };
+    for root in roots.iter_mut() {
+        root.re += shift;
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The coefficients of `∏ (x - rᵢ)`, highest degree first.
+    fn from_roots(roots: &[f64]) -> Vec<f64> {
+        roots.iter().fold(vec![1.0], |coeffs, &r| {
+            let mut next = coeffs.clone();
+            next.push(0.0);
+            for (i, c) in coeffs.iter().enumerate() {
+                next[i + 1] -= r * c;
+            }
+            next
+        })
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64], tol: f64) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} vs {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a - e).abs() <= tol * e.abs().max(1.0),
+                "{actual:?} vs {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn leading_zeros_are_stripped() {
+        assert_eq!(trim_leading_zeros(&[0.0, 0.0, 1.0, -3.0]), [1.0, -3.0]);
+        assert_close(
+            &real_roots(&[0.0, 0.0, 1.0, -3.0, 2.0]).unwrap(),
+            &[1.0, 2.0],
+            1e-15,
+        );
+        // a leading zero does not count toward the degree limit
+        let mut coeffs = vec![0.0; 1000];
+        coeffs.extend([1.0, -1.0]);
+        assert_eq!(real_roots(&coeffs).unwrap(), [1.0]);
+    }
+
+    #[test]
+    fn constant_polynomials_have_no_roots() {
+        assert_eq!(real_roots(&[]).unwrap(), Vec::<f64>::new());
+        assert_eq!(real_roots(&[0.0]).unwrap(), Vec::<f64>::new());
+        assert_eq!(real_roots(&[0.0, 0.0, 0.0]).unwrap(), Vec::<f64>::new());
+        assert_eq!(real_roots(&[5.0]).unwrap(), Vec::<f64>::new());
+        assert_eq!(real_roots(&[0.0, -2.5]).unwrap(), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn repeated_roots_are_listed_once() {
+        assert_eq!(real_roots(&[1.0, -2.0, 1.0]).unwrap(), [1.0]);
+        // (x - 1)² (x + 2) = x³ - 3x + 2
+        assert_close(
+            &real_roots(&[1.0, 0.0, -3.0, 2.0]).unwrap(),
+            &[-2.0, 1.0],
+            1e-12,
+        );
+        assert_eq!(real_roots(&[1.0, 0.0, 0.0, 0.0]).unwrap(), [0.0]);
+        assert_close(&real_roots(&[2.0, -2.0, 0.0]).unwrap(), &[0.0, 1.0], 1e-15);
+        // x² + 1 has no real roots
+        assert_eq!(real_roots(&[1.0, 0.0, 1.0]).unwrap(), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn roots_are_sorted() {
+        let expected = [-4.0, -1.0, 0.5, 3.0];
+        let coeffs = from_roots(&[3.0, -1.0, 0.5, -4.0]);
+        assert_close(&real_roots(&coeffs).unwrap(), &expected, 1e-12);
+        let expected: Vec<f64> = (1..=10).map(f64::from).collect();
+        let coeffs = from_roots(&[7.0, 2.0, 9.0, 1.0, 10.0, 4.0, 3.0, 8.0, 6.0, 5.0]);
+        let roots = real_roots(&coeffs).unwrap();
+        assert!(roots.is_sorted());
+        assert_close(&roots, &expected, 1e-9);
+    }
+
+    #[test]
+    fn degree_is_limited() {
+        let coeffs = from_roots(&[1.0; MAX_REAL_ROOTS_DEGREE + 1]);
+        assert!(real_roots(&coeffs).is_err());
+        let mut coeffs = vec![0.0; MAX_REAL_ROOTS_DEGREE + 1];
+        coeffs[0] = 1.0;
+        assert_eq!(real_roots(&coeffs).unwrap(), [0.0]);
+    }
+
+    /// The roots sorted by real and then imaginary part.
+    fn sorted(mut roots: Vec<Complex64>) -> Vec<Complex64> {
+        roots.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        roots
+    }
+
+    fn assert_roots(actual: Vec<Complex64>, expected: &[(f64, f64)], tol: f64) {
+        let actual = sorted(actual);
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, &(re, im)) in actual.iter().zip(expected) {
+            let e = Complex64::new(re, im);
+            assert!(
+                (a - e).norm() <= tol * e.norm().max(1.0),
+                "{actual:?} vs {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quadratics_avoid_cancellation() {
+        // the textbook (-b + √(b² - 4ac)) / 2a gives about -7.45e-9 for the small root
+        let roots = sorted(solve_quadratic(1.0, 1e8, 1.0));
+        assert!((roots[0].re + 1e8).abs() <= 1e-8);
+        assert!((roots[1].re + 1e-8).abs() <= 1e-8 * f64::EPSILON);
+        assert!(roots.iter().all(|z| z.im == 0.0));
+        let roots = sorted(solve_quadratic(1.0, -1e8, 1.0));
+        assert!((roots[0].re - 1e-8).abs() <= 1e-8 * f64::EPSILON);
+        assert_roots(
+            solve_quadratic(1.0, 0.0, 1.0),
+            &[(0.0, -1.0), (0.0, 1.0)],
+            0.0,
+        );
+        assert_roots(
+            solve_quadratic(2.0, -4.0, 10.0),
+            &[(1.0, -2.0), (1.0, 2.0)],
+            0.0,
+        );
+        assert_roots(
+            solve_quadratic(3.0, 0.0, 0.0),
+            &[(0.0, 0.0), (0.0, 0.0)],
+            0.0,
+        );
+    }
+
+    #[test]
+    fn cubics() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(
+            solve_cubic(1.0, -6.0, 11.0, -6.0),
+            &[(1.0, 0.0), (2.0, 0.0), (3.0, 0.0)],
+            1e-14,
+        );
+        let half_root3 = 3f64.sqrt() / 2.0;
+        assert_roots(
+            solve_cubic(2.0, 0.0, 0.0, -2.0),
+            &[(-0.5, -half_root3), (-0.5, half_root3), (1.0, 0.0)],
+            1e-15,
+        );
+        // a triple root at -1
+        assert_roots(
+            solve_cubic(1.0, 3.0, 3.0, 1.0),
+            &[(-1.0, 0.0), (-1.0, 0.0), (-1.0, 0.0)],
+            1e-15,
+        );
+        for root in solve_cubic(1.0, -2.0, 3.0, 5.0) {
+            assert!(eval_complex(&[1.0, -2.0, 3.0, 5.0], root).norm() < 1e-13);
+        }
+    }
+
+    #[test]
+    fn quartics() {
+        // the biquadratic x⁴ - 5x² + 4 = (x² - 1)(x² - 4)
+        assert_roots(
+            solve_quartic(1.0, 0.0, -5.0, 0.0, 4.0),
+            &[(-2.0, 0.0), (-1.0, 0.0), (1.0, 0.0), (2.0, 0.0)],
+            1e-15,
+        );
+        let r = std::f64::consts::FRAC_1_SQRT_2;
+        assert_roots(
+            solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0),
+            &[(-r, -r), (-r, r), (r, -r), (r, r)],
+            1e-15,
+        );
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[(1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (4.0, 0.0)],
+            1e-12,
+        );
+        let coeffs = [2.0, 1.0, -3.0, 4.0, 7.0];
+        let roots = solve_quartic(2.0, 1.0, -3.0, 4.0, 7.0);
+        assert_eq!(roots.len(), 4);
+        for root in roots {
+            assert!(eval_complex(&coeffs, root).norm() < 1e-12, "{root}");
+        }
+    }
+
+    #[test]
+    fn vanishing_leading_coefficients() {
+        assert_roots(solve_quadratic(0.0, 2.0, -4.0), &[(2.0, 0.0)], 0.0);
+        assert!(solve_quadratic(0.0, 0.0, 1.0).is_empty());
+        assert_roots(
+            solve_cubic(0.0, 1.0, 0.0, -4.0),
+            &[(-2.0, 0.0), (2.0, 0.0)],
+            0.0,
+        );
+        assert_roots(
+            solve_quartic(0.0, 1.0, -6.0, 11.0, -6.0),
+            &[(1.0, 0.0), (2.0, 0.0), (3.0, 0.0)],
+            1e-14,
+        );
+        assert!(solve_quartic(0.0, 0.0, 0.0, 0.0, 1.0).is_empty());
+    }
+}
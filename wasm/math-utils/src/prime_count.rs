@@ -0,0 +1,235 @@
+//! The prime-counting function and the `n`-th prime, exactly for 64-bit arguments and as estimates
+//! between proven bounds for larger ones.
+
+use anyhow::bail;
+use malachite::{
+    Integer as Mpz,
+    base::{num::conversion::traits::RoundingFrom, rounding_modes::RoundingMode},
+};
+use math_utils_base::primes;
+
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// π(10¹²), the index of the last prime [`nth_prime`] can reach.
+const MAX_NTH_PRIME_INDEX: u64 = 37_607_912_018;
+
+/// Dusart's `π(x) ≤ x / ln x · (1 + 1 / ln x + 2.51 / ln² x)` holds from here on, and his lower
+/// bound `π(x) ≥ x / ln x · (1 + 1 / ln x)` from 599.
+const PRIME_PI_BOUNDS_START: u64 = 355_991;
+
+/// Dusart's `pₖ ≤ k (ln k + ln ln k - 0.9484)` holds from here on, and his lower bound
+/// `pₖ ≥ k (ln k + ln ln k - 1)` from 2.
+const NTH_PRIME_BOUNDS_START: u64 = 39_017;
+
+/// Below this index the `n`-th prime is found by sieving up to Rosser's bound
+/// `pₖ < k (ln k + ln ln k)`.
+const SIEVED_NTH_PRIME_INDEX: u64 = 100_000;
+
+/// The relative margin by which the bounds are widened to cover the rounding of `f64`, far less
+/// than the slack in the inequalities.
+const ROUNDING_MARGIN: f64 = 1e-12;
+
+/// The logarithmic integral `li(x) = ∫₀ˣ dt / ln t` for `x > 1`, by Ramanujan's series for
+/// moderate `x` and by the asymptotic series `x / ln x · Σ k! / lnᵏ x`, cut off at its smallest
+/// term, for large `x`.
+pub fn li(x: f64) -> f64 {
+    let l = x.ln();
+    if l < 40.0 {
+        // li(x) = γ + ln ln x + √x Σ (-1)ⁿ⁻¹ lnⁿ x / (n! 2ⁿ⁻¹) Σ_{k < n / 2} 1 / (2k + 1)
+        let (mut sum, mut factor, mut inner) = (0.0, 1.0, 0.0);
+        for n in 1..1000 {
+            factor *= if n == 1 { l } else { -l / (2 * n) as f64 };
+            if n % 2 == 1 {
+                inner += 1.0 / n as f64;
+            }
+            let term = factor * inner;
+            sum += term;
+            if n as f64 > l && term.abs() < f64::EPSILON * sum.abs() {
+                break;
+            }
+        }
+        EULER_GAMMA + l.ln() + x.sqrt() * sum
+    } else {
+        let (mut sum, mut term) = (1.0, 1.0);
+        for k in 1.. {
+            let next = term * k as f64 / l;
+            if next >= term {
+                break;
+            }
+            term = next;
+            sum += term;
+        }
+        x / l * sum
+    }
+}
+
+/// The `x > 1` with `li(x) = y`, by Newton's method from `y ln y`.
+pub fn li_inverse(y: f64) -> f64 {
+    let mut x = (y * y.ln()).max(2.0);
+    for _ in 0..100 {
+        let step = (li(x) - y) * x.ln();
+        x -= step;
+        if step.abs() <= f64::EPSILON * x {
+            break;
+        }
+    }
+    x
+}
+
+/// The `k`-th prime, counting from `p₁ = 2`, for `k` up to π(10¹²). Beyond the sieved range, the
+/// prime count at `li⁻¹(k)` is computed exactly and the few primes between it and `pₖ` are found
+/// by sieving outward.
+pub fn nth_prime(k: u64) -> Result<u64, anyhow::Error> {
+    const WINDOW: u64 = 1 << 20;
+    if k == 0 {
+        bail!("primes are numbered from 1");
+    }
+    if k > MAX_NTH_PRIME_INDEX {
+        bail!(
+            "the {k}-th prime exceeds the limit of {}",
+            primes::MAX_PRIME_PI
+        );
+    }
+    if k < 6 {
+        return Ok([2, 3, 5, 7, 11][k as usize - 1]);
+    }
+    if k < SIEVED_NTH_PRIME_INDEX {
+        let y = k as f64;
+        let bound = (y * (y.ln() + y.ln().ln())) as u64;
+        return Ok(primes::primes_below(bound + 1)?[k as usize - 1]);
+    }
+    let x = (li_inverse(k as f64).round() as u64).min(primes::MAX_PRIME_PI);
+    let mut count = primes::prime_pi(x)?;
+    if count < k {
+        let mut lo = x + 1;
+        loop {
+            let found = primes::primes_in_range(lo, lo + WINDOW - 1)?;
+            if count + found.len() as u64 >= k {
+                return Ok(found[(k - count - 1) as usize]);
+            }
+            count += found.len() as u64;
+            lo += WINDOW;
+        }
+    } else {
+        // the `count`-th prime is the largest one up to x
+        let mut hi = x;
+        loop {
+            let lo = hi.saturating_sub(WINDOW - 1).max(2);
+            let found = primes::primes_in_range(lo, hi)?;
+            let first = count - found.len() as u64;
+            if k > first {
+                return Ok(found[(k - first - 1) as usize]);
+            }
+            count = first;
+            hi = lo - 1;
+        }
+    }
+}
+
+fn to_f64(n: &Mpz) -> Result<f64, anyhow::Error> {
+    let x = f64::rounding_from(n, RoundingMode::Nearest).0;
+    // malachite saturates at f64::MAX
+    if !x.is_finite() || x == f64::MAX {
+        bail!("{n} is too large to estimate");
+    }
+    Ok(x)
+}
+
+/// `(estimate, lower, upper)` with the bounds widened by [`ROUNDING_MARGIN`] and rounded
+/// outward.
+fn to_integer_bounds(estimate: f64, lower: f64, upper: f64) -> (Mpz, Mpz, Mpz) {
+    let round = |x: f64, mode| Mpz::rounding_from(x, mode).0;
+    (
+        round(estimate, RoundingMode::Nearest),
+        round(lower * (1.0 - ROUNDING_MARGIN), RoundingMode::Floor),
+        round(upper * (1.0 + ROUNDING_MARGIN), RoundingMode::Ceiling),
+    )
+}
+
+/// `(li(n), lower, upper)` with `lower ≤ π(n) ≤ upper` by Dusart's inequalities. Where the
+/// inequalities are not proven all three are the exact count.
+pub fn prime_pi_approx(n: &Mpz) -> Result<(Mpz, Mpz, Mpz), anyhow::Error> {
+    if *n < PRIME_PI_BOUNDS_START {
+        let count = Mpz::from(primes::prime_pi(u64::try_from(n).unwrap_or(0))?);
+        return Ok((count.clone(), count.clone(), count));
+    }
+    let x = to_f64(n)?;
+    let l = x.ln();
+    let base = x / l * (1.0 + 1.0 / l);
+    Ok(to_integer_bounds(
+        li(x),
+        base,
+        base + x / l * 2.51 / (l * l),
+    ))
+}
+
+/// `(li⁻¹(k), lower, upper)` with `lower ≤ pₖ ≤ upper` by Dusart's inequalities. Where the
+/// upper bound is not proven all three are the exact prime.
+pub fn nth_prime_approx(k: &Mpz) -> Result<(Mpz, Mpz, Mpz), anyhow::Error> {
+    if *k <= 0u32 {
+        bail!("primes are numbered from 1");
+    }
+    if *k < NTH_PRIME_BOUNDS_START {
+        let p = Mpz::from(nth_prime(u64::try_from(k).unwrap())?);
+        return Ok((p.clone(), p.clone(), p));
+    }
+    let y = to_f64(k)?;
+    let log_sum = y.ln() + y.ln().ln();
+    Ok(to_integer_bounds(
+        li_inverse(y),
+        y * (log_sum - 1.0),
+        y * (log_sum - 0.9484),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(bounds: (Mpz, Mpz, Mpz), exact: u64) -> bool {
+        let (_, lower, upper) = bounds;
+        lower <= exact && exact <= upper
+    }
+
+    #[test]
+    fn prime_count_bounds_contain_published_values() {
+        for (n, count) in [
+            (1_000_000_000u64, 50_847_534),
+            (10_000_000_000, 455_052_511),
+        ] {
+            let bounds = prime_pi_approx(&Mpz::from(n)).unwrap();
+            assert_eq!(bounds.0, Mpz::from(li(n as f64).round() as u64));
+            assert!(contains(bounds, count), "π({n})");
+        }
+        // π(10²²), far beyond the exact method
+        let n = Mpz::from(10u128.pow(22));
+        let (_, lower, upper) = prime_pi_approx(&n).unwrap();
+        let count = Mpz::from(201_467_286_689_315_906_290u128);
+        assert!(lower <= count && count <= upper);
+        // below the range of the inequalities the count is exact
+        let exact = Mpz::from(168);
+        assert_eq!(
+            prime_pi_approx(&Mpz::from(1000)).unwrap(),
+            (exact.clone(), exact.clone(), exact)
+        );
+        assert_eq!(prime_pi_approx(&Mpz::from(-5)).unwrap().0, 0);
+    }
+
+    #[test]
+    fn nth_primes() {
+        assert_eq!(nth_prime(1).unwrap(), 2);
+        assert_eq!(nth_prime(1000).unwrap(), 7919);
+        assert_eq!(nth_prime(1_000_000).unwrap(), 15_485_863);
+        assert!(nth_prime(0).is_err());
+        assert!(nth_prime(MAX_NTH_PRIME_INDEX + 1).is_err());
+        assert!(contains(
+            nth_prime_approx(&Mpz::from(1_000_000_000)).unwrap(),
+            22_801_763_489
+        ));
+        let exact = Mpz::from(7919);
+        assert_eq!(
+            nth_prime_approx(&Mpz::from(1000)).unwrap(),
+            (exact.clone(), exact.clone(), exact)
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use quaternion::Quaternion;
 use serde::{Deserialize, Serialize};
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct QuaternionData<T> {
     pub re: T,
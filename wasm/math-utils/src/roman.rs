@@ -0,0 +1,115 @@
+//! Roman numerals in the standard subtractive form, which covers 1 to 3999.
+
+use anyhow::bail;
+
+pub const MAX_ROMAN: u32 = 3999;
+
+/// The symbols and subtractive pairs from the largest value down.
+const NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+pub fn to_roman(mut n: u32) -> Result<String, anyhow::Error> {
+    if !(1..=MAX_ROMAN).contains(&n) {
+        bail!("{n} is out of the range [1, {MAX_ROMAN}] of Roman numerals");
+    }
+    let mut out = String::new();
+    for (value, symbol) in NUMERALS {
+        while n >= value {
+            out += symbol;
+            n -= value;
+        }
+    }
+    Ok(out)
+}
+
+/// Parses a Roman numeral in either case. Only the standard form is accepted, so `IIII`, `IC`
+/// and `VX` are rejected along with the empty string.
+pub fn from_roman(src: &str) -> Result<u32, anyhow::Error> {
+    let src = src.trim();
+    let values = src
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'I' => Ok(1),
+            'V' => Ok(5),
+            'X' => Ok(10),
+            'L' => Ok(50),
+            'C' => Ok(100),
+            'D' => Ok(500),
+            'M' => Ok(1000),
+            _ => bail!("{c:?} is not a Roman numeral symbol"),
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+    // a symbol before a larger one is subtracted
+    let mut n = 0u32;
+    for (i, &value) in values.iter().enumerate() {
+        match values.get(i + 1) {
+            Some(&next) if next > value => n = n.wrapping_sub(value),
+            _ => n = n.wrapping_add(value),
+        }
+    }
+    // any other spelling of the value is not the standard one
+    match to_roman(n) {
+        Ok(standard) if standard.eq_ignore_ascii_case(src) => Ok(n),
+        _ => bail!("{src:?} is not a Roman numeral in standard form"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for n in 1..=MAX_ROMAN {
+            let roman = to_roman(n).unwrap();
+            assert_eq!(from_roman(&roman).unwrap(), n, "{roman}");
+            assert_eq!(
+                from_roman(&roman.to_ascii_lowercase()).unwrap(),
+                n,
+                "{roman}"
+            );
+        }
+        assert_eq!(to_roman(1994).unwrap(), "MCMXCIV");
+        assert_eq!(to_roman(MAX_ROMAN).unwrap(), "MMMCMXCIX");
+        assert_eq!(from_roman(" xlii ").unwrap(), 42);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let error = to_roman(0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "0 is out of the range [1, 3999] of Roman numerals"
+        );
+        assert!(to_roman(MAX_ROMAN + 1).is_err());
+        assert!(to_roman(u32::MAX).is_err());
+        assert!(from_roman("MMMM").is_err());
+    }
+
+    #[test]
+    fn non_standard_forms() {
+        for src in ["IIII", "IC", "VX", "IIV", "XXXX", "DD", "IM", "CMM", ""] {
+            assert!(from_roman(src).is_err(), "{src:?}");
+        }
+        let error = from_roman("IIII").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "\"IIII\" is not a Roman numeral in standard form"
+        );
+        let error = from_roman("XIZ").unwrap_err();
+        assert_eq!(error.to_string(), "'Z' is not a Roman numeral symbol");
+    }
+}
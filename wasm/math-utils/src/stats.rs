@@ -0,0 +1,508 @@
+use std::cmp::Ordering;
+
+use anyhow::bail;
+use math_utils_base::MpqExt;
+use serde::{Deserialize, Serialize};
+
+/// The most bins a histogram may have.
+pub const MAX_BINS: u64 = 1 << 16;
+
+/// The sum of `data` with Kahan's compensated summation.
+pub fn kahan_sum(data: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    // the low-order bits lost in the last addition
+    let mut compensation = 0.0;
+    for &x in data {
+        let y = x - compensation;
+        let t = sum + y;
+        compensation = (t - sum) - y;
+        sum = t;
+    }
+    sum
+}
+
+/// `(s, e)` with `s = fl(a + b)` and `a + b = s + e` exactly (Knuth's TwoSum).
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let b_virtual = s - a;
+    let a_virtual = s - b_virtual;
+    (s, (a - a_virtual) + (b - b_virtual))
+}
+
+/// `(p, e)` with `p = fl(a · b)` and `a · b = p + e` exactly, using a fused multiply-add.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+/// The sum of `data` with Neumaier's variant of compensated summation, which also handles
+/// terms larger than the running sum. Infinities and `NaN` propagate as in plain summation.
+pub fn neumaier_sum(data: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+    for &x in data {
+        let error;
+        (sum, error) = two_sum(sum, x);
+        compensation += error;
+    }
+    // the error terms of an infinite or NaN sum are meaningless
+    if sum.is_finite() {
+        sum + compensation
+    } else {
+        sum
+    }
+}
+
+/// The exact sum of `data`, every float being a rational number.
+pub fn exact_sum(data: &[f64]) -> MpqExt {
+    data.iter().map(|&x| MpqExt::try_from(x).unwrap()).sum()
+}
+
+/// The dot product of `xs` and `ys` as if computed in twice the working precision (Ogita, Rump
+/// and Oishi's `Dot2`).
+pub fn dot_accurate(xs: &[f64], ys: &[f64]) -> Result<f64, anyhow::Error> {
+    if xs.len() != ys.len() {
+        bail!(
+            "vectors of lengths {} and {} cannot be multiplied",
+            xs.len(),
+            ys.len()
+        );
+    }
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+    for (&x, &y) in xs.iter().zip(ys) {
+        let (product, product_error) = two_product(x, y);
+        let sum_error;
+        (sum, sum_error) = two_sum(sum, product);
+        compensation += sum_error + product_error;
+    }
+    Ok(if sum.is_finite() {
+        sum + compensation
+    } else {
+        sum
+    })
+}
+
+pub fn mean(data: &[f64]) -> Result<f64, anyhow::Error> {
+    if data.is_empty() {
+        bail!("the mean of an empty data set is undefined");
+    }
+    Ok(kahan_sum(data) / data.len() as f64)
+}
+
+/// The variance by Welford's online algorithm, with the `n - 1` denominator if `sample` is set
+/// and `n` otherwise.
+pub fn variance(data: &[f64], sample: bool) -> Result<f64, anyhow::Error> {
+    let min_len = if sample { 2 } else { 1 };
+    if data.len() < min_len {
+        bail!("the variance needs at least {min_len} data points");
+    }
+    let mut mean = 0.0;
+    // the sum of squared deviations from the running mean
+    let mut m2 = 0.0;
+    for (i, &x) in data.iter().enumerate() {
+        let delta = x - mean;
+        mean += delta / (i + 1) as f64;
+        m2 += delta * (x - mean);
+    }
+    Ok(m2 / (data.len() - min_len + 1) as f64)
+}
+
+pub fn stddev(data: &[f64], sample: bool) -> Result<f64, anyhow::Error> {
+    Ok(variance(data, sample)?.sqrt())
+}
+
+/// Bin counts over `edges`, with the values that fell in no bin counted separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram {
+    pub counts: Vec<u64>,
+    pub edges: Vec<f64>,
+    /// Values below the first edge.
+    pub below: u64,
+    /// Values above the last edge.
+    pub above: u64,
+    pub nan: u64,
+}
+
+fn check_edges(edges: &[f64]) -> Result<(), anyhow::Error> {
+    if edges.len() < 2 {
+        bail!("at least 2 bin edges are needed");
+    }
+    if let Some(i) =
+        (1..edges.len()).find(|&i| edges[i].partial_cmp(&edges[i - 1]) != Some(Ordering::Greater))
+    {
+        bail!(
+            "bin edges must be strictly increasing, but edges[{i}] = {} follows edges[{}] = {}",
+            edges[i],
+            i - 1,
+            edges[i - 1]
+        );
+    }
+    Ok(())
+}
+
+/// The bin of `x` among `edges`, where each bin `[edges[i], edges[i + 1])` is closed on the left
+/// and open on the right except for the last one, which is closed on both ends.
+fn bin_index(x: f64, edges: &[f64]) -> Option<usize> {
+    let last = edges.len() - 1;
+    if !(edges[0]..=edges[last]).contains(&x) {
+        return None;
+    }
+    Some((edges.partition_point(|&e| e <= x) - 1).min(last - 1))
+}
+
+/// The bin of each value among `edges`, or `-1` for `NaN` and values outside the edges. See
+/// [`bin_index`] for which bin a value on an edge belongs to.
+pub fn bin_indices(data: &[f64], edges: &[f64]) -> Result<Vec<i64>, anyhow::Error> {
+    check_edges(edges)?;
+    Ok(data
+        .iter()
+        .map(|&x| bin_index(x, edges).map_or(-1, |i| i as i64))
+        .collect())
+}
+
+/// The smallest and largest values other than `NaN`.
+fn finite_range(data: &[f64]) -> Option<(f64, f64)> {
+    data.iter()
+        .filter(|x| !x.is_nan())
+        .fold(None, |range, &x| match range {
+            None => Some((x, x)),
+            Some((lo, hi)) => Some((x.min(lo), x.max(hi))),
+        })
+}
+
+/// A histogram with `n_bins` equal bins over `[lo, hi]`. A `NaN` bound is replaced by the least
+/// or greatest value in the data. At most [`MAX_BINS`] bins are allowed.
+pub fn histogram(data: &[f64], n_bins: u64, lo: f64, hi: f64) -> Result<Histogram, anyhow::Error> {
+    if n_bins == 0 {
+        bail!("the number of bins must be positive");
+    }
+    if n_bins > MAX_BINS {
+        bail!("at most {MAX_BINS} bins are allowed, but got {n_bins}");
+    }
+    let n_bins = n_bins as usize;
+    let (mut lo, mut hi) = (lo, hi);
+    if lo.is_nan() || hi.is_nan() {
+        let Some((min, max)) = finite_range(data) else {
+            bail!("cannot infer the range of a data set without numbers");
+        };
+        if lo.is_nan() {
+            lo = min;
+        }
+        if hi.is_nan() {
+            hi = max;
+        }
+        if lo == hi {
+            // all values coincide; center a unit-width range on them
+            (lo, hi) = (lo - 0.5, hi + 0.5);
+        }
+    }
+    if !(lo.is_finite() && hi.is_finite() && lo < hi) {
+        bail!("the histogram range [{lo}, {hi}] is invalid");
+    }
+    let width = hi - lo;
+    let mut edges: Vec<f64> = (0..=n_bins)
+        .map(|i| lo + width * i as f64 / n_bins as f64)
+        .collect();
+    edges[n_bins] = hi;
+    let mut result = Histogram {
+        counts: vec![0; n_bins],
+        edges,
+        below: 0,
+        above: 0,
+        nan: 0,
+    };
+    for &x in data {
+        if x.is_nan() {
+            result.nan += 1;
+        } else if let Some(i) = bin_index(x, &result.edges) {
+            result.counts[i] += 1;
+        } else if x < lo {
+            result.below += 1;
+        } else {
+            result.above += 1;
+        }
+    }
+    Ok(result)
+}
+
+/// The `q`-quantile of sorted data, interpolating linearly between order statistics.
+fn quantile_sorted(sorted: &[f64], q: f64) -> f64 {
+    let h = (sorted.len() - 1) as f64 * q;
+    let (i, t) = (h.floor() as usize, h.fract());
+    match sorted.get(i + 1) {
+        Some(&next) => sorted[i] + t * (next - sorted[i]),
+        None => sorted[i],
+    }
+}
+
+/// A histogram over the range of the data whose bin width `2 IQR / ∛n` follows the
+/// Freedman–Diaconis rule, falling back to Sturges' `⌈log₂ n⌉ + 1` bins for data whose
+/// interquartile range is zero. Fails if the rule asks for more than [`MAX_BINS`] bins.
+pub fn histogram_auto(data: &[f64]) -> Result<Histogram, anyhow::Error> {
+    let mut sorted: Vec<f64> = data.iter().copied().filter(|x| !x.is_nan()).collect();
+    if sorted.is_empty() {
+        bail!("cannot infer the range of a data set without numbers");
+    }
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let iqr = quantile_sorted(&sorted, 0.75) - quantile_sorted(&sorted, 0.25);
+    let range = sorted[sorted.len() - 1] - sorted[0];
+    // a few far outliers can ask for more than `MAX_BINS` bins, which `histogram` rejects
+    let n_bins = if iqr > 0.0 {
+        let width = 2.0 * iqr / n.cbrt();
+        (range / width).ceil().max(1.0) as u64
+    } else {
+        n.log2().ceil() as u64 + 1
+    };
+    histogram(data, n_bins, f64::NAN, f64::NAN)
+}
+
+/// The maximum of `data` and how many times it occurs, or `NaN` if any value is `NaN`.
+fn max_with_count(data: &[f64]) -> Result<(f64, usize), anyhow::Error> {
+    if data.is_empty() {
+        bail!("the data set is empty");
+    }
+    if data.iter().any(|x| x.is_nan()) {
+        return Ok((f64::NAN, 0));
+    }
+    let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    Ok((max, data.iter().filter(|&&x| x == max).count()))
+}
+
+/// `ln Σ exp(xᵢ)`, shifted by the maximum so that no term overflows. It is `-∞` if every value
+/// is `-∞`.
+pub fn logsumexp(data: &[f64]) -> Result<f64, anyhow::Error> {
+    let (max, count) = max_with_count(data)?;
+    if max.is_infinite() {
+        return Ok(max);
+    }
+    // each maximum contributes exactly 1; leaving one of them to `ln_1p` keeps the small terms
+    let rest: f64 = data
+        .iter()
+        .filter(|&&x| x != max)
+        .map(|&x| (x - max).exp())
+        .sum();
+    Ok(max + (count as f64 - 1.0 + rest).ln_1p())
+}
+
+/// `exp(xᵢ) / Σ exp(xⱼ)`. Any `+∞` values split the whole mass equally among themselves; data
+/// that are all `-∞` have no softmax.
+pub fn softmax(data: &[f64]) -> Result<Vec<f64>, anyhow::Error> {
+    let (max, count) = max_with_count(data)?;
+    if max == f64::NEG_INFINITY {
+        bail!("the softmax of values that are all -∞ is undefined");
+    }
+    if max == f64::INFINITY {
+        let share = 1.0 / count as f64;
+        return Ok(data
+            .iter()
+            .map(|&x| if x == max { share } else { 0.0 })
+            .collect());
+    }
+    let weights: Vec<f64> = data.iter().map(|&x| (x - max).exp()).collect();
+    let total = kahan_sum(&weights);
+    Ok(weights.into_iter().map(|w| w / total).collect())
+}
+
+/// `xᵢ - ln Σ exp(xⱼ)`, the logarithm of [`softmax`] computed without underflow.
+pub fn log_softmax(data: &[f64]) -> Result<Vec<f64>, anyhow::Error> {
+    let (max, count) = max_with_count(data)?;
+    if max == f64::NEG_INFINITY {
+        bail!("the softmax of values that are all -∞ is undefined");
+    }
+    if max == f64::INFINITY {
+        let share = (1.0 / count as f64).ln();
+        return Ok(data
+            .iter()
+            .map(|&x| if x == max { share } else { f64::NEG_INFINITY })
+            .collect());
+    }
+    let lse = logsumexp(data)?;
+    Ok(data.iter().map(|&x| x - lse).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::{Rational as Mpq, base::num::basic::traits::Zero};
+
+    use super::*;
+
+    #[test]
+    fn accurate_sum_of_many_tenths() {
+        const N: usize = 10_000_000;
+        let data = vec![0.1; N];
+        let naive: f64 = data.iter().sum();
+        let exact = Mpq::try_from(0.1).unwrap() * Mpq::from(N);
+        let exact = MpqExt::from(exact).to_f64_nearest();
+        assert_ne!(naive, exact);
+        assert!((naive - 1e6).abs() > 1e-6);
+        assert_eq!(neumaier_sum(&data), exact);
+        assert_eq!(kahan_sum(&data), exact);
+    }
+
+    #[test]
+    fn exact_sums() {
+        let data = [0.1; 1000];
+        let exact = MpqExt::from(Mpq::try_from(0.1).unwrap() * Mpq::from(1000u32));
+        assert_eq!(exact_sum(&data), exact);
+        assert_eq!(exact_sum(&[1e100, 1.0, -1e100]), MpqExt::from(1u32));
+        assert_eq!(exact_sum(&[]), MpqExt::from(Mpq::ZERO));
+        // Neumaier's compensation catches the large cancelling terms that Kahan's misses
+        assert_eq!(neumaier_sum(&[1.0, 1e100, 1.0, -1e100]), 2.0);
+    }
+
+    #[test]
+    fn special_values_propagate() {
+        assert_eq!(neumaier_sum(&[1.0, f64::INFINITY]), f64::INFINITY);
+        assert!(neumaier_sum(&[f64::INFINITY, f64::NEG_INFINITY]).is_nan());
+        assert!(neumaier_sum(&[1.0, f64::NAN]).is_nan());
+        assert!(dot_accurate(&[f64::INFINITY], &[0.0]).unwrap().is_nan());
+    }
+
+    #[test]
+    fn accurate_dot_products() {
+        assert_eq!(dot_accurate(&[1e16, 1.0, -1e16], &[1.0; 3]).unwrap(), 1.0);
+        // (1 + ε)² − (1 + 2ε) = ε², which the naive dot product rounds away
+        let x = 1.0 + f64::EPSILON;
+        let (xs, ys) = ([x, -1.0], [x, 1.0 + 2.0 * f64::EPSILON]);
+        assert_eq!(xs[0] * ys[0] + xs[1] * ys[1], 0.0);
+        assert_eq!(dot_accurate(&xs, &ys).unwrap(), f64::EPSILON * f64::EPSILON);
+        assert_eq!(dot_accurate(&[x, x], &[x, -x]).unwrap(), 0.0);
+        assert!(dot_accurate(&[1.0], &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn values_on_edges() {
+        let edges = [0.0, 1.0, 2.0, 3.0];
+        let data = [
+            0.0,
+            0.5,
+            1.0,
+            2.0,
+            2.5,
+            3.0,
+            -0.0,
+            -1e-300,
+            3.000_000_000_000_001,
+            f64::NAN,
+        ];
+        // bins are closed on the left and open on the right, except the last, which is closed
+        assert_eq!(
+            bin_indices(&data, &edges).unwrap(),
+            [0, 0, 1, 2, 2, 2, 0, -1, -1, -1]
+        );
+        let histogram = histogram(&data, 3, 0.0, 3.0).unwrap();
+        assert_eq!(histogram.edges, edges);
+        assert_eq!(histogram.counts, [3, 1, 3]);
+        assert_eq!((histogram.below, histogram.above, histogram.nan), (1, 1, 1));
+    }
+
+    #[test]
+    fn inferred_ranges() {
+        let histogram = histogram(&[1.0, f64::NAN, 4.0, 2.0], 3, f64::NAN, f64::NAN).unwrap();
+        assert_eq!(histogram.edges, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(histogram.counts, [1, 1, 1]);
+        assert_eq!(histogram.nan, 1);
+        let single = super::histogram(&[5.0, 5.0], 2, f64::NAN, f64::NAN).unwrap();
+        assert_eq!(single.edges, [4.5, 5.0, 5.5]);
+        assert_eq!(single.counts, [0, 2]);
+        assert!(super::histogram(&[f64::NAN], 2, f64::NAN, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn invalid_histograms() {
+        assert!(histogram(&[], 0, 0.0, 1.0).is_err());
+        assert!(histogram(&[], MAX_BINS + 1, 0.0, 1.0).is_err());
+        assert!(histogram(&[], MAX_BINS, 0.0, 1.0).is_ok());
+        assert!(histogram(&[], 2, 1.0, 1.0).is_err());
+        assert!(histogram(&[], 2, 0.0, f64::INFINITY).is_err());
+        assert!(bin_indices(&[], &[0.0]).is_err());
+        assert!(bin_indices(&[], &[0.0, 1.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn automatic_bins() {
+        let data: Vec<f64> = (0..1000).map(|i| i as f64).collect();
+        // IQR = 499.5 gives a width of 99.9, so 10 bins
+        let histogram = histogram_auto(&data).unwrap();
+        assert_eq!(histogram.counts.len(), 10);
+        assert_eq!(histogram.counts.iter().sum::<u64>(), 1000);
+        // constant data falls back to Sturges' rule
+        assert_eq!(histogram_auto(&[2.0; 8]).unwrap().counts.len(), 4);
+        // a single far outlier would ask for about 10¹² bins
+        let mut outlier = data.clone();
+        outlier.push(1e14);
+        assert!(histogram_auto(&outlier).is_err());
+    }
+
+    /// Asserts that `actual` and `expected` agree to a relative error of `tol`.
+    fn assert_close(actual: &[f64], expected: &[f64], tol: f64) {
+        assert_eq!(actual.len(), expected.len());
+        for (&a, &e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= tol * e.abs(), "{a} differs from {e}");
+        }
+    }
+
+    const SMALL: [f64; 6] = [0.5, -1.25, 2.0, 0.0, 3.75, -0.125];
+
+    #[test]
+    fn log_space_agrees_with_naive() {
+        let exps: Vec<f64> = SMALL.iter().map(|x| x.exp()).collect();
+        let total: f64 = exps.iter().sum();
+        let naive_lse = total.ln();
+        assert_close(&[logsumexp(&SMALL).unwrap()], &[naive_lse], 1e-15);
+        let naive_softmax: Vec<f64> = exps.iter().map(|e| e / total).collect();
+        assert_close(&softmax(&SMALL).unwrap(), &naive_softmax, 1e-15);
+        let naive_log_softmax: Vec<f64> = SMALL.iter().map(|x| x - naive_lse).collect();
+        assert_close(&log_softmax(&SMALL).unwrap(), &naive_log_softmax, 1e-15);
+    }
+
+    #[test]
+    fn log_space_is_shift_stable() {
+        let shifted: Vec<f64> = SMALL.iter().map(|x| x + 1000.0).collect();
+        let lse = logsumexp(&shifted).unwrap();
+        assert!(lse.is_finite());
+        assert_close(&[lse - 1000.0], &[logsumexp(&SMALL).unwrap()], 1e-12);
+        // softmax does not see the shift at all, as x - max is exact here
+        assert_close(
+            &softmax(&shifted).unwrap(),
+            &softmax(&SMALL).unwrap(),
+            1e-15,
+        );
+        assert_close(
+            &log_softmax(&shifted).unwrap(),
+            &log_softmax(&SMALL).unwrap(),
+            1e-12,
+        );
+        // values spanning ±700 still sum to 1
+        let wide = [-700.0, 0.0, 700.0, 699.0];
+        let weights = softmax(&wide).unwrap();
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-15);
+        assert!(weights[0] == 0.0 && weights[2] > weights[3]);
+    }
+
+    #[test]
+    fn log_space_special_values() {
+        let neg_inf = [f64::NEG_INFINITY; 3];
+        assert_eq!(logsumexp(&neg_inf).unwrap(), f64::NEG_INFINITY);
+        assert!(softmax(&neg_inf).is_err());
+        assert!(log_softmax(&neg_inf).is_err());
+        assert!(logsumexp(&[]).is_err());
+        assert!(softmax(&[]).is_err());
+        // a single +∞ takes the whole mass
+        let data = [1.0, f64::INFINITY, f64::NEG_INFINITY];
+        assert_eq!(logsumexp(&data).unwrap(), f64::INFINITY);
+        assert_eq!(softmax(&data).unwrap(), [0.0, 1.0, 0.0]);
+        assert_eq!(
+            log_softmax(&data).unwrap(),
+            [f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY]
+        );
+        assert_eq!(
+            softmax(&[f64::INFINITY, 0.0, f64::INFINITY]).unwrap(),
+            [0.5, 0.0, 0.5]
+        );
+        assert!(logsumexp(&[1.0, f64::NAN]).unwrap().is_nan());
+        assert_eq!(softmax(&[f64::NEG_INFINITY, 2.0]).unwrap(), [0.0, 1.0]);
+    }
+}
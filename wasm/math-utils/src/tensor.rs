@@ -0,0 +1,180 @@
+//! Arrays of any rank passed as a shape and the elements in row-major order.
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// An array whose element count always matches its shape. Dimensions may be zero, and the empty
+/// shape holds a single element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "TensorData<T>", into = "TensorData<T>")]
+#[serde(bound(
+    serialize = "T: Serialize + Clone",
+    deserialize = "T: Deserialize<'de>"
+))]
+pub struct Tensor<T> {
+    shape: Vec<u64>,
+    data: Vec<T>,
+}
+
+/// The serialized form of [`Tensor`], which is checked on deserialization.
+#[derive(Serialize, Deserialize)]
+struct TensorData<T> {
+    shape: Vec<u64>,
+    data: Vec<T>,
+}
+
+/// The number of elements in an array of `shape`.
+fn element_count(shape: &[u64]) -> Result<usize, anyhow::Error> {
+    shape
+        .iter()
+        .try_fold(1usize, |count, &dim| {
+            usize::try_from(dim)
+                .ok()
+                .and_then(|dim| count.checked_mul(dim))
+        })
+        .ok_or_else(|| anyhow::anyhow!("a tensor of shape {shape:?} is too large"))
+}
+
+impl<T> Tensor<T> {
+    pub fn new(shape: Vec<u64>, data: Vec<T>) -> Result<Self, anyhow::Error> {
+        let count = element_count(&shape)?;
+        if data.len() != count {
+            bail!(
+                "a tensor of shape {shape:?} needs {count} elements, but got {}",
+                data.len()
+            );
+        }
+        Ok(Self { shape, data })
+    }
+
+    /// Applies `f` to every element, keeping the shape.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Tensor<U> {
+        Tensor {
+            shape: self.shape,
+            data: self.data.into_iter().map(f).collect(),
+        }
+    }
+
+    /// The slices along the last axis, in order, and the shape of the array they form.
+    fn lanes(&self) -> Result<(impl Iterator<Item = &[T]>, &[u64]), anyhow::Error> {
+        let Some((&last, outer)) = self.shape.split_last() else {
+            bail!("a tensor of rank 0 has no axis to operate along");
+        };
+        let last = last as usize;
+        let lanes = (0..element_count(outer)?).map(move |i| &self.data[i * last..(i + 1) * last]);
+        Ok((lanes, outer))
+    }
+
+    /// Replaces every slice along the last axis by `f` of it, which must be as long.
+    pub fn map_lanes<U>(
+        &self,
+        mut f: impl FnMut(&[T]) -> Result<Vec<U>, anyhow::Error>,
+    ) -> Result<Tensor<U>, anyhow::Error> {
+        let (lanes, _) = self.lanes()?;
+        let mut data = Vec::with_capacity(self.data.len());
+        for lane in lanes {
+            data.extend(f(lane)?);
+        }
+        Tensor::new(self.shape.clone(), data)
+    }
+
+    /// Reduces every slice along the last axis to `f` of it, removing the last axis.
+    pub fn reduce_lanes<U>(
+        &self,
+        f: impl FnMut(&[T]) -> Result<U, anyhow::Error>,
+    ) -> Result<Tensor<U>, anyhow::Error> {
+        let (lanes, outer) = self.lanes()?;
+        let data = lanes.map(f).collect::<Result<_, _>>()?;
+        Tensor::new(outer.to_vec(), data)
+    }
+}
+
+impl<T> TryFrom<TensorData<T>> for Tensor<T> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TensorData<T>) -> Result<Self, Self::Error> {
+        Self::new(value.shape, value.data)
+    }
+}
+
+impl<T> From<Tensor<T>> for TensorData<T> {
+    fn from(value: Tensor<T>) -> Self {
+        Self {
+            shape: value.shape,
+            data: value.data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::Integer as Mpz;
+
+    use super::*;
+    use crate::{FromWasmInput, IntoWasmOutput};
+
+    fn round_trip<T>(tensor: Tensor<T>) -> Tensor<T>
+    where
+        T: Serialize + serde::de::DeserializeOwned + Clone,
+    {
+        Tensor::from_wasm_input(&tensor.into_wasm_output()).unwrap()
+    }
+
+    #[test]
+    fn empty_matrix_round_trip() {
+        let tensor = Tensor::<f64>::new(vec![0, 5], vec![]).unwrap();
+        assert_eq!(round_trip(tensor.clone()), tensor);
+        // a reduction over each of the zero rows leaves zero elements
+        let means = tensor.reduce_lanes(|row| Ok(row.len())).unwrap();
+        assert_eq!(means, Tensor::new(vec![0], vec![]).unwrap());
+        let tensor = Tensor::<f64>::new(vec![5, 0], vec![]).unwrap();
+        let lengths = tensor.reduce_lanes(|row| Ok(row.len())).unwrap();
+        assert_eq!(lengths, Tensor::new(vec![5], vec![0; 5]).unwrap());
+    }
+
+    #[test]
+    fn rank_3_round_trip() {
+        let data: Vec<f64> = (0..24).map(|i| i as f64 / 4.0).collect();
+        let tensor = Tensor::new(vec![2, 3, 4], data.clone()).unwrap();
+        assert_eq!(round_trip(tensor.clone()), tensor);
+        // row-major: the last axis varies fastest
+        let sums = tensor
+            .reduce_lanes(|lane| Ok(lane.iter().sum::<f64>()))
+            .unwrap();
+        let expected = data.chunks(4).map(|lane| lane.iter().sum()).collect();
+        assert_eq!(sums, Tensor::new(vec![2, 3], expected).unwrap());
+        let doubled = tensor
+            .map_lanes(|lane| Ok(lane.iter().map(|x| x * 2.0).collect()))
+            .unwrap();
+        assert_eq!(doubled, tensor.clone().map(|x| x * 2.0));
+        assert!(tensor.map_lanes(|lane| Ok(lane[1..].to_vec())).is_err());
+    }
+
+    #[test]
+    fn typed_elements_round_trip() {
+        let big = Mpz::from(-3) << 100u32;
+        let tensor = Tensor::new(vec![2], vec![big, Mpz::from(7)]).unwrap();
+        assert_eq!(round_trip(tensor.clone()), tensor);
+        let scalar = Tensor::new(vec![], vec![1.5f64]).unwrap();
+        assert_eq!(round_trip(scalar.clone()), scalar);
+        assert!(scalar.reduce_lanes(|lane| Ok(lane.len())).is_err());
+    }
+
+    #[test]
+    fn shape_is_checked() {
+        let error = Tensor::new(vec![2, 3], vec![0.0; 5]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "a tensor of shape [2, 3] needs 6 elements, but got 5"
+        );
+        assert!(Tensor::<f64>::new(vec![u64::MAX, 2], vec![]).is_err());
+        let data = TensorData {
+            shape: vec![2, 2],
+            data: vec![1.0f64; 3],
+        };
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&data, &mut bytes).unwrap();
+        let error = Tensor::<f64>::from_wasm_input(&bytes).unwrap_err();
+        assert!(error.to_string().contains("needs 4 elements"), "{error}");
+    }
+}
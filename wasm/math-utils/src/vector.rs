@@ -0,0 +1,186 @@
+//! Vectors of any dimension given as slices of coordinates.
+
+use std::ops::{Mul, Sub};
+
+use anyhow::bail;
+use math_utils_base::MpqExt;
+
+use crate::stats;
+
+fn check_lengths<T>(a: &[T], b: &[T]) -> Result<(), anyhow::Error> {
+    if a.len() != b.len() {
+        bail!(
+            "vectors of lengths {} and {} do not match",
+            a.len(),
+            b.len()
+        );
+    }
+    Ok(())
+}
+
+/// The dot product, computed as if in twice the working precision.
+pub fn dot(a: &[f64], b: &[f64]) -> Result<f64, anyhow::Error> {
+    check_lengths(a, b)?;
+    stats::dot_accurate(a, b)
+}
+
+pub fn dot_exact(a: &[MpqExt], b: &[MpqExt]) -> Result<MpqExt, anyhow::Error> {
+    check_lengths(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+/// The squared Euclidean norm `a·a`, which unlike the norm itself stays rational.
+pub fn norm_sq_exact(a: &[MpqExt]) -> MpqExt {
+    a.iter().map(|x| x * x).sum()
+}
+
+pub fn cross<T>(a: &[T], b: &[T]) -> Result<Vec<T>, anyhow::Error>
+where
+    T: Sub<Output = T>,
+    for<'a> &'a T: Mul<&'a T, Output = T>,
+{
+    let ([a1, a2, a3], [b1, b2, b3]) = (a, b) else {
+        bail!(
+            "the cross product needs two 3-dimensional vectors, but got lengths {} and {}",
+            a.len(),
+            b.len()
+        );
+    };
+    Ok(vec![
+        a2 * b3 - a3 * b2,
+        a3 * b1 - a1 * b3,
+        a1 * b2 - a2 * b1,
+    ])
+}
+
+/// The `p`-norm `(Σ |aᵢ|ᵖ)^(1/p)` for `p > 0`, which is the largest magnitude for `p = ∞`. The
+/// coordinates are scaled by the largest magnitude first so that the powers cannot overflow.
+pub fn norm(a: &[f64], p: f64) -> Result<f64, anyhow::Error> {
+    if p.is_nan() || p <= 0.0 {
+        bail!("the order of a norm must be positive, but got {p}");
+    }
+    let max = a.iter().fold(0.0, |max: f64, x| max.max(x.abs()));
+    if p == f64::INFINITY || max == 0.0 || max.is_infinite() {
+        return Ok(max);
+    }
+    if a.iter().any(|x| x.is_nan()) {
+        return Ok(f64::NAN);
+    }
+    Ok(match p {
+        1.0 => stats::neumaier_sum(&a.iter().map(|x| x.abs()).collect::<Vec<_>>()),
+        2.0 => max * a.iter().map(|x| (x / max).powi(2)).sum::<f64>().sqrt(),
+        _ => {
+            max * a
+                .iter()
+                .map(|x| (x / max).abs().powf(p))
+                .sum::<f64>()
+                .powf(p.recip())
+        }
+    })
+}
+
+/// The angle between two nonzero vectors in `[0, π]`, by Kahan's formula `2 atan2(‖u - v‖,
+/// ‖u + v‖)` for the unit vectors `u` and `v` along them, which unlike `acos(u·v)` stays accurate
+/// for nearly parallel and nearly opposite vectors.
+pub fn angle(a: &[f64], b: &[f64]) -> Result<f64, anyhow::Error> {
+    check_lengths(a, b)?;
+    let (norm_a, norm_b) = (norm(a, 2.0)?, norm(b, 2.0)?);
+    if norm_a == 0.0 || norm_b == 0.0 {
+        bail!("the angle with a zero vector is undefined");
+    }
+    let (mut diff, mut sum) = (Vec::with_capacity(a.len()), Vec::with_capacity(a.len()));
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (x / norm_a, y / norm_b);
+        diff.push(x - y);
+        sum.push(x + y);
+    }
+    Ok(2.0 * norm(&diff, 2.0)?.atan2(norm(&sum, 2.0)?))
+}
+
+/// The orthogonal projection `(a·b / b·b) b` of `a` onto the line of a nonzero `b`.
+pub fn project(a: &[f64], onto: &[f64]) -> Result<Vec<f64>, anyhow::Error> {
+    check_lengths(a, onto)?;
+    let scale = norm(onto, 2.0)?;
+    if scale == 0.0 {
+        bail!("cannot project onto a zero vector");
+    }
+    // with the unit vector u = b / ‖b‖ the projection is (a·u) u, which cannot overflow in b·b
+    let unit: Vec<f64> = onto.iter().map(|x| x / scale).collect();
+    let t = dot(a, &unit)?;
+    Ok(unit.into_iter().map(|u| t * u).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use malachite::Rational as Mpq;
+
+    use super::*;
+
+    fn q(n: i64, d: i64) -> MpqExt {
+        MpqExt::from(Mpq::from_signeds(n, d))
+    }
+
+    #[test]
+    fn nearly_parallel_angles() {
+        let (a, b) = ([1.0, 0.0], [1.0, 1e-10]);
+        // the cosine rounds to 1, so the naive formula loses the angle entirely
+        let naive =
+            (dot(&a, &b).unwrap() / (norm(&a, 2.0).unwrap() * norm(&b, 2.0).unwrap())).acos();
+        assert_eq!(naive, 0.0);
+        let expected = 1e-10f64.atan();
+        assert!((angle(&a, &b).unwrap() - expected).abs() <= 1e-16 * expected);
+        let opposite = angle(&a, &[-1.0, 1e-10]).unwrap();
+        assert!((opposite - (std::f64::consts::PI - expected)).abs() <= 1e-15);
+        assert_eq!(
+            angle(&[2.0, 0.0, 0.0], &[0.0, 0.0, 5.0]).unwrap(),
+            std::f64::consts::FRAC_PI_2
+        );
+        assert_eq!(angle(&[1e300, 1e300], &[3.0, 3.0]).unwrap(), 0.0);
+        assert!(angle(&[0.0, 0.0], &[1.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn exact_cross_product() {
+        let a = [q(1, 2), q(2, 3), q(-1, 1)];
+        let b = [q(3, 1), q(-1, 4), q(5, 6)];
+        // (2/3 · 5/6 - 1/4, -3 - 1/2 · 5/6, -1/8 - 2/3 · 3)
+        let c = cross::<MpqExt>(&a, &b).unwrap();
+        assert_eq!(c, [q(11, 36), q(-41, 12), q(-17, 8)]);
+        assert_eq!(dot_exact(&c, &a).unwrap(), MpqExt::Zero(true));
+        assert_eq!(dot_exact(&c, &b).unwrap(), MpqExt::Zero(true));
+        assert_eq!(norm_sq_exact(&a), q(61, 36));
+        assert_eq!(
+            cross::<f64>(&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0]).unwrap(),
+            [0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn norms_and_projections() {
+        let a = [3.0, -4.0];
+        assert_eq!(norm(&a, 1.0).unwrap(), 7.0);
+        assert_eq!(norm(&a, 2.0).unwrap(), 5.0);
+        assert_eq!(norm(&a, f64::INFINITY).unwrap(), 4.0);
+        assert!((norm(&a, 3.0).unwrap() - 91f64.cbrt()).abs() < 1e-14);
+        // scaling keeps the squares from overflowing
+        assert_eq!(norm(&[3e300, 4e300], 2.0).unwrap(), 5e300);
+        assert!(norm(&a, 0.0).is_err());
+        assert_eq!(project(&[2.0, 3.0], &[0.0, 7.0]).unwrap(), [0.0, 3.0]);
+        assert!(project(&[1.0, 1.0], &[0.0, 0.0]).is_err());
+        assert_eq!(dot(&[1e16, 1.0, -1e16], &[1.0, 1.0, 1.0]).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn mismatched_lengths() {
+        let error = dot(&[1.0, 2.0], &[1.0]).unwrap_err();
+        assert_eq!(error.to_string(), "vectors of lengths 2 and 1 do not match");
+        assert!(angle(&[1.0], &[1.0, 2.0]).is_err());
+        assert!(project(&[1.0], &[1.0, 2.0]).is_err());
+        assert!(dot_exact(&[q(1, 2)], &[]).is_err());
+        let error = cross::<f64>(&[1.0, 2.0], &[3.0, 4.0]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the cross product needs two 3-dimensional vectors, but got lengths 2 and 2"
+        );
+    }
+}